@@ -1,3 +1,6 @@
+// pyo3 0.16 macros expand to impl blocks inside generated statics.
+#![allow(non_local_definitions)]
+
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
 
@@ -29,7 +32,7 @@ impl Ins {
     }
 
     fn __str__(&self) -> String {
-        FormattedIns(self.0.clone()).to_string()
+        FormattedIns(self.0).to_string()
     }
 
     fn fields(&self) -> Vec<(&'static str, i64)> {
@@ -0,0 +1,246 @@
+//! Assembler for single PowerPC 750CL instructions.
+//!
+//! Accepts the syntax printed by [`FormattedIns`](crate::formatter::FormattedIns),
+//! including all simplified mnemonics defined in `isa.yaml`.
+
+use core::fmt::{Display, Formatter};
+
+use crate::encode::EncodeError;
+use crate::generated::_assemble;
+use crate::inline_vec::{InlineVec, Placeholder};
+
+/// An error that can be raised while assembling an instruction.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AsmError {
+    /// The mnemonic does not name an opcode or simplified mnemonic.
+    UnknownMnemonic,
+    /// The mnemonic exists but no form of it takes this number of operands.
+    OperandCount(usize),
+    /// The operand at the given index could not be parsed.
+    InvalidOperand(usize),
+    /// An operand value could not be encoded.
+    Encode(EncodeError),
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AsmError::UnknownMnemonic => write!(f, "unknown mnemonic"),
+            AsmError::OperandCount(n) => write!(f, "invalid number of operands: {}", n),
+            AsmError::InvalidOperand(i) => write!(f, "invalid operand #{}", i + 1),
+            AsmError::Encode(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsmError {}
+
+impl From<EncodeError> for AsmError {
+    fn from(err: EncodeError) -> Self {
+        AsmError::Encode(err)
+    }
+}
+
+/// Assembles a single instruction (e.g. `lwz r3, 0x10(r31)`) into machine code.
+///
+/// Branch destinations are relative offsets, matching the disassembler output.
+pub fn assemble(text: &str) -> Result<u32, AsmError> {
+    let text = text.trim();
+    let (mnemonic, operands) = match text.split_once(char::is_whitespace) {
+        Some((mnemonic, rest)) => (mnemonic, split_operands(rest.trim())?),
+        None => (text, Operands::new()),
+    };
+    _assemble(mnemonic, &operands)
+}
+
+/// A single operand token.
+#[derive(Copy, Clone)]
+pub(crate) struct Operand<'a> {
+    text: &'a str,
+    /// Whether the operand was enclosed in parentheses, as in `0x10(r31)`.
+    indirect: bool,
+}
+
+impl Placeholder for Operand<'_> {
+    const PLACEHOLDER: Self = Operand {
+        text: "",
+        indirect: false,
+    };
+}
+
+/// Operand tokens of an instruction, more than any mnemonic takes.
+type Operands<'a> = InlineVec<Operand<'a>, 8>;
+
+fn split_operands(text: &str) -> Result<Operands<'_>, AsmError> {
+    let mut operands = Operands::new();
+    if text.is_empty() {
+        return Ok(operands);
+    }
+    let count = text.split(',').count() + text.matches('(').count();
+    if count > operands.capacity() {
+        return Err(AsmError::OperandCount(count));
+    }
+    for part in text.split(',') {
+        let part = part.trim();
+        if let Some((offset, rest)) = part.split_once('(') {
+            let base = rest
+                .strip_suffix(')')
+                .ok_or(AsmError::InvalidOperand(operands.len()))?;
+            operands.push(Operand {
+                text: offset.trim(),
+                indirect: false,
+            });
+            operands.push(Operand {
+                text: base.trim(),
+                indirect: true,
+            });
+        } else {
+            operands.push(Operand {
+                text: part,
+                indirect: false,
+            });
+        }
+    }
+    Ok(operands)
+}
+
+/// The kind of an operand, mirroring the variants of [`Argument`](crate::Argument).
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum ArgKind {
+    GPR,
+    FPR,
+    SR,
+    SPR,
+    CRField,
+    CRBit,
+    GQR,
+    Uimm,
+    Simm,
+    Offset,
+    BranchDest,
+    OpaqueU,
+}
+
+/// Parses operands against the expected kinds of a mnemonic form.
+pub(crate) fn parse_operands<const N: usize>(
+    operands: &[Operand],
+    kinds: [ArgKind; N],
+) -> Result<[i64; N], AsmError> {
+    if operands.len() != N {
+        return Err(AsmError::OperandCount(operands.len()));
+    }
+    let mut values = [0i64; N];
+    for (i, (operand, kind)) in operands.iter().zip(kinds).enumerate() {
+        let indirect = i > 0 && kinds[i - 1] == ArgKind::Offset;
+        if operand.indirect != indirect {
+            return Err(AsmError::InvalidOperand(i));
+        }
+        values[i] = parse_operand(operand.text, kind).ok_or(AsmError::InvalidOperand(i))?;
+    }
+    Ok(values)
+}
+
+fn parse_operand(text: &str, kind: ArgKind) -> Option<i64> {
+    match kind {
+        ArgKind::GPR => parse_register(text, "r", 32),
+        ArgKind::FPR => parse_register(text, "f", 32),
+        ArgKind::CRField => parse_register(text, "cr", 8),
+        ArgKind::GQR => parse_register(text, "qr", 8),
+        ArgKind::SPR => parse_spr(text),
+        ArgKind::CRBit => parse_crbit(text),
+        ArgKind::SR
+        | ArgKind::Uimm
+        | ArgKind::Simm
+        | ArgKind::Offset
+        | ArgKind::BranchDest
+        | ArgKind::OpaqueU => parse_int(text),
+    }
+}
+
+/// Parses a register number after its prefix, written in decimal without sign or leading zeros.
+pub(crate) fn parse_register(text: &str, prefix: &str, count: i64) -> Option<i64> {
+    let digits = text.strip_prefix(prefix)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) || (digits.len() > 1 && digits.starts_with('0'))
+    {
+        return None;
+    }
+    let idx = digits.parse::<i64>().ok()?;
+    (0..count).contains(&idx).then_some(idx)
+}
+
+pub(crate) fn parse_spr(text: &str) -> Option<i64> {
+    if let Some(value) = parse_int(text) {
+        return Some(value);
+    }
+    // Names of all processors are accepted.
+    (0..1024u16)
+        .find(|&n| {
+            crate::Variant::ALL.iter().any(|&variant| {
+                crate::SPR(n)
+                    .name_for(variant)
+                    .is_some_and(|name| name.eq_ignore_ascii_case(text))
+            })
+        })
+        .map(|n| n as i64)
+}
+
+pub(crate) fn parse_crbit(text: &str) -> Option<i64> {
+    const CR_NAMES: [&str; 4] = ["lt", "gt", "eq", "so"];
+    if let Some(value) = parse_int(text) {
+        return Some(value);
+    }
+    let (cr, cc) = match text.split_once('+') {
+        Some((cr, cc)) => (
+            parse_register(cr.trim().strip_prefix("4*")?, "cr", 8)?,
+            cc.trim(),
+        ),
+        None => (0, text),
+    };
+    let cc = CR_NAMES.iter().position(|&name| name == cc)? as i64;
+    Some(cr * 4 + cc)
+}
+
+pub(crate) fn parse_int(text: &str) -> Option<i64> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (digits, radix) = if let Some(hex) = text.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(bin) = text.strip_prefix("0b") {
+        (bin, 2)
+    } else {
+        (text, 10)
+    };
+    // `from_str_radix` accepts a sign of its own.
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = i64::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Keeps the most specific of two errors raised by different forms of a mnemonic.
+pub(crate) fn prefer_error(prev: Option<AsmError>, err: AsmError) -> Option<AsmError> {
+    match (prev, err) {
+        (Some(prev), AsmError::OperandCount(_)) => Some(prev),
+        (_, err) => Some(err),
+    }
+}
+
+/// Matches a mnemonic against a base name followed by optional modifier suffixes.
+///
+/// Returns a bitset of the suffixes present, indexed by their position in `suffixes`.
+pub(crate) fn parse_mnemonic(mnemonic: &str, name: &str, suffixes: &[char]) -> Option<u8> {
+    let mut rest = mnemonic.strip_prefix(name)?;
+    let mut set = 0u8;
+    for (i, suffix) in suffixes.iter().enumerate() {
+        if let Some(s) = rest.strip_prefix(*suffix) {
+            rest = s;
+            set |= 1 << i;
+        }
+    }
+    rest.is_empty().then_some(set)
+}
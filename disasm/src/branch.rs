@@ -0,0 +1,128 @@
+//! Decoded branch semantics, including the `BO` field of conditional branches.
+
+use crate::prelude::*;
+use crate::Opcode;
+
+/// Where a branch takes its target from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BranchKind {
+    /// Target encoded in the instruction (`b`, `bc`).
+    Direct,
+    /// Target in the link register (`bclr`).
+    Lr,
+    /// Target in the count register (`bcctr`).
+    Ctr,
+}
+
+/// Test of the count register after it is decremented.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CtrTest {
+    /// Branch if the count register is zero (`bdz`).
+    Zero,
+    /// Branch if the count register is not zero (`bdnz`).
+    NonZero,
+}
+
+/// Test of a condition register bit.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CrTest {
+    pub bit: CRBit,
+    /// Value of the bit for which the branch is taken.
+    pub value: bool,
+}
+
+impl CrTest {
+    /// Returns the condition register field containing the tested bit.
+    pub fn field(&self) -> CRField {
+        CRField(self.bit.0 >> 2)
+    }
+}
+
+/// Decoded semantics of a branch instruction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BranchInfo {
+    pub kind: BranchKind,
+    /// Whether the return address is saved to the link register.
+    pub link: bool,
+    /// Absolute target, known for direct branches only.
+    pub target: Option<u32>,
+    /// Decrement of the count register and its test, if any.
+    pub ctr: Option<CtrTest>,
+    /// Test of a condition register bit, if any.
+    pub cr: Option<CrTest>,
+    /// Whether the `y` bit of `BO` reverses the static prediction.
+    pub hint: bool,
+    /// Whether the branch is statically predicted taken.
+    pub predict_taken: bool,
+}
+
+impl BranchInfo {
+    /// Returns whether the branch is always taken.
+    pub fn is_unconditional(&self) -> bool {
+        self.ctr.is_none() && self.cr.is_none()
+    }
+
+    /// Returns whether the count register is decremented.
+    pub fn decrements_ctr(&self) -> bool {
+        self.ctr.is_some()
+    }
+}
+
+impl Ins {
+    /// Decodes the kind, target and condition of a branch.
+    ///
+    /// Returns `None` for instructions other than `b`, `bc`, `bclr` and `bcctr`.
+    pub fn branch_info(&self) -> Option<BranchInfo> {
+        let kind = match self.op {
+            Opcode::B | Opcode::Bc => BranchKind::Direct,
+            Opcode::Bclr => BranchKind::Lr,
+            Opcode::Bcctr => BranchKind::Ctr,
+            _ => return None,
+        };
+        let link = self.field_LK();
+        let target = self.branch_dest();
+        if self.op == Opcode::B {
+            return Some(BranchInfo {
+                kind,
+                link,
+                target,
+                ctr: None,
+                cr: None,
+                hint: false,
+                predict_taken: true,
+            });
+        }
+        let bo = self.field_BO();
+        let ctr_test = if bo & 0b00100 != 0 {
+            None
+        } else if bo & 0b00010 != 0 {
+            Some(CtrTest::Zero)
+        } else {
+            Some(CtrTest::NonZero)
+        };
+        let cr_test = if bo & 0b10000 != 0 {
+            None
+        } else {
+            Some(CrTest {
+                bit: CRBit(self.field_BI() as u8),
+                value: bo & 0b01000 != 0,
+            })
+        };
+        let hint = bo & 0b00001 != 0;
+        // Without the hint, only backward direct branches are predicted taken.
+        let predict_taken = if ctr_test.is_none() && cr_test.is_none() {
+            true
+        } else {
+            (self.op == Opcode::Bc && self.field_BD() < 0) != hint
+        };
+        Some(BranchInfo {
+            kind,
+            link,
+            target,
+            ctr: ctr_test,
+            cr: cr_test,
+            hint,
+            predict_taken,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+//! Encoder for instructions composed from an opcode and field values.
+
+use core::fmt::{Display, Formatter};
+
+use crate::{Field, Modifier};
+
+/// An error that can be raised while encoding an instruction.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EncodeError {
+    /// The illegal opcode has no encoding.
+    IllegalOpcode,
+    /// A field value does not fit into its bit range.
+    OutOfRange { field: &'static str, value: i64 },
+    /// A field required by the opcode was not given.
+    MissingField(&'static str),
+    /// A field was given that the opcode does not take, or was given twice.
+    UnexpectedField(&'static str),
+    /// The opcode does not support the modifier.
+    InvalidModifier(Modifier),
+    /// The register field must be non-zero, as in the update forms of loads and stores.
+    ZeroRegister(&'static str),
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EncodeError::IllegalOpcode => write!(f, "illegal opcode"),
+            EncodeError::OutOfRange { field, value } => {
+                write!(f, "value {} out of range for field {}", value, field)
+            }
+            EncodeError::MissingField(field) => write!(f, "missing field {}", field),
+            EncodeError::UnexpectedField(field) => write!(f, "unexpected field {}", field),
+            EncodeError::InvalidModifier(modifier) => write!(f, "invalid modifier {:?}", modifier),
+            EncodeError::ZeroRegister(field) => write!(f, "field {} must not be zero", field),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+/// Inserts field values into the given pattern, checking them against the arguments of an opcode.
+///
+/// Fields may be given in any order, but each argument must be given exactly once.
+pub(crate) fn encode_fields<const N: usize>(
+    mut code: u32,
+    fields: &[Field],
+    args: [FieldBits; N],
+) -> Result<u32, EncodeError> {
+    let mut seen = [false; N];
+    for field in fields {
+        let unexpected = EncodeError::UnexpectedField(field.name());
+        let bits = field.field_bits().ok_or(unexpected.clone())?;
+        let idx = args
+            .iter()
+            .position(|arg| arg.name == bits.name)
+            .filter(|&idx| !seen[idx])
+            .ok_or(unexpected)?;
+        seen[idx] = true;
+        let value = field.argument().map(i64::from).unwrap_or_default();
+        code = bits.insert(code, value)?;
+    }
+    match seen.iter().position(|&seen| !seen) {
+        Some(idx) => Err(EncodeError::MissingField(args[idx].name)),
+        None => Ok(code),
+    }
+}
+
+/// Bit layout of an instruction field.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct FieldBits {
+    pub(crate) name: &'static str,
+    pub(crate) start: u8,
+    pub(crate) end: u8,
+    pub(crate) signed: bool,
+    pub(crate) split: bool,
+    pub(crate) shift_left: u8,
+}
+
+impl FieldBits {
+    fn len(self) -> u32 {
+        (self.end - self.start) as u32
+    }
+
+    fn mask(self) -> u32 {
+        ((1u64 << self.len()) - 1) as u32
+    }
+
+    fn swap_halves(self, raw: u32) -> u32 {
+        if self.split {
+            ((raw & 0b11111_00000) >> 5) | ((raw & 0b00000_11111) << 5)
+        } else {
+            raw
+        }
+    }
+
+    /// Reads the field value from the given machine code.
+    pub(crate) fn extract(self, code: u32) -> i64 {
+        let raw = self.swap_halves((code >> (32 - self.end as u32)) & self.mask());
+        let value = if self.signed {
+            let sign = 1u32 << (self.len() - 1);
+            ((raw ^ sign).wrapping_sub(sign)) as i32 as i64
+        } else {
+            raw as i64
+        };
+        value << self.shift_left
+    }
+
+    /// Writes the field value into the given machine code, checking its range.
+    pub(crate) fn insert(self, code: u32, value: i64) -> Result<u32, EncodeError> {
+        let out_of_range = EncodeError::OutOfRange {
+            field: self.name,
+            value,
+        };
+        if value & ((1 << self.shift_left) - 1) != 0 {
+            return Err(out_of_range);
+        }
+        let shifted = value >> self.shift_left;
+        let range = if self.signed {
+            -(1i64 << (self.len() - 1))..(1i64 << (self.len() - 1))
+        } else {
+            0..(1i64 << self.len())
+        };
+        if !range.contains(&shifted) {
+            return Err(out_of_range);
+        }
+        Ok(self.insert_masked(code, self.mask(), shifted as u32))
+    }
+
+    /// Writes the masked bits of a field value into the given machine code.
+    pub(crate) fn insert_masked(self, code: u32, mask: u32, value: u32) -> u32 {
+        let shift = 32 - self.end as u32;
+        let mask = self.swap_halves(mask & self.mask()) << shift;
+        let value = self.swap_halves(value & self.mask()) << shift;
+        (code & !mask) | (value & mask)
+    }
+}
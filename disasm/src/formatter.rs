@@ -1,30 +1,273 @@
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter, Write};
 
 use crate::prelude::*;
+#[cfg(feature = "alloc")]
+use crate::symbols::{Half, SymbolResolver};
 
+/// An instruction displayed with the default format options.
 pub struct FormattedIns(pub Ins);
 
 impl Display for FormattedIns {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let simple = self.0.clone().simplified();
-        write!(f, "{}{}", simple.mnemonic, simple.suffix)?;
-        let mut writing_offset = false;
-        for (i, arg) in simple.args.iter().enumerate() {
-            if i == 0 {
-                write!(f, " ")?;
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        FormatOptions::default().display(self.0.simplified()).fmt(f)
+    }
+}
+
+/// Syntax of register operands.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RegisterStyle {
+    /// Registers prefixed by their kind, as in `r3`, `f1` or `cr7`.
+    Named,
+    /// Named registers with a leading `%`, as in `%r3` (GNU `as`).
+    Percent,
+    /// Plain register numbers, as in `3`.
+    Number,
+}
+
+/// Syntax of immediate operands.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ImmediateStyle {
+    /// Signed hexadecimal, as in `-0x10`.
+    Hex,
+    /// Signed decimal, as in `-16`.
+    Decimal,
+}
+
+/// Options controlling the syntax of formatted instructions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FormatOptions {
+    /// Syntax of register operands.
+    pub registers: RegisterStyle,
+    /// Whether special-purpose registers are printed by name (`HID2`) instead of number.
+    pub spr_names: bool,
+    /// Processor whose special-purpose register names are used.
+    pub variant: Variant,
+    /// Syntax of immediate operands.
+    pub immediates: ImmediateStyle,
+    /// Whether mnemonics are printed in uppercase.
+    pub uppercase: bool,
+    /// Whether simplified mnemonics (`blr`, `li`, ...) are used.
+    pub simplified: bool,
+    /// Column at which operands start, zero separates them by a single space.
+    pub operand_column: usize,
+    /// Whether branch destinations are printed as absolute hex addresses instead of offsets.
+    pub absolute_branches: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            registers: RegisterStyle::Named,
+            spr_names: true,
+            variant: Variant::default(),
+            immediates: ImmediateStyle::Hex,
+            uppercase: false,
+            simplified: true,
+            operand_column: 0,
+            absolute_branches: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Options matching the syntax of GNU `as` and objdump.
+    pub fn gnu() -> Self {
+        Self {
+            registers: RegisterStyle::Percent,
+            spr_names: false,
+            immediates: ImmediateStyle::Decimal,
+            absolute_branches: true,
+            ..Self::default()
+        }
+    }
+
+    /// Displays an instruction with these options.
+    ///
+    /// If simplified mnemonics are disabled, the basic form of the instruction is used.
+    pub fn display(&self, ins: SimplifiedIns) -> StyledIns<'_> {
+        StyledIns {
+            ins,
+            options: self,
+            #[cfg(feature = "alloc")]
+            symbols: None,
+        }
+    }
+
+    fn write_mnemonic(&self, f: &mut Formatter<'_>, ins: &SimplifiedIns) -> core::fmt::Result {
+        let mut len = 0;
+        for c in ins
+            .mnemonic
+            .chars()
+            .chain(ins.suffix.iter().map(|m| m.suffix()))
+        {
+            f.write_char(if self.uppercase {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            })?;
+            len += 1;
+        }
+        if !ins.args.is_empty() {
+            for _ in len + 1..self.operand_column {
+                f.write_char(' ')?;
+            }
+            f.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn write_register(&self, f: &mut Formatter<'_>, prefix: &str, num: u8) -> core::fmt::Result {
+        match self.registers {
+            RegisterStyle::Named => write!(f, "{}{}", prefix, num),
+            RegisterStyle::Percent => write!(f, "%{}{}", prefix, num),
+            RegisterStyle::Number => write!(f, "{}", num),
+        }
+    }
+
+    fn write_immediate<T>(&self, f: &mut Formatter<'_>, arg: T, value: i64) -> core::fmt::Result
+    where
+        T: Display,
+    {
+        match self.immediates {
+            ImmediateStyle::Hex => arg.fmt(f),
+            ImmediateStyle::Decimal => write!(f, "{}", value),
+        }
+    }
+
+    /// Writes a single operand.
+    pub fn write_argument(&self, f: &mut Formatter<'_>, arg: &Argument) -> core::fmt::Result {
+        match *arg {
+            Argument::GPR(x) => self.write_register(f, "r", x.0),
+            Argument::FPR(x) => self.write_register(f, "f", x.0),
+            Argument::CRField(x) => self.write_register(f, "cr", x.0),
+            Argument::GQR(x) => self.write_register(f, "qr", x.0),
+            Argument::CRBit(x) if self.registers == RegisterStyle::Number => write!(f, "{}", x.0),
+            Argument::CRBit(x) => {
+                const CR_NAMES: [&str; 4] = ["lt", "gt", "eq", "so"];
+                if x.0 >> 2 != 0 {
+                    f.write_str("4*")?;
+                    self.write_register(f, "cr", x.0 >> 2)?;
+                    f.write_char('+')?;
+                }
+                f.write_str(CR_NAMES[(x.0 & 3) as usize])
             }
+            Argument::SPR(x) => match x.name_for(self.variant) {
+                Some(name) if self.spr_names => f.write_str(name),
+                _ => write!(f, "{}", x.0),
+            },
+            Argument::Uimm(x) => self.write_immediate(f, x, x.0 as i64),
+            Argument::Simm(x) => self.write_immediate(f, x, x.0 as i64),
+            Argument::Offset(x) => self.write_immediate(f, x, x.0 as i64),
+            Argument::BranchDest(x) => self.write_immediate(f, x, x.0 as i64),
+            Argument::SR(_) | Argument::Bit(_) | Argument::OpaqueU(_) => arg.fmt(f),
+        }
+    }
+
+    /// Writes a symbol reference such as `func+0x1c`.
+    #[cfg(feature = "alloc")]
+    fn write_symbol(&self, f: &mut Formatter<'_>, name: &str, off: u32) -> core::fmt::Result {
+        f.write_str(name)?;
+        match (off, self.immediates) {
+            (0, _) => Ok(()),
+            (_, ImmediateStyle::Hex) => write!(f, "+{:#x}", off),
+            (_, ImmediateStyle::Decimal) => write!(f, "+{}", off),
+        }
+    }
+}
+
+/// An instruction displayed with the given format options.
+pub struct StyledIns<'a> {
+    pub ins: SimplifiedIns,
+    pub options: &'a FormatOptions,
+    /// Symbols for branch targets and address halves.
+    #[cfg(feature = "alloc")]
+    pub symbols: Option<&'a dyn SymbolResolver>,
+}
+
+impl<'a> StyledIns<'a> {
+    /// Renders branch targets and address halves using the given symbols.
+    #[cfg(feature = "alloc")]
+    pub fn with_symbols(self, symbols: &'a dyn SymbolResolver) -> Self {
+        Self {
+            symbols: Some(symbols),
+            ..self
+        }
+    }
+
+    /// Writes an operand, replacing addresses by symbols where known.
+    ///
+    /// Branch destinations are printed as absolute addresses if enabled.
+    fn write_argument(
+        &self,
+        f: &mut Formatter<'_>,
+        arg: &Argument,
+        #[cfg(feature = "alloc")] half: &mut Option<(u32, Half)>,
+    ) -> core::fmt::Result {
+        #[cfg(feature = "alloc")]
+        if let Some(symbols) = self.symbols {
+            let addr = match arg {
+                Argument::BranchDest(_) => self.ins.ins.branch_dest(),
+                Argument::Uimm(_) | Argument::Simm(_) | Argument::Offset(_) => {
+                    half.map(|(addr, _)| addr)
+                }
+                _ => None,
+            };
+            if let Some((name, off)) = addr.and_then(|addr| symbols.resolve(addr)) {
+                self.options.write_symbol(f, &name, off)?;
+                if let Some((_, half)) = half
+                    .take()
+                    .filter(|_| !matches!(arg, Argument::BranchDest(_)))
+                {
+                    f.write_str(half.suffix())?;
+                }
+                return Ok(());
+            }
+        }
+        if let (Argument::BranchDest(_), true) = (arg, self.options.absolute_branches) {
+            if let Some(dest) = self.ins.ins.branch_dest() {
+                return write!(f, "{:#x}", dest);
+            }
+        }
+        self.options.write_argument(f, arg)
+    }
+}
+
+impl Display for StyledIns<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        // Address half of the first immediate operand.
+        #[cfg(feature = "alloc")]
+        let mut half = self
+            .symbols
+            .and_then(|symbols| symbols.resolve_half(self.ins.ins.addr));
+        // Symbols are only supported with `alloc`.
+        #[cfg(not(feature = "alloc"))]
+        let half: Option<()> = None;
+        // Mnemonics such as `subi` negate the immediate, which a symbol cannot express.
+        let negated = matches!(self.ins.mnemonic, "subi" | "subis" | "subic" | "subic.");
+        let ins = if self.options.simplified && !(negated && half.is_some()) {
+            self.ins
+        } else {
+            SimplifiedIns::basic_form(self.ins.ins)
+        };
+        self.options.write_mnemonic(f, &ins)?;
+        let mut writing_offset = false;
+        for (i, arg) in ins.args.iter().enumerate() {
             if i > 0 && !writing_offset {
-                write!(f, ", ")?;
+                f.write_str(", ")?;
             }
-            if let Argument::Offset(val) = arg {
-                write!(f, "{}(", val)?;
+            self.write_argument(
+                f,
+                arg,
+                #[cfg(feature = "alloc")]
+                &mut half,
+            )?;
+            if let Argument::Offset(_) = arg {
+                f.write_char('(')?;
                 writing_offset = true;
                 continue;
-            } else {
-                write!(f, "{}", arg)?;
             }
             if writing_offset {
-                write!(f, ")")?;
+                f.write_char(')')?;
                 writing_offset = false;
             }
         }
@@ -0,0 +1,131 @@
+//! Fixed-capacity list stored inline, used to return instruction data without allocating.
+
+use core::fmt::{Debug, Formatter};
+use core::ops::Deref;
+
+/// A value filling the unused slots of an [`InlineVec`].
+///
+/// It is never observable through the list.
+pub trait Placeholder: Copy {
+    const PLACEHOLDER: Self;
+}
+
+/// A list of up to `N` items stored inline.
+///
+/// Dereferences to a slice of the items. Pushing more than `N` items panics.
+#[derive(Copy, Clone)]
+pub struct InlineVec<T: Placeholder, const N: usize> {
+    items: [T; N],
+    len: u8,
+}
+
+impl<T: Placeholder, const N: usize> InlineVec<T, N> {
+    /// Constructs an empty list.
+    pub const fn new() -> Self {
+        Self {
+            items: [T::PLACEHOLDER; N],
+            len: 0,
+        }
+    }
+
+    /// Constructs a list from the given items.
+    pub fn from_slice(items: &[T]) -> Self {
+        let mut vec = Self::new();
+        vec.items[..items.len()].copy_from_slice(items);
+        vec.len = items.len() as u8;
+        vec
+    }
+
+    /// Appends an item to the end of the list.
+    pub fn push(&mut self, item: T) {
+        self.items[self.len as usize] = item;
+        self.len += 1;
+    }
+
+    /// Returns the maximum number of items.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the items as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items[..self.len as usize]
+    }
+}
+
+impl<T: Placeholder, const N: usize> Default for InlineVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Placeholder, const N: usize> Deref for InlineVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: Placeholder + Debug, const N: usize> Debug for InlineVec<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Placeholder, const N: usize> FromIterator<T> for InlineVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        for item in iter {
+            vec.push(item);
+        }
+        vec
+    }
+}
+
+impl<T: Placeholder + Eq, const N: usize> Eq for InlineVec<T, N> {}
+
+impl<T: Placeholder + PartialEq, const N: usize> PartialEq for InlineVec<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Placeholder + PartialEq, const N: usize> PartialEq<[T]> for InlineVec<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Placeholder + PartialEq, const N: usize, const M: usize> PartialEq<[T; M]>
+    for InlineVec<T, N>
+{
+    fn eq(&self, other: &[T; M]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Placeholder + PartialEq, const N: usize> PartialEq<alloc::vec::Vec<T>> for InlineVec<T, N> {
+    fn eq(&self, other: &alloc::vec::Vec<T>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<'a, T: Placeholder, const N: usize> IntoIterator for &'a InlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Placeholder, const N: usize> IntoIterator for InlineVec<T, N> {
+    type Item = T;
+    type IntoIter = core::iter::Take<core::array::IntoIter<T, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().take(self.len as usize)
+    }
+}
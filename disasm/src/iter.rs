@@ -1,13 +1,70 @@
-use crate::Ins;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Ins, Variant};
 
 /// Returns an iterator of instructions in the given byte slice.
-pub fn disasm_iter(code: &[u8], addr: u32) -> DisasmIterator {
-    DisasmIterator { code, addr }
+pub fn disasm_iter(code: &[u8], addr: u32) -> DisasmIterator<'_> {
+    DisasmIterator {
+        code,
+        addr,
+        variant: Variant::default(),
+    }
+}
+
+/// Disassembles the given byte slice across threads, in order.
+#[cfg(feature = "rayon")]
+pub fn disasm_par(code: &[u8], addr: u32) -> Vec<Ins> {
+    disasm_par_for(code, addr, Variant::default())
+}
+
+/// Disassembles the given byte slice across threads for the given processor, see [`Ins::new_for`].
+#[cfg(feature = "rayon")]
+pub fn disasm_par_for(code: &[u8], addr: u32, variant: Variant) -> Vec<Ins> {
+    code.par_chunks_exact(4)
+        .enumerate()
+        .map(|(i, word)| {
+            let code = u32::from_be_bytes(word.try_into().unwrap());
+            Ins::new_for(code, addr + (i as u32) * 4, variant)
+        })
+        .collect()
+}
+
+/// Splits the given byte slice into chunks of `chunk_len` instructions, decoded across threads
+/// for the given processor.
+///
+/// Chunks keep the order of the slice, so that collecting them preserves it.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is zero.
+#[cfg(feature = "rayon")]
+pub fn disasm_par_chunks(
+    code: &[u8],
+    addr: u32,
+    chunk_len: usize,
+    variant: Variant,
+) -> impl IndexedParallelIterator<Item = DisasmIterator<'_>> {
+    assert_ne!(chunk_len, 0);
+    code.par_chunks(chunk_len * 4)
+        .enumerate()
+        .map(move |(i, chunk)| {
+            disasm_iter(chunk, addr + (i * chunk_len * 4) as u32).variant(variant)
+        })
 }
 
 pub struct DisasmIterator<'a> {
     code: &'a [u8],
     addr: u32,
+    variant: Variant,
+}
+
+impl<'a> DisasmIterator<'a> {
+    /// Decodes instructions for the given processor, see [`Ins::new_for`].
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
 }
 
 impl<'a> Iterator for DisasmIterator<'a> {
@@ -24,7 +81,7 @@ impl<'a> Iterator for DisasmIterator<'a> {
         self.code = &self.code[4..];
         let addr = self.addr;
         self.addr += 4;
-        Some(Ins::new(code, addr))
+        Some(Ins::new_for(code, addr, self.variant))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
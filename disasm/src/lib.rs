@@ -1,13 +1,39 @@
-use std::fmt::{Display, Formatter, LowerHex, UpperHex, Write};
-use std::ops::Range;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::fmt::{Display, Formatter, LowerHex, UpperHex, Write};
+use core::ops::Range;
 
 use num_traits::{AsPrimitive, PrimInt};
 
+pub use crate::branch::{BranchInfo, BranchKind, CrTest, CtrTest};
+use crate::encode::EncodeError;
+use crate::formatter::FormatOptions;
+pub use crate::inline_vec::InlineVec;
+use crate::inline_vec::Placeholder;
 pub use crate::iter::{disasm_iter, DisasmIterator};
-
+#[cfg(feature = "rayon")]
+pub use crate::iter::{disasm_par, disasm_par_chunks, disasm_par_for};
+pub use crate::memory::{MemAccess, MemData, MemKind, MemOffset};
+pub use crate::parse::ParseError;
+pub use crate::registers::{Register, Registers};
+
+pub mod asm;
+pub mod branch;
+pub mod encode;
 pub mod formatter;
 mod generated;
+pub mod inline_vec;
 mod iter;
+pub mod memory;
+mod parse;
+pub mod registers;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "alloc")]
+pub mod symbols;
 pub use generated::*;
 
 pub mod prelude {
@@ -15,18 +41,25 @@ pub mod prelude {
     pub use crate::Argument;
     pub use crate::Field::*;
     pub use crate::Ins;
+    pub use crate::Modifier;
+    pub use crate::Modifiers;
     pub use crate::Opcode::*;
     pub use crate::SimplifiedIns;
+    pub use crate::{Access, Accesses, Arguments, Fields, InlineVec, Register, Registers};
     pub use crate::{
         Bit, BranchDest, CRBit, CRField, Offset, OpaqueU, Simm, Uimm, FPR, GPR, GQR, SPR, SR,
     };
+    pub use crate::{BranchInfo, BranchKind, CrTest, CtrTest};
+    pub use crate::{Category, Form, Validity, Variant};
+    pub use crate::{MemAccess, MemData, MemKind, MemOffset};
 }
 
 macro_rules! field_arg_no_display {
     ($name:ident, $typ:ident) => {
-        #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+        #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name(pub $typ);
-        impl std::convert::From<$name> for Argument {
+        impl core::convert::From<$name> for Argument {
             fn from(x: $name) -> Argument {
                 Argument::$name(x)
             }
@@ -38,7 +71,7 @@ macro_rules! field_arg {
     ($name:ident, $typ:ident) => {
         field_arg_no_display!($name, $typ);
         impl Display for $name {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
                 write!(f, "{}", self.0)
             }
         }
@@ -46,7 +79,7 @@ macro_rules! field_arg {
     ($name:ident, $typ:ident, $format:literal) => {
         field_arg_no_display!($name, $typ);
         impl Display for $name {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
                 write!(f, $format, self.0)
             }
         }
@@ -54,7 +87,7 @@ macro_rules! field_arg {
     ($name:ident, $typ:ident, $format:literal, $format_arg:expr) => {
         field_arg_no_display!($name, $typ);
         impl Display for $name {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
                 write!(f, $format, $format_arg(self.0))
             }
         }
@@ -69,7 +102,7 @@ fn bit(x: u32, idx: usize) -> bool {
 #[inline(always)]
 fn bits<F>(x: u32, range: Range<usize>) -> F
 where
-    F: 'static + std::marker::Copy,
+    F: 'static + core::marker::Copy,
     u32: AsPrimitive<F>,
 {
     let masked: u32 = (x >> (32 - range.end)) & ((1 << range.len()) - 1);
@@ -79,21 +112,36 @@ where
 // https://stackoverflow.com/questions/44711012/how-do-i-format-a-signed-integer-to-a-sign-aware-hexadecimal-representation
 struct ReallySigned<N: PrimInt>(N);
 
-impl<N: PrimInt> LowerHex for ReallySigned<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl<N: PrimInt> ReallySigned<N> {
+    /// Pads the hex digits of the magnitude, without allocating.
+    fn fmt_hex(&self, f: &mut Formatter<'_>, digits: &[u8; 16]) -> core::fmt::Result {
         let num = self.0.to_i32().unwrap();
         let prefix = if f.alternate() { "0x" } else { "" };
-        let bare_hex = format!("{:x}", num.abs());
-        f.pad_integral(num >= 0, prefix, &bare_hex)
+        let mut buf = [0u8; 8];
+        let mut pos = buf.len();
+        let mut abs = num.unsigned_abs();
+        loop {
+            pos -= 1;
+            buf[pos] = digits[(abs & 0xf) as usize];
+            abs >>= 4;
+            if abs == 0 {
+                break;
+            }
+        }
+        let bare_hex = core::str::from_utf8(&buf[pos..]).unwrap();
+        f.pad_integral(num >= 0, prefix, bare_hex)
+    }
+}
+
+impl<N: PrimInt> LowerHex for ReallySigned<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.fmt_hex(f, b"0123456789abcdef")
     }
 }
 
 impl<N: PrimInt> UpperHex for ReallySigned<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let num = self.0.to_i32().unwrap();
-        let prefix = if f.alternate() { "0x" } else { "" };
-        let bare_hex = format!("{:X}", num.abs());
-        f.pad_integral(num >= 0, prefix, &bare_hex)
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.fmt_hex(f, b"0123456789ABCDEF")
     }
 }
 
@@ -105,79 +153,24 @@ field_arg!(FPR, u8, "f{}");
 field_arg!(SR, u8);
 // Special-purpose register.
 field_arg_no_display!(SPR, u16);
+impl SPR {
+    /// Returns the name of a special-purpose register on the 750CL, such as `HID2`.
+    pub fn name(self) -> Option<&'static str> {
+        self._name(Variant::Broadway) // auto-generated
+    }
+
+    /// Returns the name of a special-purpose register on the given processor.
+    pub fn name_for(self, variant: Variant) -> Option<&'static str> {
+        self._name(variant) // auto-generated
+    }
+}
+
 impl Display for SPR {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self.0 {
-            1 => "XER",
-            8 => "LR",
-            9 => "CTR",
-            18 => "DSISR",
-            19 => "DAR",
-            22 => "DEC",
-            25 => "SDR1",
-            26 => "SRR0",
-            27 => "SRR1",
-            272 => "SPRG0",
-            273 => "SPRG1",
-            274 => "SPRG2",
-            275 => "SPRG3",
-            282 => "EAR",
-            287 => "PVR",
-            528 => "IBAT0U",
-            529 => "IBAT0L",
-            530 => "IBAT1U",
-            531 => "IBAT1L",
-            532 => "IBAT2U",
-            533 => "IBAT2L",
-            534 => "IBAT3U",
-            535 => "IBAT3L",
-            536 => "DBAT0U",
-            537 => "DBAT0L",
-            538 => "DBAT1U",
-            539 => "DBAT1L",
-            540 => "DBAT2U",
-            541 => "DBAT2L",
-            542 => "DBAT3U",
-            543 => "DBAT3L",
-            912 => "GQR0",
-            913 => "GQR1",
-            914 => "GQR2",
-            915 => "GQR3",
-            916 => "GQR4",
-            917 => "GQR5",
-            918 => "GQR6",
-            919 => "GQR7",
-            920 => "HID2",
-            921 => "WPAR",
-            922 => "DMA_U",
-            923 => "DMA_L",
-            936 => "UMMCR0",
-            937 => "UPMC1",
-            938 => "UPMC2",
-            939 => "USIA",
-            940 => "UMMCR1",
-            941 => "UPMC3",
-            942 => "UPMC4",
-            943 => "USDA",
-            952 => "MMCR0",
-            953 => "PMC1",
-            954 => "PMC2",
-            955 => "SIA",
-            956 => "MMCR1",
-            957 => "PMC3",
-            958 => "PMC4",
-            959 => "SDA",
-            1008 => "HID0",
-            1009 => "HID1",
-            1010 => "IABR",
-            1013 => "DABR",
-            1017 => "L2CR",
-            1019 => "ICTC",
-            1020 => "THRM1",
-            1021 => "THRM2",
-            1022 => "THRM3",
-            _ => return write!(f, "{}", self.0),
-        })
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0),
+        }
     }
 }
 // Condition register field.
@@ -185,7 +178,7 @@ field_arg!(CRField, u8, "cr{}");
 // Condition register bit (index + condition case).
 field_arg_no_display!(CRBit, u8);
 impl Display for CRBit {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let cr = self.0 >> 2;
         let cc = self.0 & 3;
         if cr != 0 {
@@ -208,14 +201,15 @@ field_arg!(BranchDest, i32, "{:#x}", ReallySigned);
 // Opaque zero or one argument.
 field_arg_no_display!(Bit, bool);
 impl Display for Bit {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_char(if self.0 { '1' } else { '0' })
     }
 }
 // Unsigned opaque argument.
 field_arg!(OpaqueU, u32);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Argument {
     GPR(GPR),
     FPR(FPR),
@@ -232,8 +226,12 @@ pub enum Argument {
     OpaqueU(OpaqueU),
 }
 
+impl Placeholder for Argument {
+    const PLACEHOLDER: Self = Argument::OpaqueU(OpaqueU(0));
+}
+
 impl Display for Argument {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             Argument::GPR(x) => x.fmt(f),
             Argument::FPR(x) => x.fmt(f),
@@ -272,6 +270,10 @@ impl From<Argument> for i64 {
     }
 }
 
+impl Placeholder for Field {
+    const PLACEHOLDER: Self = Field::simm(Simm(0));
+}
+
 impl TryInto<Argument> for &Field {
     type Error = ();
     fn try_into(self) -> Result<Argument, Self::Error> {
@@ -289,23 +291,218 @@ impl Opcode {
     pub fn mnemonic(self) -> &'static str {
         self._mnemonic() // auto-generated
     }
+
+    /// Returns the name of an opcode, such as `Add Immediate`.
+    pub fn description(self) -> &'static str {
+        self._description() // auto-generated
+    }
+
+    /// Returns the mask of the bits that identify an opcode.
+    ///
+    /// Zero for illegal instructions.
+    pub fn bitmask(self) -> u32 {
+        self._bitmask() // auto-generated
+    }
+
+    /// Returns the value of the bits that identify an opcode, under its bitmask.
+    pub fn pattern(self) -> u32 {
+        self._pattern() // auto-generated
+    }
+
+    /// Detects the opcode of an instruction on the given processor.
+    ///
+    /// Opcodes that the processor does not implement are illegal.
+    pub fn detect_for(code: u32, variant: Variant) -> Self {
+        let op = Self::detect(code);
+        if op.is_available(variant) {
+            op
+        } else {
+            Opcode::Illegal
+        }
+    }
+
+    /// Returns the processors implementing an opcode.
+    pub fn variants(self) -> &'static [Variant] {
+        self._variants() // auto-generated
+    }
+
+    /// Returns whether the given processor implements an opcode.
+    pub fn is_available(self, variant: Variant) -> bool {
+        self.variants().contains(&variant)
+    }
+
+    /// Returns the modifiers that can be applied to an opcode.
+    pub fn modifiers(self) -> &'static [Modifier] {
+        self._modifiers() // auto-generated
+    }
+
+    /// Returns the mask of the reserved bits that are not part of the bitmask.
+    ///
+    /// These must be zero, but are ignored by [`Opcode::detect`].
+    pub fn reserved_bits(self) -> u32 {
+        self._reserved_bits() // auto-generated
+    }
+
+    /// Returns the instruction form, the layout of the fields in the instruction word.
+    pub fn form(self) -> Option<Form> {
+        self._form() // auto-generated
+    }
+
+    /// Returns the functional category of an opcode.
+    pub fn category(self) -> Option<Category> {
+        self._category() // auto-generated
+    }
+}
+
+/// Instruction form, named as in the Programming Environments Manual.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Form {
+    /// Branch with a 24-bit target (`b`).
+    I,
+    /// Conditional branch with a 14-bit target (`bc`).
+    B,
+    /// System call (`sc`).
+    SC,
+    /// 16-bit immediate or displacement.
+    D,
+    /// 14-bit doubleword displacement, 64-bit implementations only.
+    DS,
+    /// Register operands with a 10-bit extended opcode.
+    X,
+    /// Condition register and branch to register operations.
+    XL,
+    /// Special-purpose register and field mask operations.
+    XFX,
+    /// Floating-point status and control register field mask (`mtfsf`).
+    XFL,
+    /// Integer arithmetic with the overflow enable bit.
+    XO,
+    /// Floating-point with up to four register operands.
+    A,
+    /// Rotate with mask.
+    M,
+    /// Paired single quantized load or store with a 12-bit displacement.
+    PSQ,
+    /// Indexed paired single quantized load or store.
+    PSQX,
+}
+
+/// Functional category of an opcode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Category {
+    /// Integer arithmetic and compares.
+    Arithmetic,
+    /// Integer logical, rotate and shift.
+    Logical,
+    /// Loads of any register type.
+    Load,
+    /// Stores of any register type.
+    Store,
+    /// Floating-point arithmetic and status.
+    FloatingPoint,
+    /// Paired single arithmetic.
+    PairedSingle,
+    /// Branches.
+    Branch,
+    /// Condition register operations.
+    ConditionRegister,
+    /// Cache management.
+    Cache,
+    /// Supervisor, synchronization, trap and special-purpose register operations.
+    System,
 }
 
-impl Default for Opcode {
-    fn default() -> Self {
-        Opcode::Illegal
+/// A processor of the PowerPC 750 family.
+///
+/// Decoding without a variant targets the 750CL (`Broadway`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    /// Generic PowerPC 750, without paired singles.
+    Ppc750,
+    /// PowerPC 750CXe derivative in the Nintendo GameCube.
+    Gekko,
+    /// PowerPC 750CL in the Nintendo Wii.
+    #[default]
+    Broadway,
+    /// Multi-core 750CL derivative in the Nintendo Wii U.
+    Espresso,
+}
+
+impl Variant {
+    /// All variants, from the oldest.
+    pub const ALL: [Variant; 4] = [
+        Variant::Ppc750,
+        Variant::Gekko,
+        Variant::Broadway,
+        Variant::Espresso,
+    ];
+}
+
+/// Result of strict decoding, see [`Ins::validity`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Validity {
+    /// A valid instruction.
+    Valid,
+    /// No opcode matches the instruction word.
+    Illegal,
+    /// Reserved bits of the instruction word are set.
+    ReservedBits,
+    /// The operands form an invalid combination, such as `lwzu` with `rA` equal to `rD`.
+    InvalidForm,
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.mnemonic())
+    }
+}
+
+/// A set of instruction modifiers, displayed as a mnemonic suffix such as `o.`.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Modifiers(u16);
+
+impl Modifiers {
+    /// Constructs an empty set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Adds a modifier to the set.
+    pub fn insert(&mut self, modifier: Modifier) {
+        self.0 |= 1 << modifier as u16;
+    }
+
+    /// Returns whether the set contains a modifier.
+    pub fn contains(self, modifier: Modifier) -> bool {
+        self.0 & (1 << modifier as u16) != 0
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns an iterator over the modifiers in the set, in suffix order.
+    pub fn iter(self) -> impl Iterator<Item = Modifier> {
+        Modifier::ALL
+            .into_iter()
+            .filter(move |&modifier| self.contains(modifier))
     }
 }
 
-impl std::string::ToString for Opcode {
-    fn to_string(&self) -> String {
-        let mnemonic = self.mnemonic();
-        mnemonic.to_owned()
+impl Display for Modifiers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        for modifier in self.iter() {
+            f.write_char(modifier.suffix())?;
+        }
+        Ok(())
     }
 }
 
 /// A PowerPC 750CL instruction.
-#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Ins {
     pub code: u32,
     pub addr: u32,
@@ -324,31 +521,71 @@ impl Ins {
         }
     }
 
+    /// Constructs an instruction for the given processor, see [`Opcode::detect_for`].
+    pub fn new_for(code: u32, addr: u32, variant: Variant) -> Self {
+        Self {
+            code,
+            addr,
+            op: Opcode::detect_for(code, variant),
+        }
+    }
+
+    /// Encodes an instruction from its opcode, fields and modifiers into machine code.
+    ///
+    /// The fields must match the arguments of the opcode, in any order.
+    pub fn encode(
+        op: Opcode,
+        fields: &[Field],
+        modifiers: &[Modifier],
+    ) -> Result<u32, EncodeError> {
+        Self::_encode(op, fields, modifiers) // auto-generated
+    }
+
     /// Returns the simplified representation of an instruction.
     pub fn simplified(self) -> SimplifiedIns {
         self._simplified() // auto-generated
     }
 
     /// Gets the fields of an instruction.
-    pub fn fields(&self) -> Vec<Field> {
+    pub fn fields(&self) -> Fields {
         self._fields() // auto-generated
     }
 
     /// Gets the suffix of an instruction mnemonic.
-    pub fn suffix(&self) -> String {
+    pub fn suffix(&self) -> Modifiers {
         self._suffix() // auto-generated
     }
 
     /// Gets the defs of an instruction.
-    pub fn defs(&self) -> Vec<Field> {
+    pub fn defs(&self) -> Fields {
         self._defs() // auto-generated
     }
 
     /// Gets the uses of an instruction.
-    pub fn uses(&self) -> Vec<Field> {
+    pub fn uses(&self) -> Fields {
         self._uses() // auto-generated
     }
 
+    /// Classifies an instruction as valid or invalid, stricter than [`Opcode::detect`].
+    ///
+    /// Invalid forms have boundedly undefined results, so valid code does not contain them.
+    pub fn validity(&self) -> Validity {
+        if self.op == Opcode::Illegal {
+            Validity::Illegal
+        } else if self.code & self.op.reserved_bits() != 0 {
+            Validity::ReservedBits
+        } else if self._invalid_form() {
+            Validity::InvalidForm
+        } else {
+            Validity::Valid
+        }
+    }
+
+    /// Returns whether an instruction is valid, see [`Ins::validity`].
+    pub fn is_valid(&self) -> bool {
+        self.validity() == Validity::Valid
+    }
+
     /// Gets the given bit from the machine code instruction.
     pub fn bit(&self, idx: usize) -> bool {
         bit(self.code, idx)
@@ -390,14 +627,12 @@ impl Ins {
         matches!(self.op, Opcode::B | Opcode::Bc)
     }
 
+    /// Returns whether an instruction is a branch that is always taken.
+    ///
+    /// See [`Ins::branch_info`] for the conditions of other branches.
     pub fn is_unconditional_branch(&self) -> bool {
-        match self.op {
-            Opcode::B => true,
-            Opcode::Bc | Opcode::Bcctr | Opcode::Bclr => {
-                self.field_BO() == 20 && self.field_BI() == 0
-            }
-            _ => false,
-        }
+        self.branch_info()
+            .is_some_and(|branch| branch.is_unconditional())
     }
 
     pub fn is_conditional_branch(&self) -> bool {
@@ -411,39 +646,45 @@ impl Ins {
     }
 }
 
+/// How an instruction accesses a register operand.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Access {
+    Read,
+    Write,
+    /// Read and written, as `rA` of `rlwimi`.
+    ReadWrite,
+    /// Base register of an effective address, written back by update forms.
+    Base {
+        update: bool,
+    },
+}
+
+impl Placeholder for Option<Access> {
+    const PLACEHOLDER: Self = None;
+}
+
 /// A simplified PowerPC 750CL instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SimplifiedIns {
     pub ins: Ins,
     pub mnemonic: &'static str,
-    pub suffix: String,
-    pub args: Vec<Argument>,
+    pub suffix: Modifiers,
+    pub args: Arguments,
+    /// Access mode of each argument, `None` for immediates.
+    pub access: Accesses,
 }
 
 impl Display for SimplifiedIns {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}{} ", self.mnemonic, self.suffix)?;
-        let mut writing_offset = false;
-        for (i, argument) in self.args.iter().enumerate() {
-            write!(f, "{}", argument)?;
-            if let Argument::Offset(_) = argument {
-                write!(f, "(")?;
-                writing_offset = true;
-                continue;
-            }
-            if writing_offset {
-                write!(f, ")")?;
-                writing_offset = false;
-            }
-            if i != self.args.len() - 1 {
-                write!(f, ", ")?;
-            }
-        }
-        Ok(())
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        FormatOptions::default().display(*self).fmt(f)
     }
 }
 
 impl SimplifiedIns {
-    pub(crate) fn basic_form(ins: Ins) -> Self {
+    /// Returns the basic form of an instruction, without simplified mnemonics.
+    pub fn basic_form(ins: Ins) -> Self {
         Self {
             mnemonic: ins.op.mnemonic(),
             suffix: ins.suffix(),
@@ -452,7 +693,13 @@ impl SimplifiedIns {
                 .iter()
                 .flat_map(|field| field.argument())
                 .collect(),
+            access: ins._accesses(), // auto-generated
             ins,
         }
     }
+
+    /// Returns the arguments along with their access modes.
+    pub fn operands(&self) -> impl Iterator<Item = (Argument, Option<Access>)> + '_ {
+        self.args.iter().copied().zip(self.access.iter().copied())
+    }
 }
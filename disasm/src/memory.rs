@@ -0,0 +1,152 @@
+//! Uniform description of the memory accessed by load and store instructions.
+
+use crate::prelude::*;
+use crate::{Field, Opcode};
+
+/// Direction of a memory access.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MemKind {
+    Load,
+    Store,
+}
+
+/// Second operand of an effective address.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MemOffset {
+    /// Signed displacement, as in `lwz r3, 0x10(r1)`.
+    Displacement(i16),
+    /// Index register, as in `lwzx r3, r1, r4`.
+    Index(GPR),
+}
+
+/// Type of the data transferred by a memory access.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MemData {
+    /// Integer of `width` bytes, sign-extended on load if `signed`, zero-extended otherwise.
+    Int { width: u8, signed: bool },
+    /// Single-precision float, converted to or from double precision in the register.
+    Single,
+    /// Double-precision float.
+    Double,
+    /// Words of consecutive GPRs up to `r31` (`lmw`, `stmw`).
+    Multiple { count: u8 },
+    /// Bytes of consecutive GPRs (`lswi`, `lswx`), the length of indexed forms is held by XER.
+    String { len: Option<u8> },
+    /// Paired single quantized by a GQR, transferring only `ps0` if `single`.
+    Quantized { gqr: GQR, single: bool },
+}
+
+/// A memory access by a load or store instruction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MemAccess {
+    pub kind: MemKind,
+    /// Base register of the effective address, `None` if `rA` is `r0` which reads as zero.
+    pub base: Option<GPR>,
+    pub offset: MemOffset,
+    pub data: MemData,
+    /// Whether the effective address is written back to the base register.
+    pub update: bool,
+    /// Whether the bytes of the data are reversed (`lwbrx`, `sthbrx`).
+    pub byte_reversed: bool,
+}
+
+impl MemAccess {
+    /// Returns the number of bytes accessed, if known without the register state.
+    ///
+    /// The size of quantized accesses depends on the GQR.
+    pub fn size(&self) -> Option<u32> {
+        match self.data {
+            MemData::Int { width, .. } => Some(width as u32),
+            MemData::Single => Some(4),
+            MemData::Double => Some(8),
+            MemData::Multiple { count } => Some(count as u32 * 4),
+            MemData::String { len } => len.map(|len| len as u32),
+            MemData::Quantized { .. } => None,
+        }
+    }
+}
+
+impl Ins {
+    /// Describes the memory accessed by a load or store instruction.
+    ///
+    /// Cache management instructions are not included.
+    pub fn memory_access(&self) -> Option<MemAccess> {
+        let kind = match self.op.category()? {
+            Category::Load => MemKind::Load,
+            Category::Store => MemKind::Store,
+            _ => return None,
+        };
+        let int = |width, signed| MemData::Int { width, signed };
+        let data = match self.op {
+            Opcode::Lbz | Opcode::Lbzu | Opcode::Lbzux | Opcode::Lbzx => int(1, false),
+            Opcode::Stb | Opcode::Stbu | Opcode::Stbux | Opcode::Stbx => int(1, false),
+            Opcode::Lha | Opcode::Lhau | Opcode::Lhaux | Opcode::Lhax => int(2, true),
+            Opcode::Lhz | Opcode::Lhzu | Opcode::Lhzux | Opcode::Lhzx | Opcode::Lhbrx => {
+                int(2, false)
+            }
+            Opcode::Sth | Opcode::Sthu | Opcode::Sthux | Opcode::Sthx | Opcode::Sthbrx => {
+                int(2, false)
+            }
+            Opcode::Lfs | Opcode::Lfsu | Opcode::Lfsux | Opcode::Lfsx => MemData::Single,
+            Opcode::Stfs | Opcode::Stfsu | Opcode::Stfsux | Opcode::Stfsx => MemData::Single,
+            Opcode::Lfd | Opcode::Lfdu | Opcode::Lfdux | Opcode::Lfdx => MemData::Double,
+            Opcode::Stfd | Opcode::Stfdu | Opcode::Stfdux | Opcode::Stfdx => MemData::Double,
+            Opcode::Lmw => MemData::Multiple {
+                count: 32 - self.field_rD() as u8,
+            },
+            Opcode::Stmw => MemData::Multiple {
+                count: 32 - self.field_rS() as u8,
+            },
+            Opcode::Lswi | Opcode::Stswi => MemData::String {
+                len: Some(match self.field_NB() {
+                    0 => 32,
+                    n => n as u8,
+                }),
+            },
+            Opcode::Lswx | Opcode::Stswx => MemData::String { len: None },
+            Opcode::PsqL | Opcode::PsqLu | Opcode::PsqSt | Opcode::PsqStu => MemData::Quantized {
+                gqr: GQR(self.field_ps_I() as u8),
+                single: self.field_ps_W() != 0,
+            },
+            Opcode::PsqLx | Opcode::PsqLux | Opcode::PsqStx | Opcode::PsqStux => {
+                MemData::Quantized {
+                    gqr: GQR(self.field_ps_IX() as u8),
+                    single: self.field_ps_WX() != 0,
+                }
+            }
+            // Remaining word accesses, including `stfiwx` which stores the low word of an FPR.
+            _ => int(4, false),
+        };
+        let mut base = None;
+        let mut addend = MemOffset::Displacement(0);
+        let mut update = false;
+        for field in self.fields().iter() {
+            match *field {
+                rA(GPR(0)) => {}
+                rA(reg) => base = Some(reg),
+                rB(reg) => addend = MemOffset::Index(reg),
+                Field::offset(Offset(off)) | ps_offset(Offset(off)) => {
+                    addend = MemOffset::Displacement(off)
+                }
+                _ => {}
+            }
+        }
+        // Update forms define their base register.
+        for field in self.defs().iter() {
+            if let rA(_) = field {
+                update = true;
+            }
+        }
+        Some(MemAccess {
+            kind,
+            base,
+            offset: addend,
+            data,
+            update,
+            byte_reversed: matches!(
+                self.op,
+                Opcode::Lhbrx | Opcode::Lwbrx | Opcode::Sthbrx | Opcode::Stwbrx
+            ),
+        })
+    }
+}
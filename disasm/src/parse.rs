@@ -0,0 +1,95 @@
+//! Parsing of registers and opcodes from the syntax printed by their `Display` impls.
+
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::asm::{parse_crbit, parse_int, parse_mnemonic, parse_register, parse_spr};
+use crate::{CRBit, CRField, Modifier, Modifiers, Opcode, FPR, GPR, GQR, SPR, SR};
+
+/// An error returned when parsing a register or opcode fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    /// The kind of value that was expected, such as `GPR`.
+    pub expected: &'static str,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid {}", self.expected)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+macro_rules! impl_from_str {
+    ($name:ident, $parse:expr) => {
+        impl FromStr for $name {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let parse: fn(&str) -> Option<i64> = $parse;
+                parse(s.trim()).map(|x| $name(x as _)).ok_or(ParseError {
+                    expected: stringify!($name),
+                })
+            }
+        }
+    };
+}
+
+impl_from_str!(GPR, |s| parse_register(s, "r", 32));
+impl_from_str!(FPR, |s| parse_register(s, "f", 32));
+impl_from_str!(CRField, |s| parse_register(s, "cr", 8));
+impl_from_str!(GQR, |s| parse_register(s, "qr", 8));
+impl_from_str!(SR, |s| parse_int(s).filter(|x| (0..16).contains(x)));
+impl_from_str!(SPR, |s| parse_spr(s).filter(|x| (0..1024).contains(x)));
+impl_from_str!(CRBit, |s| parse_crbit(s).filter(|x| (0..32).contains(x)));
+
+impl Opcode {
+    /// Parses a basic mnemonic followed by modifier suffixes, such as `addo.` or `bcl`.
+    ///
+    /// Simplified mnemonics are not accepted, see [`assemble`](crate::asm::assemble).
+    pub fn from_mnemonic(mnemonic: &str) -> Option<(Self, Modifiers)> {
+        let mut best: Option<(Self, Modifiers)> = None;
+        for op in Opcode::ALL {
+            // Prefer the longest name, so that `bclr` is not read as `bc` with a suffix.
+            if best.is_some_and(|(prev, _)| prev.mnemonic().len() >= op.mnemonic().len()) {
+                continue;
+            }
+            // Suffixes are written in the order of `Modifier::ALL`.
+            let mut modifiers = [Modifier::OE; Modifier::ALL.len()];
+            let mut suffixes = [' '; Modifier::ALL.len()];
+            let mut count = 0;
+            for modifier in Modifier::ALL {
+                if op.modifiers().contains(&modifier) {
+                    modifiers[count] = modifier;
+                    suffixes[count] = modifier.suffix();
+                    count += 1;
+                }
+            }
+            if let Some(set) = parse_mnemonic(mnemonic, op.mnemonic(), &suffixes[..count]) {
+                let mut parsed = Modifiers::empty();
+                for (i, &modifier) in modifiers[..count].iter().enumerate() {
+                    if set & (1 << i) != 0 {
+                        parsed.insert(modifier);
+                    }
+                }
+                best = Some((op, parsed));
+            }
+        }
+        best
+    }
+}
+
+impl FromStr for Opcode {
+    type Err = ParseError;
+
+    /// Parses an opcode from its mnemonic, ignoring modifier suffixes such as `o.` in `addo.`.
+    ///
+    /// Use [`Opcode::from_mnemonic`] to also get the modifiers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Opcode::from_mnemonic(s.trim())
+            .map(|(op, _)| op)
+            .ok_or(ParseError { expected: "opcode" })
+    }
+}
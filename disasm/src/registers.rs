@@ -0,0 +1,257 @@
+//! Registers read and written by instructions, including implicit ones.
+
+use crate::inline_vec::Placeholder;
+use crate::prelude::*;
+use crate::{Field, Opcode};
+
+/// A register accessed by an instruction.
+///
+/// Condition register and XER bits are grouped by how instructions update them,
+/// so that a write always replaces the whole register.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Register {
+    GPR(GPR),
+    FPR(FPR),
+    /// Condition register field.
+    CR(CRField),
+    /// Summary overflow bit of XER.
+    XerSO,
+    /// Overflow bit of XER.
+    XerOV,
+    /// Carry bit of XER.
+    XerCA,
+    /// Byte count of XER, used by `lswx` and `stswx`.
+    XerBC,
+    LR,
+    CTR,
+    FPSCR,
+    MSR,
+    SR(SR),
+    /// Graphics quantization register.
+    GQR(GQR),
+    /// Any other special-purpose register.
+    SPR(SPR),
+}
+
+impl Register {
+    pub const CR0: Self = Self::CR(CRField(0));
+    pub const CR1: Self = Self::CR(CRField(1));
+    pub const SRR0: Self = Self::SPR(SPR(26));
+    pub const SRR1: Self = Self::SPR(SPR(27));
+    pub const EAR: Self = Self::SPR(SPR(282));
+}
+
+impl Placeholder for Register {
+    const PLACEHOLDER: Self = Register::LR;
+}
+
+/// Registers accessed by an instruction, without duplicates.
+pub type Registers = InlineVec<Register, 40>;
+
+fn push(regs: &mut Registers, reg: Register) {
+    if !regs.contains(&reg) {
+        regs.push(reg);
+    }
+}
+
+/// Adds the registers behind a special-purpose register number.
+///
+/// The time base is reported by its write numbers, `TBL` 284 and `TBU` 285.
+fn push_spr(regs: &mut Registers, num: usize) {
+    match num {
+        1 => {
+            for reg in [
+                Register::XerSO,
+                Register::XerOV,
+                Register::XerCA,
+                Register::XerBC,
+            ] {
+                push(regs, reg);
+            }
+        }
+        8 => push(regs, Register::LR),
+        9 => push(regs, Register::CTR),
+        268 | 284 => push(regs, Register::SPR(SPR(284))),
+        269 | 285 => push(regs, Register::SPR(SPR(285))),
+        912..=919 => push(regs, Register::GQR(GQR((num - 912) as u8))),
+        _ => push(regs, Register::SPR(SPR(num as u16))),
+    }
+}
+
+/// Adds the register named by an operand field.
+fn push_field(regs: &mut Registers, field: &Field) {
+    match field {
+        // `mcrxr` clears the bits copied to the condition register.
+        xer => {
+            for reg in [Register::XerSO, Register::XerOV, Register::XerCA] {
+                push(regs, reg);
+            }
+        }
+        ctr => push(regs, Register::CTR),
+        lr => push(regs, Register::LR),
+        _ => match field.argument() {
+            Some(Argument::GPR(reg)) => push(regs, Register::GPR(reg)),
+            Some(Argument::FPR(reg)) => push(regs, Register::FPR(reg)),
+            Some(Argument::CRField(reg)) => push(regs, Register::CR(reg)),
+            Some(Argument::CRBit(bit)) => push(regs, Register::CR(CRField(bit.0 >> 2))),
+            Some(Argument::SR(reg)) => push(regs, Register::SR(reg)),
+            Some(Argument::GQR(reg)) => push(regs, Register::GQR(reg)),
+            Some(Argument::SPR(reg)) => push_spr(regs, reg.0 as usize),
+            _ => {}
+        },
+    }
+}
+
+/// Adds `count` consecutive GPRs starting at `first`, wrapping around after `r31`.
+fn push_gprs(regs: &mut Registers, first: usize, count: usize) {
+    for i in 0..count {
+        push(regs, Register::GPR(GPR(((first + i) % 32) as u8)));
+    }
+}
+
+impl Ins {
+    /// Whether the `Rc` bit records to `cr1` instead of `cr0`.
+    fn records_fp(&self) -> bool {
+        matches!(
+            self.op.category(),
+            Some(Category::FloatingPoint | Category::PairedSingle)
+        )
+    }
+
+    /// Whether a conditional branch decrements the count register.
+    fn decrements_ctr(&self) -> bool {
+        self.branch_info()
+            .is_some_and(|branch| branch.decrements_ctr())
+    }
+
+    /// Number of registers transferred by `lswi` and `stswi`.
+    fn string_registers(&self) -> usize {
+        match self.field_NB() {
+            0 => 8,
+            n => n.div_ceil(4),
+        }
+    }
+
+    /// Gets the registers read by an instruction, including implicit ones.
+    ///
+    /// Registers that are only partially written, such as a condition register field
+    /// updated by `crand`, are also reported as read.
+    pub fn reads(&self) -> Registers {
+        let mut regs = Registers::new();
+        for field in self.uses().iter() {
+            push_field(&mut regs, field);
+        }
+        for field in self.defs().iter() {
+            if let crbD(bit) = field {
+                push(&mut regs, Register::CR(CRField(bit.0 >> 2)));
+            }
+        }
+        for &reg in self.op._implicit_reads() {
+            push(&mut regs, reg);
+        }
+        let suffix = self.suffix();
+        if suffix.contains(Modifier::Rc) {
+            // The summary overflow is copied to the recorded field.
+            let reg = if self.records_fp() {
+                Register::FPSCR
+            } else {
+                Register::XerSO
+            };
+            push(&mut regs, reg);
+        }
+        if suffix.contains(Modifier::OE) {
+            push(&mut regs, Register::XerSO);
+        }
+        if self.decrements_ctr() {
+            push(&mut regs, Register::CTR);
+        }
+        if let Some(test) = self.branch_info().and_then(|branch| branch.cr) {
+            push(&mut regs, Register::CR(test.field()));
+        }
+        match self.op {
+            Opcode::Mfcr => {
+                for i in 0..8 {
+                    push(&mut regs, Register::CR(CRField(i)));
+                }
+            }
+            Opcode::Mfspr => push_spr(&mut regs, self.field_spr()),
+            Opcode::Mftb => push_spr(&mut regs, self.field_tbr()),
+            Opcode::Mfsr => push(&mut regs, Register::SR(SR(self.field_sr() as u8))),
+            // The segment register is only known at runtime.
+            Opcode::Mfsrin => {
+                for i in 0..16 {
+                    push(&mut regs, Register::SR(SR(i)));
+                }
+            }
+            Opcode::PsqL
+            | Opcode::PsqLu
+            | Opcode::PsqLux
+            | Opcode::PsqLx
+            | Opcode::PsqSt
+            | Opcode::PsqStu
+            | Opcode::PsqStux
+            | Opcode::PsqStx => {
+                for field in self.fields().iter() {
+                    if let Some(Argument::GQR(reg)) = field.argument() {
+                        push(&mut regs, Register::GQR(reg));
+                    }
+                }
+            }
+            Opcode::Stmw => push_gprs(&mut regs, self.field_rS(), 32 - self.field_rS()),
+            Opcode::Stswi => push_gprs(&mut regs, self.field_rS(), self.string_registers()),
+            // The number of registers is only known at runtime.
+            Opcode::Stswx => push_gprs(&mut regs, 0, 32),
+            _ => {}
+        }
+        regs
+    }
+
+    /// Gets the registers written by an instruction, including implicit ones.
+    ///
+    /// Registers written through a runtime index, as by `mtsrin`, are not included.
+    pub fn writes(&self) -> Registers {
+        let mut regs = Registers::new();
+        for field in self.defs().iter() {
+            push_field(&mut regs, field);
+        }
+        for &reg in self.op._implicit_writes() {
+            push(&mut regs, reg);
+        }
+        let suffix = self.suffix();
+        if suffix.contains(Modifier::Rc) {
+            let reg = if self.records_fp() {
+                Register::CR1
+            } else {
+                Register::CR0
+            };
+            push(&mut regs, reg);
+        }
+        if suffix.contains(Modifier::OE) {
+            push(&mut regs, Register::XerSO);
+            push(&mut regs, Register::XerOV);
+        }
+        if suffix.contains(Modifier::LK) {
+            push(&mut regs, Register::LR);
+        }
+        if self.decrements_ctr() {
+            push(&mut regs, Register::CTR);
+        }
+        match self.op {
+            Opcode::Mtcrf => {
+                for i in 0..8 {
+                    if self.field_crm() & (0x80 >> i) != 0 {
+                        push(&mut regs, Register::CR(CRField(i)));
+                    }
+                }
+            }
+            Opcode::Mtspr => push_spr(&mut regs, self.field_spr()),
+            Opcode::Mtsr => push(&mut regs, Register::SR(SR(self.field_sr() as u8))),
+            Opcode::Lmw => push_gprs(&mut regs, self.field_rD(), 32 - self.field_rD()),
+            Opcode::Lswi => push_gprs(&mut regs, self.field_rD(), self.string_registers()),
+            // The number of registers is only known at runtime.
+            Opcode::Lswx => push_gprs(&mut regs, 0, 32),
+            _ => {}
+        }
+        regs
+    }
+}
@@ -0,0 +1,150 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! The serialized shape is stable:
+//! - [`Ins`] is a struct of `code`, `addr` and `op`. On deserialization, `op` must be the
+//!   opcode of `code` for one of the [`Variant`]s.
+//! - [`Opcode`] is its basic mnemonic, such as `"addi"`, or `"<illegal>"`.
+//! - Registers and immediates such as [`GPR`](crate::GPR) are their number.
+//! - [`Field`](crate::Field) and [`Argument`](crate::Argument) are externally tagged
+//!   enums, such as `{"rA": 3}` and `{"GPR": 3}`. Fields without a value are a string.
+//! - [`Modifiers`] is a list of modifier names, such as `["OE", "Rc"]`.
+//! - Lists of fields and arguments are sequences.
+//! - [`SimplifiedIns`](crate::SimplifiedIns) is a struct of `ins`, `mnemonic`, `suffix`,
+//!   `args` and `access`. It can only be serialized, as its mnemonic is static.
+
+use core::fmt::Formatter;
+use core::marker::PhantomData;
+
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::inline_vec::Placeholder;
+use crate::{InlineVec, Ins, Modifier, Modifiers, Opcode, Variant};
+
+impl Serialize for Opcode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.mnemonic())
+    }
+}
+
+impl<'de> Deserialize<'de> for Opcode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OpcodeVisitor;
+
+        impl Visitor<'_> for OpcodeVisitor {
+            type Value = Opcode;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                f.write_str("a basic mnemonic")
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<Opcode, E> {
+                if v == Opcode::Illegal.mnemonic() {
+                    return Ok(Opcode::Illegal);
+                }
+                Opcode::ALL
+                    .into_iter()
+                    .find(|op| op.mnemonic() == v)
+                    .ok_or_else(|| E::unknown_variant(v, &[]))
+            }
+        }
+
+        deserializer.deserialize_str(OpcodeVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ins {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Ins")]
+        struct InsRepr {
+            code: u32,
+            addr: u32,
+            op: Opcode,
+        }
+
+        let InsRepr { code, addr, op } = InsRepr::deserialize(deserializer)?;
+        // The variant is not serialized, so any of them may have decoded the instruction.
+        if !Variant::ALL
+            .iter()
+            .any(|&variant| Opcode::detect_for(code, variant) == op)
+        {
+            return Err(D::Error::custom(format_args!(
+                "opcode {} does not match code {:#010x}",
+                op.mnemonic(),
+                code
+            )));
+        }
+        Ok(Ins { code, addr, op })
+    }
+}
+
+impl Serialize for Modifiers {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de> Deserialize<'de> for Modifiers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ModifiersVisitor;
+
+        impl<'de> Visitor<'de> for ModifiersVisitor {
+            type Value = Modifiers;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                f.write_str("a sequence of modifier names")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Modifiers, A::Error> {
+                let mut modifiers = Modifiers::empty();
+                while let Some(modifier) = seq.next_element::<Modifier>()? {
+                    modifiers.insert(modifier);
+                }
+                Ok(modifiers)
+            }
+        }
+
+        deserializer.deserialize_seq(ModifiersVisitor)
+    }
+}
+
+impl<T: Placeholder + Serialize, const N: usize> Serialize for InlineVec<T, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for item in self.iter() {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: Placeholder + Deserialize<'de>, const N: usize> Deserialize<'de> for InlineVec<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InlineVecVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Placeholder + Deserialize<'de>, const N: usize> Visitor<'de>
+            for InlineVecVisitor<T, N>
+        {
+            type Value = InlineVec<T, N>;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                write!(f, "a sequence of at most {} items", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut vec = InlineVec::new();
+                while let Some(item) = seq.next_element()? {
+                    if vec.len() == N {
+                        return Err(A::Error::invalid_length(N + 1, &self));
+                    }
+                    vec.push(item);
+                }
+                Ok(vec)
+            }
+        }
+
+        deserializer.deserialize_seq(InlineVecVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,156 @@
+//! Symbol lookup for formatting branch targets and address operands.
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::prelude::*;
+
+/// Half of a 32-bit address built by an instruction pair such as `lis`/`addi`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Half {
+    /// High half, adjusted for the sign of the low half (`@ha`).
+    Ha,
+    /// High half (`@h`).
+    Hi,
+    /// Low half (`@l`).
+    Lo,
+}
+
+impl Half {
+    /// Returns the assembler suffix selecting this half.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Half::Ha => "@ha",
+            Half::Hi => "@h",
+            Half::Lo => "@l",
+        }
+    }
+}
+
+/// A source of symbol names consulted by the formatter.
+pub trait SymbolResolver {
+    /// Looks up the symbol for an address.
+    ///
+    /// Returns the symbol name and the offset of the address into the symbol.
+    fn resolve(&self, addr: u32) -> Option<(Cow<'_, str>, u32)>;
+
+    /// Looks up the address that the immediate of the instruction at `ins_addr` is a half of.
+    ///
+    /// No halves are known by default.
+    fn resolve_half(&self, _ins_addr: u32) -> Option<(u32, Half)> {
+        None
+    }
+}
+
+/// Resolves addresses that start a symbol.
+#[cfg(feature = "std")]
+impl<S: AsRef<str>> SymbolResolver for HashMap<u32, S> {
+    fn resolve(&self, addr: u32) -> Option<(Cow<'_, str>, u32)> {
+        self.get(&addr)
+            .map(|name| (Cow::Borrowed(name.as_ref()), 0))
+    }
+}
+
+/// Resolves addresses relative to the closest symbol at or before them, given the
+/// name and size of each symbol by start address.
+///
+/// Addresses past the end of the symbol are not resolved. Symbols of size zero only
+/// resolve their start address.
+impl<S: AsRef<str>> SymbolResolver for BTreeMap<u32, (S, u32)> {
+    fn resolve(&self, addr: u32) -> Option<(Cow<'_, str>, u32)> {
+        let (&start, (name, size)) = self.range(..=addr).next_back()?;
+        let off = addr - start;
+        (off == 0 || off < *size).then(|| (Cow::Borrowed(name.as_ref()), off))
+    }
+}
+
+impl<F, S> SymbolResolver for F
+where
+    F: Fn(u32) -> Option<(S, u32)>,
+    S: Into<Cow<'static, str>>,
+{
+    fn resolve(&self, addr: u32) -> Option<(Cow<'_, str>, u32)> {
+        self(addr).map(|(name, off)| (name.into(), off))
+    }
+}
+
+/// A resolver that also knows the address halves built by a sequence of instructions.
+pub struct WithHalves<R> {
+    pub symbols: R,
+    /// Address and half of the immediate operand, by instruction address.
+    pub halves: BTreeMap<u32, (u32, Half)>,
+}
+
+impl<R: SymbolResolver> WithHalves<R> {
+    /// Wraps a resolver, pairing up address halves in the given instructions.
+    pub fn new<I>(symbols: R, code: I) -> Self
+    where
+        I: IntoIterator<Item = Ins>,
+    {
+        Self {
+            symbols,
+            halves: find_address_halves(code),
+        }
+    }
+}
+
+impl<R: SymbolResolver> SymbolResolver for WithHalves<R> {
+    fn resolve(&self, addr: u32) -> Option<(Cow<'_, str>, u32)> {
+        self.symbols.resolve(addr)
+    }
+
+    fn resolve_half(&self, ins_addr: u32) -> Option<(u32, Half)> {
+        self.halves.get(&ins_addr).copied()
+    }
+}
+
+/// Pairs `lis` instructions with the `addi`, `ori` or memory access using the same register.
+///
+/// Only straight-line code is followed, branches end all pairs.
+pub fn find_address_halves<I>(code: I) -> BTreeMap<u32, (u32, Half)>
+where
+    I: IntoIterator<Item = Ins>,
+{
+    let mut halves = BTreeMap::new();
+    // Address of the pending `lis` and its value, by register.
+    let mut high: [Option<(u32, u32)>; 32] = [None; 32];
+    for ins in code {
+        // Base register, half built by the `lis`, low half, and whether halves are combined by OR.
+        let low = match ins.op {
+            Addi => Some((ins.field_rA(), Half::Ha, ins.field_simm() as u32, false)),
+            Ori => Some((ins.field_rS(), Half::Hi, ins.field_uimm() as u32, true)),
+            _ => ins.fields().iter().find_map(|field| match field {
+                offset(Offset(off)) | ps_offset(Offset(off)) => {
+                    Some((ins.field_rA(), Half::Ha, *off as u32, false))
+                }
+                _ => None,
+            }),
+        };
+        if let Some((base, half, low, or)) = low {
+            if let Some((lis_addr, value)) = high[base].filter(|_| base != 0) {
+                let addr = if or {
+                    value | low
+                } else {
+                    value.wrapping_add(low)
+                };
+                halves.entry(lis_addr).or_insert((addr, half));
+                halves.insert(ins.addr, (addr, Half::Lo));
+            }
+        }
+        if ins.is_branch() {
+            high = [None; 32];
+            continue;
+        }
+        for def in ins.defs().iter() {
+            if let rD(GPR(reg)) | rA(GPR(reg)) = def {
+                high[*reg as usize] = None;
+            }
+        }
+        if ins.op == Addis && ins.field_rA() == 0 {
+            high[ins.field_rD()] = Some((ins.addr, (ins.field_uimm() as u32) << 16));
+        }
+    }
+    halves
+}
@@ -0,0 +1,67 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Write;
+
+use ppc750cl::prelude::*;
+
+struct CountingAlloc;
+
+thread_local! {
+    // Counted per thread, so the test harness does not interfere.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Formats into a fixed buffer without allocating.
+struct Buffer {
+    data: [u8; 64],
+    len: usize,
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let end = self.len + s.len();
+        self.data
+            .get_mut(self.len..end)
+            .ok_or(std::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_disasm_print_no_alloc() {
+    let mut buf = Buffer {
+        data: [0; 64],
+        len: 0,
+    };
+    let before = ALLOCATIONS.with(Cell::get);
+    let mut code = 0x1234_5678u32;
+    for _ in 0..100_000 {
+        code ^= code << 13;
+        code ^= code >> 17;
+        code ^= code << 5;
+        let ins = Ins::new(code, 0x8000_0000);
+        buf.len = 0;
+        write!(buf, "{}", FormattedIns(ins)).unwrap();
+        for field in ins.fields().iter().chain(&ins.defs()).chain(&ins.uses()) {
+            write!(buf, "{:?}", field.argument()).unwrap();
+            buf.len = 0;
+        }
+    }
+    assert_eq!(ALLOCATIONS.with(Cell::get), before);
+}
@@ -0,0 +1,119 @@
+use ppc750cl::asm::{assemble, AsmError};
+use ppc750cl::encode::EncodeError;
+use ppc750cl::prelude::*;
+
+macro_rules! assert_roundtrip {
+    ($code:literal, $disasm:literal) => {{
+        let ins = Ins::new($code, 0x8000_0000);
+        assert_eq!(format!("{}", FormattedIns(ins)), $disasm);
+        assert_eq!(assemble($disasm), Ok($code));
+    }};
+}
+
+#[test]
+fn test_asm_basic() {
+    assert_eq!(assemble("lwz r3, 0x10(r31)"), Ok(0x807f0010));
+    assert_eq!(assemble("  addi r0, r1, 0x140  "), Ok(0x38010140));
+    assert_eq!(assemble("addo. r3, r4, r5"), Ok(0x7c642e15));
+    assert_eq!(assemble("mfspr r3, HID2"), Ok(0x7c78e2a6));
+    assert_eq!(assemble("mfspr r3, 920"), Ok(0x7c78e2a6));
+    assert_eq!(assemble("crand 4*cr1+eq, lt, 4*cr7+so"), Ok(0x4cc0fa02));
+    assert_roundtrip!(0x4E800020, "blr");
+    assert_roundtrip!(0x4E800021, "blrl");
+    assert_roundtrip!(0x4E800420, "bctr");
+    assert_roundtrip!(0x7C000378, "mr r0, r0");
+    assert_roundtrip!(0x60000000, "nop");
+    assert_roundtrip!(0x10400420, "ps_merge00 f2, f0, f0");
+    assert_roundtrip!(0xE3E1000C, "psq_l f31, 0xc(r1), 0, qr0");
+    assert_roundtrip!(0x7C0004AC, "sync");
+}
+
+#[test]
+fn test_asm_simplified() {
+    assert_roundtrip!(0x4E000020, "bdnzlr");
+    assert_roundtrip!(0x4E200020, "bdnzlr+");
+    assert_roundtrip!(0x4200FFF8, "bdnz -0x8");
+    assert_roundtrip!(0x41820010, "beq 0x10");
+    assert_roundtrip!(0x41A20010, "beq+ 0x10");
+    assert_roundtrip!(0x41A2FFF0, "beq- -0x10");
+    assert_roundtrip!(0x408600D8, "bne cr1, 0xd8");
+    assert_roundtrip!(0x4D820020, "beqlr");
+    assert_roundtrip!(0x4D860020, "beqlr cr1");
+    assert_roundtrip!(0x3C608000, "lis r3, 0x8000");
+    assert_roundtrip!(0x3860FFFF, "li r3, -0x1");
+    assert_roundtrip!(0x2C030000, "cmpwi r3, 0x0");
+    assert_roundtrip!(0x2F830000, "cmpwi cr7, r3, 0x0");
+    assert_roundtrip!(0x5463103A, "slwi r3, r3, 2");
+    assert_roundtrip!(0x5463F0BE, "srwi r3, r3, 2");
+    assert_roundtrip!(0x5463063E, "clrlwi r3, r3, 24");
+    assert_roundtrip!(0x5463C63E, "extrwi r3, r3, 8, 16");
+    assert_roundtrip!(0x7C6803A6, "mtlr r3");
+    assert_roundtrip!(0x7C7142A6, "mfsprg r3, 1");
+    assert_roundtrip!(0x4C421242, "crset eq");
+    assert_roundtrip!(0x7FE00008, "trap");
+    assert_roundtrip!(0x3863FFF0, "subi r3, r3, 0x10");
+    assert_roundtrip!(0x5083842E, "inslwi r3, r4, 8, 16");
+    assert_roundtrip!(0x7C77E3A6, "mtspr GQR7, r3");
+    assert_roundtrip!(0x7C7F42A6, "mfpvr r3");
+}
+
+#[test]
+fn test_asm_aliases() {
+    assert_eq!(assemble("sub r3, r4, r5"), Ok(0x7c652050));
+    assert_eq!(assemble("subc r3, r4, r5"), Ok(0x7c652010));
+    assert_eq!(assemble("la r3, 0x8(r1)"), Ok(0x38610008));
+    assert_eq!(assemble("mtspr HID0, r3"), Ok(0x7c70fba6));
+}
+
+#[test]
+fn test_asm_errors() {
+    assert_eq!(assemble("foo r3"), Err(AsmError::UnknownMnemonic));
+    assert_eq!(assemble("add r3, r4"), Err(AsmError::OperandCount(2)));
+    assert_eq!(assemble("add r3, r4, f5"), Err(AsmError::InvalidOperand(2)));
+    assert_eq!(
+        assemble("lwz r3, r31, 0x10"),
+        Err(AsmError::InvalidOperand(1))
+    );
+    assert_eq!(
+        assemble("addi r3, r3, 0x8000"),
+        Err(AsmError::Encode(EncodeError::OutOfRange {
+            field: "simm",
+            value: 0x8000
+        }))
+    );
+    assert_eq!(
+        assemble("b 0x2"),
+        Err(AsmError::Encode(EncodeError::OutOfRange {
+            field: "LI",
+            value: 2
+        }))
+    );
+    assert_eq!(assemble("li r3, --5"), Err(AsmError::InvalidOperand(1)));
+    assert_eq!(assemble("li r3, 0x-5"), Err(AsmError::InvalidOperand(1)));
+    assert_eq!(assemble("li r3, +5"), Err(AsmError::InvalidOperand(1)));
+    assert_eq!(assemble("li r3, 0x"), Err(AsmError::InvalidOperand(1)));
+}
+
+#[test]
+fn test_asm_roundtrip_random() {
+    // Xorshift sequence over the instruction space.
+    let mut code = 0x1234_5678u32;
+    for _ in 0..500_000 {
+        code ^= code << 13;
+        code ^= code >> 17;
+        code ^= code << 5;
+        let ins = Ins::new(code, 0x8000_0000);
+        if ins.op == Illegal {
+            continue;
+        }
+        let disasm = FormattedIns(ins).to_string();
+        let reassembled = assemble(&disasm).unwrap_or_else(|e| panic!("{}: {}", disasm, e));
+        // Words with reserved bits or invalid forms need not encode back exactly.
+        if ins.is_valid() {
+            assert_eq!(reassembled, code, "{}", disasm);
+        } else {
+            let disasm2 = FormattedIns(Ins::new(reassembled, 0x8000_0000)).to_string();
+            assert_eq!(disasm, disasm2, "{:#010x} -> {:#010x}", code, reassembled);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use ppc750cl::prelude::*;
+
+fn ins(code: u32) -> Ins {
+    Ins::new(code, 0x8000_0000)
+}
+
+#[test]
+fn test_branch_direct() {
+    // bl 0x80000100
+    let branch = ins(0x48000101).branch_info().unwrap();
+    assert_eq!(
+        branch,
+        BranchInfo {
+            kind: BranchKind::Direct,
+            link: true,
+            target: Some(0x8000_0100),
+            ctr: None,
+            cr: None,
+            hint: false,
+            predict_taken: true,
+        }
+    );
+    assert!(branch.is_unconditional());
+    // beq cr1, 0x80000010
+    let branch = ins(0x41860010).branch_info().unwrap();
+    assert_eq!(
+        branch.cr,
+        Some(CrTest {
+            bit: CRBit(6),
+            value: true
+        })
+    );
+    assert_eq!(branch.cr.unwrap().field(), CRField(1));
+    assert_eq!(branch.ctr, None);
+    assert!(!branch.predict_taken);
+    // bne+ 0x80000010
+    let branch = ins(0x40A20010).branch_info().unwrap();
+    assert!(branch.hint);
+    assert!(branch.predict_taken);
+    // bge 0x7ffffff0
+    assert!(ins(0x4080FFF0).branch_info().unwrap().predict_taken);
+}
+
+#[test]
+fn test_branch_ctr() {
+    // bdnz 0x7ffffff8
+    let branch = ins(0x4200FFF8).branch_info().unwrap();
+    assert_eq!(branch.ctr, Some(CtrTest::NonZero));
+    assert_eq!(branch.cr, None);
+    assert!(branch.decrements_ctr());
+    assert!(branch.predict_taken);
+    // bdzf eq, 0x80000008
+    let branch = ins(0x40420008).branch_info().unwrap();
+    assert_eq!(branch.ctr, Some(CtrTest::Zero));
+    assert_eq!(
+        branch.cr,
+        Some(CrTest {
+            bit: CRBit(2),
+            value: false
+        })
+    );
+}
+
+#[test]
+fn test_branch_indirect() {
+    // blr
+    let branch = ins(0x4E800020).branch_info().unwrap();
+    assert_eq!(branch.kind, BranchKind::Lr);
+    assert_eq!(branch.target, None);
+    assert!(branch.is_unconditional());
+    // bctrl
+    let branch = ins(0x4E800421).branch_info().unwrap();
+    assert_eq!(branch.kind, BranchKind::Ctr);
+    assert!(branch.link);
+    // beqlr
+    assert!(!ins(0x4D820020).branch_info().unwrap().is_unconditional());
+    // addi r3, r3, 0x1
+    assert_eq!(ins(0x38630001).branch_info(), None);
+}
+
+#[test]
+fn test_branch_helpers() {
+    // bdnz 0x7ffffff8
+    assert!(ins(0x4200FFF8).is_conditional_branch());
+    // Branch always, with BI ignored
+    assert!(ins(0x4E9F0020).is_unconditional_branch());
+    // b 0x80000010
+    assert!(ins(0x48000010).is_unconditional_branch());
+    assert!(!ins(0x38630001).is_conditional_branch());
+}
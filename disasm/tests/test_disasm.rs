@@ -1,4 +1,5 @@
 use ppc750cl::prelude::*;
+use ppc750cl::{disasm_iter, Opcode};
 
 macro_rules! assert_asm {
     ($ins:ident, $disasm:literal) => {{
@@ -10,53 +11,62 @@ macro_rules! assert_asm {
     }};
 }
 
+#[test]
+fn test_ins_add() {
+    assert_asm!(0x7C642A14, "add r3, r4, r5");
+    assert_asm!(0x7C642E14, "addo r3, r4, r5");
+    assert_asm!(0x7C642E15, "addo. r3, r4, r5");
+}
+
 #[test]
 fn test_ins_addc() {
     let ins = Ins::new(0x7c002014, 0x8000_0000u32);
     assert_eq!(ins.op, Addc);
-    assert_eq!(ins.fields(), vec![rD(GPR(0)), rA(GPR(0)), rB(GPR(4))]);
+    assert_eq!(ins.fields(), [rD(GPR(0)), rA(GPR(0)), rB(GPR(4))]);
     assert_asm!(ins, "addc r0, r0, r4");
+    assert_asm!(0x7C642C14, "addco r3, r4, r5");
 }
 
 #[test]
 fn test_ins_addi() {
     let ins = Ins::new(0x38010140, 0x8000_0000u32);
     assert_eq!(ins.op, Addi);
-    assert_eq!(
-        ins.fields(),
-        vec![rD(GPR(0)), rA(GPR(1)), simm(Simm(0x140))]
-    );
-    assert_eq!(ins.defs(), vec![rD(GPR(0))]);
-    assert_eq!(ins.uses(), vec![rA(GPR(1))]);
+    assert_eq!(ins.fields(), [rD(GPR(0)), rA(GPR(1)), simm(Simm(0x140))]);
+    assert_eq!(ins.defs(), [rD(GPR(0))]);
+    assert_eq!(ins.uses(), [rA(GPR(1))]);
     assert_asm!(ins, "addi r0, r1, 0x140");
 
     assert_asm!(0x38010008, "addi r0, r1, 0x8");
     assert_asm!(0x38010010, "addi r0, r1, 0x10");
     assert_asm!(0x38010018, "addi r0, r1, 0x18");
     assert_asm!(0x38010140, "addi r0, r1, 0x140");
-    assert_asm!(0x38049000, "addi r0, r4, -0x7000");
+    assert_asm!(0x38049000, "subi r0, r4, 0x7000");
+    assert_asm!(0x38048000, "addi r0, r4, -0x8000");
     assert_asm!(0x38a00000, "li r5, 0x0");
 }
 
 #[test]
 fn test_ins_adde() {
     assert_asm!(0x7c006114, "adde r0, r0, r12");
+    assert_asm!(0x7C642D14, "addeo r3, r4, r5");
 }
 
 #[test]
 fn test_ins_addic() {
-    assert_asm!(0x3060ffff, "addic r3, r0, -0x1");
+    assert_asm!(0x3060ffff, "subic r3, r0, 0x1");
     assert_asm!(0x30840800, "addic r4, r4, 0x800");
     assert_asm!(0x30a50008, "addic r5, r5, 0x8");
     assert_asm!(0x37DF001C, "addic. r30, r31, 0x1c");
     assert_asm!(0x37E06278, "addic. r31, r0, 0x6278");
-    assert_asm!(0x37E3FFFF, "addic. r31, r3, -0x1");
+    assert_asm!(0x37E3FFFF, "subic. r31, r3, 0x1");
 }
 
 #[test]
 fn test_ins_addis() {
     assert_asm!(0x3C030000, "addis r0, r3, 0x0");
-    assert_asm!(0x3C038000, "addis r0, r3, 0x8000");
+    assert_asm!(0x3C037FFF, "addis r0, r3, 0x7fff");
+    assert_asm!(0x3C038000, "subis r0, r3, 0x8000");
+    assert_asm!(0x3C03FFFF, "subis r0, r3, 0x1");
     assert_asm!(0x3D00EFCE, "lis r8, 0xefce");
 }
 
@@ -128,6 +138,7 @@ fn test_ins_bc() {
     assert_asm!(0x40410035, "bdzfl gt, 0x34");
     assert_asm!(0x41430023, "bdztla so, 0x20");
     assert_asm!(0x4108FFE3, "bdnztla 4*cr2+lt, -0x20");
+    assert_asm!(0x40080010, "bdnzf 4*cr2+lt, 0x10");
     assert_asm!(0x40A20008, "bne+ 0x8");
 }
 
@@ -156,6 +167,7 @@ fn test_ins_bclr() {
     assert_asm!(0x4E800021, "blrl");
     assert_asm!(0x4D000020, "bdnztlr lt");
     assert_asm!(0x4C1F0021, "bdnzflrl 4*cr7+so");
+    assert_asm!(0x4C410020, "bdzflr gt");
 }
 
 #[test]
@@ -267,6 +279,14 @@ fn test_ins_divwu() {
     assert_asm!(0x7C69E396, "divwu r3, r9, r28");
 }
 
+#[test]
+fn test_ins_eqv() {
+    assert_asm!(0x7C832A38, "eqv r3, r4, r5");
+    assert_asm!(0x7C832A39, "eqv. r3, r4, r5");
+    // Bit 21 is part of the extended opcode.
+    assert_eq!(Ins::new(0x7C832E38, 0x8000_0000).op, Illegal);
+}
+
 #[test]
 fn test_ins_extsb() {
     assert_asm!(0x7C650774, "extsb r5, r3");
@@ -553,6 +573,7 @@ fn test_ins_mfcr() {
 #[test]
 fn test_ins_mffs() {
     assert_asm!(0xFC00048E, "mffs f0");
+    assert_asm!(0xFC00048F, "mffs. f0");
 }
 
 #[test]
@@ -563,7 +584,11 @@ fn test_ins_mfmsr() {
 #[test]
 fn test_ins_mfspr() {
     assert_asm!(0x7E1A02A6, "mfsrr0 r16");
-    assert_asm!(0x7C70FAA6, "mfspr r3, HID0");
+    assert_asm!(0x7C70FAA6, "mfhid0 r3");
+    assert_asm!(0x7C78E2A6, "mfhid2 r3");
+    assert_asm!(0x7C71E2A6, "mfspr r3, GQR1");
+    assert_asm!(0x7C7F42A6, "mfpvr r3");
+    assert_asm!(0x7C75FAA6, "mfspr r3, DABR");
     assert_asm!(0x7C7482A6, "mfibatu r3, 2");
     assert_asm!(0x7C7782A6, "mfibatl r3, 3");
 }
@@ -575,12 +600,14 @@ fn test_ins_mfsr() {
 
 #[test]
 fn test_ins_mftb() {
-    assert_asm!(0x7C8C42E6, "mftb r4, 268");
+    assert_asm!(0x7C8C42E6, "mftb r4");
+    assert_asm!(0x7C8D42E6, "mftbu r4");
 }
 
 #[test]
 fn test_ins_mtcrf() {
-    assert_asm!(0x7C6FF120, "mtcrf 255, r3");
+    assert_asm!(0x7C6FF120, "mtcr r3");
+    assert_asm!(0x7C680120, "mtcrf 128, r3");
 }
 
 #[test]
@@ -607,7 +634,8 @@ fn test_ins_mtmsr() {
 #[test]
 fn test_ins_mtspr() {
     assert_asm!(0x7E75FBA6, "mtspr DABR, r19");
-    assert_asm!(0x7C70FBA6, "mtspr HID0, r3");
+    assert_asm!(0x7C70FBA6, "mthid0 r3");
+    assert_asm!(0x7C77E3A6, "mtspr GQR7, r3");
     assert_asm!(0x7C7603A6, "mtdec r3");
     assert_asm!(0x7C7043A6, "mtsprg 0, r3");
     assert_asm!(0x7C7143A6, "mtsprg 1, r3");
@@ -652,11 +680,14 @@ fn test_ins_nand() {
 #[test]
 fn test_ins_neg() {
     assert_asm!(0x7C0600D0, "neg r0, r6");
+    assert_asm!(0x7C6404D0, "nego r3, r4");
+    assert_asm!(0x7C6404D1, "nego. r3, r4");
 }
 
 #[test]
 fn test_ins_nor() {
-    assert_asm!(0x7C0500F8, "nor r5, r0, r0");
+    assert_asm!(0x7C0500F8, "not r5, r0");
+    assert_asm!(0x7C0508F8, "nor r5, r0, r1");
 }
 
 #[test]
@@ -695,7 +726,7 @@ fn test_ins_psq_lx() {
     assert_eq!(ins.op, PsqLx);
     assert_eq!(
         ins.fields(),
-        vec![
+        [
             frD(FPR(0)),
             rA(GPR(0)),
             rB(GPR(0)),
@@ -703,8 +734,8 @@ fn test_ins_psq_lx() {
             ps_IX(GQR(0)),
         ]
     );
-    assert_eq!(ins.defs(), vec![frD(FPR(0))]);
-    assert_eq!(ins.uses(), vec![rB(GPR(0))]);
+    assert_eq!(ins.defs(), [frD(FPR(0))]);
+    assert_eq!(ins.uses(), [rB(GPR(0))]);
 
     assert_asm!(0x1000000C, "psq_lx f0, r0, r0, 0, qr0");
 }
@@ -728,11 +759,13 @@ fn test_ins_psq_stx() {
 #[test]
 fn test_ins_ps_abs() {
     assert_asm!(0x10A03210, "ps_abs f5, f6");
+    assert_asm!(0x10A03211, "ps_abs. f5, f6");
 }
 
 #[test]
 fn test_ins_ps_add() {
     assert_asm!(0x1006382A, "ps_add f0, f6, f7");
+    assert_asm!(0x1006382B, "ps_add. f0, f6, f7");
 }
 
 #[test]
@@ -753,6 +786,7 @@ fn test_ins_ps_cmpu1() {
 #[test]
 fn test_ins_ps_madd() {
     assert_asm!(0x112141FA, "ps_madd f9, f1, f7, f8");
+    assert_asm!(0x112141FB, "ps_madd. f9, f1, f7, f8");
 }
 
 #[test]
@@ -768,6 +802,7 @@ fn test_ins_ps_madds1() {
 #[test]
 fn test_ins_ps_merge00() {
     assert_asm!(0x10400420, "ps_merge00 f2, f0, f0");
+    assert_asm!(0x10400421, "ps_merge00. f2, f0, f0");
 }
 
 #[test]
@@ -788,6 +823,7 @@ fn test_ins_ps_merge11() {
 #[test]
 fn test_ins_ps_mr() {
     assert_asm!(0x10200090, "ps_mr f1, f0");
+    assert_asm!(0x10200091, "ps_mr. f1, f0");
 }
 
 #[test]
@@ -798,6 +834,7 @@ fn test_ins_ps_msub() {
 #[test]
 fn test_ins_ps_mul() {
     assert_asm!(0x10000032, "ps_mul f0, f0, f0");
+    assert_asm!(0x10000033, "ps_mul. f0, f0, f0");
 }
 
 #[test]
@@ -848,6 +885,7 @@ fn test_ins_ps_sum0() {
 #[test]
 fn test_ins_ps_sum1() {
     assert_asm!(0x10A12956, "ps_sum1 f5, f1, f5, f5");
+    assert_asm!(0x10A12957, "ps_sum1. f5, f1, f5, f5");
 }
 
 #[test]
@@ -857,8 +895,11 @@ fn test_ins_rfi() {
 
 #[test]
 fn test_ins_rlwimi() {
-    assert_asm!(0x500306FE, "rlwimi r3, r0, 0, 27, 31");
-    assert_asm!(0x50032D74, "rlwimi r3, r0, 5, 21, 26");
+    assert_asm!(0x500306FE, "insrwi r3, r0, 5, 27");
+    assert_asm!(0x50032D74, "insrwi r3, r0, 6, 21");
+    assert_asm!(0x5083C00E, "insrwi r3, r4, 8, 0");
+    assert_asm!(0x5083842E, "inslwi r3, r4, 8, 16");
+    assert_asm!(0x50834438, "rlwimi r3, r4, 8, 16, 28");
     assert_asm!(0x5400003F, "clrrwi. r0, r0, 0");
 }
 
@@ -1068,6 +1109,10 @@ fn test_tw() {
     assert_asm!(0x7C063808, "tw 0, r6, r7");
     assert_asm!(0x7C842808, "tweq r4, r5");
     assert_asm!(0x7CA42808, "twlge r4, r5");
+    assert_asm!(0x7E042808, "twlt r4, r5");
+    assert_asm!(0x7F042808, "twne r4, r5");
+    assert_asm!(0x7C442808, "twllt r4, r5");
+    assert_asm!(0x7FE42808, "twu r4, r5");
     assert_asm!(0x7FE00008, "trap");
 }
 
@@ -1077,6 +1122,7 @@ fn test_twi() {
     assert_asm!(0x0D07FFFF, "twgti r7, -0x1");
     assert_asm!(0x0CC4FF01, "twllei r4, -0xff");
     assert_asm!(0x0FE40003, "twui r4, 0x3");
+    assert_asm!(0x0C830000, "tweqi r3, 0x0");
 }
 
 #[test]
@@ -1094,3 +1140,178 @@ fn test_ins_xori() {
 fn test_ins_xoris() {
     assert_asm!(0x6E3D8000, "xoris r29, r17, 0x8000");
 }
+
+#[test]
+fn test_opcode_metadata() {
+    assert_eq!(Addi.description(), "Add Immediate");
+    assert_eq!(Addi.form(), Some(Form::D));
+    assert_eq!(Addi.category(), Some(Category::Arithmetic));
+    assert_eq!(Subf.form(), Some(Form::XO));
+    assert_eq!(Rlwinm.category(), Some(Category::Logical));
+    assert_eq!(Mtspr.form(), Some(Form::XFX));
+    assert_eq!(PsqLx.form(), Some(Form::PSQX));
+    assert_eq!(PsqL.category(), Some(Category::Load));
+    assert_eq!(PsMadd.form(), Some(Form::A));
+    assert_eq!(Bclr.category(), Some(Category::Branch));
+    assert_eq!(Illegal.form(), None);
+    assert_eq!(Illegal.bitmask(), 0);
+
+    for op in Opcode::ALL {
+        assert_eq!(op.pattern() & !op.bitmask(), 0, "{}", op);
+        assert_eq!(Opcode::detect(op.pattern()), op, "{}", op);
+    }
+}
+
+#[test]
+fn test_ins_validity() {
+    let validity = |code| Ins::new(code, 0x8000_0000).validity();
+    // lwzu r0, 0x8(r3)
+    assert_eq!(validity(0x84030008), Validity::Valid);
+    // lwzu r3, 0x8(r3)
+    assert_eq!(validity(0x84630008), Validity::InvalidForm);
+    // lwzu r3, 0x8(r0)
+    assert_eq!(validity(0x84600008), Validity::InvalidForm);
+    // lmw r29, 0x8(r1)
+    assert_eq!(validity(0xBBA10008), Validity::Valid);
+    // lmw r29, 0x8(r30)
+    assert_eq!(validity(0xBBBE0008), Validity::InvalidForm);
+    // lswi r5, r4, 32
+    assert_eq!(validity(0x7CA404AA), Validity::Valid);
+    // lswi r5, r6, 32
+    assert_eq!(validity(0x7CA604AA), Validity::InvalidForm);
+    // lswi r31, r0, 8 wraps around to r0
+    assert_eq!(validity(0x7FE044AA), Validity::InvalidForm);
+    // bctr
+    assert_eq!(validity(0x4E800420), Validity::Valid);
+    assert_eq!(validity(0x4E808420), Validity::ReservedBits);
+    // bdnzctr
+    assert_eq!(validity(0x4E000420), Validity::InvalidForm);
+    // stwcx. without Rc
+    assert_eq!(validity(0x7C00012C), Validity::Illegal);
+    assert!(Ins::new(0x7C00012D, 0).is_valid());
+    assert_eq!(Bclr.reserved_bits(), 0xE000);
+    // lwzx r3, r3, r4 with bit 31 set
+    assert_eq!(validity(0x7C63202E), Validity::Valid);
+    assert_eq!(validity(0x7C63202F), Validity::ReservedBits);
+    // sync with Rc or bit 21 set
+    assert_eq!(validity(0x7C0004AC), Validity::Valid);
+    assert_eq!(validity(0x7C0004AD), Validity::ReservedBits);
+    assert_eq!(validity(0x7C2004AC), Validity::ReservedBits);
+    // mfspr r3, LR with bit 31 set
+    assert_eq!(validity(0x7C6802A7), Validity::ReservedBits);
+    // fabs f1, f2 with frA set
+    assert_eq!(validity(0xFC231210), Validity::ReservedBits);
+    // cmpw cr1, r3, r4 with bit 9 set
+    assert_eq!(validity(0x7CC32000), Validity::ReservedBits);
+    // Words with reserved bits decode to the same opcode.
+    assert_eq!(Ins::new(0x7C63202F, 0).op, Lwzx);
+    assert_eq!(Ins::new(0x7C2004AC, 0).op, Sync);
+}
+
+#[test]
+fn test_simplified_access() {
+    let access = |code| Ins::new(code, 0x8000_0000).simplified().access;
+    // lwz r3, 0x8(r1)
+    assert_eq!(
+        access(0x80610008),
+        [
+            Some(Access::Write),
+            None,
+            Some(Access::Base { update: false })
+        ]
+    );
+    // stwu r1, -0x10(r1)
+    assert_eq!(
+        access(0x9421FFF0),
+        [
+            Some(Access::Read),
+            None,
+            Some(Access::Base { update: true })
+        ]
+    );
+    // insrwi r3, r4, 8, 16
+    assert_eq!(
+        access(0x5083442E),
+        [Some(Access::ReadWrite), Some(Access::Read), None, None]
+    );
+    // mr r3, r4
+    assert_eq!(
+        access(0x7C832378),
+        [Some(Access::Write), Some(Access::Read)]
+    );
+    // bne cr1, 0x8
+    let ins = Ins::new(0x40860008, 0x8000_0000).simplified();
+    assert_eq!(
+        ins.operands().collect::<Vec<_>>(),
+        [
+            (Argument::CRField(CRField(1)), Some(Access::Read)),
+            (Argument::BranchDest(BranchDest(8)), None)
+        ]
+    );
+    // bdnzt 4*cr1+eq, 0x8
+    assert_eq!(access(0x41060008), [Some(Access::Read), None]);
+    // bclr 20, 4*cr1+eq, 0 in its basic form
+    assert_eq!(
+        SimplifiedIns::basic_form(Ins::new(0x4C860020, 0x8000_0000)).access,
+        [None, Some(Access::Read), None]
+    );
+    // mtspr HID4, r3
+    let ins = Ins::new(0x7C73FBA6, 0x8000_0000).simplified();
+    assert_eq!(
+        ins.operands().collect::<Vec<_>>(),
+        [
+            (Argument::SPR(SPR(1011)), Some(Access::Write)),
+            (Argument::GPR(GPR(3)), Some(Access::Read))
+        ]
+    );
+}
+
+#[test]
+fn test_ins_typed_fields() {
+    // addi r3, r1, 0x10
+    let ins = Ins::new(0x38610010, 0x8000_0000);
+    assert_eq!(ins.rd(), Some(GPR(3)));
+    assert_eq!(ins.ra(), Some(GPR(1)));
+    assert_eq!(ins.simm(), Some(Simm(0x10)));
+    assert_eq!(ins.rb(), None);
+    assert_eq!(ins.spr(), None);
+    // mfspr r3, HID4
+    let ins = Ins::new(0x7C73FAA6, 0x8000_0000);
+    assert_eq!(ins.spr(), Some(SPR(1011)));
+    assert_eq!(ins.ra(), None);
+    // bne cr1, 0x8
+    let ins = Ins::new(0x40860008, 0x8000_0000);
+    assert_eq!(ins.bi(), Some(CRBit(6)));
+    assert_eq!(ins.bd(), Some(BranchDest(8)));
+    assert_eq!(Ins::new(0, 0).rd(), None);
+}
+
+#[test]
+fn test_ins_variant() {
+    // ps_add f1, f2, f3
+    let code = 0x1022182A;
+    assert_eq!(Ins::new_for(code, 0, Variant::Ppc750).op, Illegal);
+    assert_eq!(Ins::new_for(code, 0, Variant::Gekko).op, PsAdd);
+    assert_eq!(Ins::new(code, 0).op, PsAdd);
+    assert!(!DcbzL.is_available(Variant::Ppc750));
+    assert!(Add.is_available(Variant::Ppc750));
+    let ops: Vec<Opcode> = disasm_iter(&[0x10, 0x22, 0x18, 0x2A], 0)
+        .variant(Variant::Ppc750)
+        .map(|ins| ins.op)
+        .collect();
+    assert_eq!(ops, [Illegal]);
+}
+
+#[test]
+fn test_spr_variant() {
+    assert_eq!(SPR(1011).name_for(Variant::Broadway), Some("HID4"));
+    assert_eq!(SPR(1011).name_for(Variant::Gekko), None);
+    assert_eq!(SPR(944).name_for(Variant::Espresso), Some("HID5"));
+    assert_eq!(SPR(920).name_for(Variant::Ppc750), None);
+    assert_eq!(SPR(560).name_for(Variant::Broadway), Some("IBAT4U"));
+    assert_eq!(SPR(575).name_for(Variant::Espresso), Some("DBAT7L"));
+    assert_eq!(SPR(568).name_for(Variant::Gekko), None);
+    assert_eq!(SPR(920).name(), Some("HID2"));
+    assert_eq!("HID5".parse::<SPR>(), Ok(SPR(944)));
+    assert_eq!("DBAT4U".parse::<SPR>(), Ok(SPR(568)));
+}
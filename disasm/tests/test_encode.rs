@@ -0,0 +1,160 @@
+use ppc750cl::encode::EncodeError;
+use ppc750cl::prelude::*;
+
+#[test]
+fn test_encode_basic() {
+    assert_eq!(
+        Ins::encode(Addi, &[rD(GPR(0)), rA(GPR(1)), simm(Simm(0x140))], &[]),
+        Ok(0x38010140)
+    );
+    // Fields may be given in any order.
+    assert_eq!(
+        Ins::encode(Lwz, &[rA(GPR(31)), rD(GPR(3)), offset(Offset(0x10))], &[]),
+        Ok(0x807f0010)
+    );
+    assert_eq!(
+        Ins::encode(
+            Add,
+            &[rD(GPR(3)), rA(GPR(4)), rB(GPR(5))],
+            &[Modifier::OE, Modifier::Rc]
+        ),
+        Ok(0x7c642e15)
+    );
+    assert_eq!(
+        Ins::encode(Mfspr, &[rD(GPR(3)), spr(SPR(920))], &[]),
+        Ok(0x7c78e2a6)
+    );
+    assert_eq!(
+        Ins::encode(
+            PsqL,
+            &[
+                frD(FPR(31)),
+                ps_offset(Offset(0xc)),
+                rA(GPR(1)),
+                ps_W(OpaqueU(0)),
+                ps_I(GQR(0))
+            ],
+            &[]
+        ),
+        Ok(0xE3E1000C)
+    );
+    assert_eq!(
+        Ins::encode(
+            Bc,
+            &[BO(OpaqueU(12)), BI(CRBit(2)), BD(BranchDest(0x20))],
+            &[Modifier::BP, Modifier::LK]
+        ),
+        Ok(0x41a20021)
+    );
+    assert_eq!(Ins::encode(Sync, &[], &[]), Ok(0x7c0004ac));
+}
+
+#[test]
+fn test_encode_errors() {
+    assert_eq!(
+        Ins::encode(Illegal, &[], &[]),
+        Err(EncodeError::IllegalOpcode)
+    );
+    assert_eq!(
+        Ins::encode(Addi, &[rD(GPR(32)), rA(GPR(1)), simm(Simm(1))], &[]),
+        Err(EncodeError::OutOfRange {
+            field: "rD",
+            value: 32
+        })
+    );
+    assert_eq!(
+        Ins::encode(
+            PsqL,
+            &[
+                frD(FPR(1)),
+                ps_offset(Offset(0x800)),
+                rA(GPR(1)),
+                ps_W(OpaqueU(0)),
+                ps_I(GQR(0))
+            ],
+            &[]
+        ),
+        Err(EncodeError::OutOfRange {
+            field: "ps_offset",
+            value: 0x800
+        })
+    );
+    assert_eq!(
+        Ins::encode(B, &[LI(BranchDest(0x3))], &[]),
+        Err(EncodeError::OutOfRange {
+            field: "LI",
+            value: 3
+        })
+    );
+    assert_eq!(
+        Ins::encode(Addi, &[rD(GPR(0)), rA(GPR(1))], &[]),
+        Err(EncodeError::MissingField("simm"))
+    );
+    assert_eq!(
+        Ins::encode(Addi, &[rD(GPR(0)), rA(GPR(1)), uimm(Uimm(1))], &[]),
+        Err(EncodeError::UnexpectedField("uimm"))
+    );
+    assert_eq!(
+        Ins::encode(Addi, &[rD(GPR(0)), rD(GPR(1)), simm(Simm(1))], &[]),
+        Err(EncodeError::UnexpectedField("rD"))
+    );
+    assert_eq!(
+        Ins::encode(Mtspr, &[spr(SPR(9)), rS(GPR(3)), ctr], &[]),
+        Err(EncodeError::UnexpectedField("ctr"))
+    );
+    assert_eq!(
+        Ins::encode(
+            Addi,
+            &[rD(GPR(0)), rA(GPR(1)), simm(Simm(1))],
+            &[Modifier::Rc]
+        ),
+        Err(EncodeError::InvalidModifier(Modifier::Rc))
+    );
+    assert_eq!(
+        Ins::encode(
+            Bc,
+            &[BO(OpaqueU(12)), BI(CRBit(2)), BD(BranchDest(-0x20))],
+            &[Modifier::BP]
+        ),
+        Err(EncodeError::InvalidModifier(Modifier::BP))
+    );
+    assert_eq!(
+        Ins::encode(Lwzu, &[rD(GPR(3)), offset(Offset(4)), rA(GPR(0))], &[]),
+        Err(EncodeError::ZeroRegister("rA"))
+    );
+    // Plain loads read rA as zero instead.
+    assert_eq!(
+        Ins::encode(Lwz, &[rD(GPR(3)), offset(Offset(4)), rA(GPR(0))], &[]),
+        Ok(0x80600004)
+    );
+}
+
+#[test]
+fn test_encode_roundtrip_random() {
+    // Xorshift sequence over the instruction space.
+    let mut code = 0x1234_5678u32;
+    for _ in 0..500_000 {
+        code ^= code << 13;
+        code ^= code >> 17;
+        code ^= code << 5;
+        let ins = Ins::new(code, 0x8000_0000);
+        if ins.op == Illegal {
+            continue;
+        }
+        let fields = ins.fields();
+        let encoded = match Ins::encode(ins.op, &fields, &[]) {
+            Ok(encoded) => encoded,
+            Err(EncodeError::ZeroRegister(_)) => continue,
+            Err(e) => panic!("{:#010x}: {}", code, e),
+        };
+        let decoded = Ins::new(encoded, 0x8000_0000);
+        assert_eq!(decoded.op, ins.op, "{:#010x} -> {:#010x}", code, encoded);
+        assert_eq!(
+            decoded.fields(),
+            fields,
+            "{:#010x} -> {:#010x}",
+            code,
+            encoded
+        );
+    }
+}
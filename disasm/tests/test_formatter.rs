@@ -0,0 +1,84 @@
+use ppc750cl::formatter::{FormatOptions, ImmediateStyle, RegisterStyle};
+use ppc750cl::prelude::*;
+
+fn format(code: u32, options: &FormatOptions) -> String {
+    options
+        .display(Ins::new(code, 0x8000_0000).simplified())
+        .to_string()
+}
+
+#[test]
+fn test_format_default() {
+    let options = FormatOptions::default();
+    assert_eq!(format(0x807f0010, &options), "lwz r3, 0x10(r31)");
+    assert_eq!(format(0x8061fff0, &options), "lwz r3, -0x10(r1)");
+    assert_eq!(format(0x4E800020, &options), "blr");
+    assert_eq!(format(0x7c79faa6, &options), "mfspr r3, L2CR");
+}
+
+#[test]
+fn test_format_gnu() {
+    let options = FormatOptions::gnu();
+    assert_eq!(format(0x807f0010, &options), "lwz %r3, 16(%r31)");
+    assert_eq!(format(0x8061fff0, &options), "lwz %r3, -16(%r1)");
+    assert_eq!(format(0x7c79faa6, &options), "mfspr %r3, 1017");
+    assert_eq!(
+        format(0x4cc0fa02, &options),
+        "crand 4*%cr1+eq, lt, 4*%cr7+so"
+    );
+    assert_eq!(format(0xfc0b6040, &options), "fcmpo %cr0, %f11, %f12");
+}
+
+#[test]
+fn test_format_absolute_branches() {
+    let options = FormatOptions {
+        absolute_branches: true,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format(0x48000abd, &options), "bl 0x80000abc");
+    assert_eq!(format(0x4bfffff0, &options), "b 0x7ffffff0");
+    assert_eq!(format(0x4182fff0, &options), "beq 0x7ffffff0");
+    assert_eq!(format(0x4200ffe0, &options), "bdnz 0x7fffffe0");
+    // Absolute branches ignore the instruction address.
+    assert_eq!(format(0x48000102, &options), "ba 0x100");
+    assert_eq!(format(0x41820103, &options), "beqla 0x100");
+    assert_eq!(format(0x4E800020, &options), "blr");
+    assert_eq!(format(0x48000abd, &FormatOptions::gnu()), "bl 0x80000abc");
+}
+
+#[test]
+fn test_format_plain_registers() {
+    let options = FormatOptions {
+        registers: RegisterStyle::Number,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format(0x7c642a14, &options), "add 3, 4, 5");
+    assert_eq!(format(0x4cc0fa02, &options), "crand 6, 0, 31");
+    assert_eq!(format(0xE3E1000C, &options), "psq_l 31, 0xc(1), 0, 0");
+}
+
+#[test]
+fn test_format_uppercase_basic_aligned() {
+    let options = FormatOptions {
+        immediates: ImmediateStyle::Decimal,
+        uppercase: true,
+        simplified: false,
+        operand_column: 8,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format(0x4E800020, &options), "BCLR    20, lt, 0");
+    assert_eq!(format(0x7c642e15, &options), "ADDO.   r3, r4, r5");
+    assert_eq!(format(0x38600010, &options), "ADDI    r3, r0, 16");
+    assert_eq!(format(0x7C0004AC, &options), "SYNC");
+    assert_eq!(format(0x10400420, &options), "PS_MERGE00 f2, f0, f0");
+}
+
+#[test]
+fn test_format_variant() {
+    let mut options = FormatOptions::default();
+    assert_eq!(format(0x7C73FAA6, &options), "mfspr r3, HID4");
+    options.variant = Variant::Gekko;
+    assert_eq!(format(0x7C73FAA6, &options), "mfspr r3, 1011");
+    options.variant = Variant::Espresso;
+    assert_eq!(format(0x7C70EAA6, &options), "mfspr r3, HID5");
+}
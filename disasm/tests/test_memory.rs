@@ -0,0 +1,92 @@
+use ppc750cl::prelude::*;
+
+fn ins(code: u32) -> Ins {
+    Ins::new(code, 0x8000_0000)
+}
+
+#[test]
+fn test_memory_integer() {
+    // lwzu r0, 0x8(r3)
+    let access = ins(0x84030008).memory_access().unwrap();
+    assert_eq!(
+        access,
+        MemAccess {
+            kind: MemKind::Load,
+            base: Some(GPR(3)),
+            offset: MemOffset::Displacement(8),
+            data: MemData::Int {
+                width: 4,
+                signed: false
+            },
+            update: true,
+            byte_reversed: false,
+        }
+    );
+    assert_eq!(access.size(), Some(4));
+    // lha r3, -0x10(r4)
+    let access = ins(0xA864FFF0).memory_access().unwrap();
+    assert_eq!(access.offset, MemOffset::Displacement(-0x10));
+    assert_eq!(
+        access.data,
+        MemData::Int {
+            width: 2,
+            signed: true
+        }
+    );
+    assert!(!access.update);
+    // lwbrx r3, r0, r5
+    let access = ins(0x7C602C2C).memory_access().unwrap();
+    assert_eq!(access.base, None);
+    assert_eq!(access.offset, MemOffset::Index(GPR(5)));
+    assert!(access.byte_reversed);
+}
+
+#[test]
+fn test_memory_float() {
+    // stfd f1, 0x18(r1)
+    let access = ins(0xD8210018).memory_access().unwrap();
+    assert_eq!(access.kind, MemKind::Store);
+    assert_eq!(access.base, Some(GPR(1)));
+    assert_eq!(access.data, MemData::Double);
+    assert_eq!(access.size(), Some(8));
+    // psq_l f31, 0xc(r1), 0, qr0
+    let access = ins(0xE3E1000C).memory_access().unwrap();
+    assert_eq!(
+        access.data,
+        MemData::Quantized {
+            gqr: GQR(0),
+            single: false
+        }
+    );
+    assert_eq!(access.size(), None);
+    // psq_l f1, 0x0(r3), 1, qr2
+    let access = ins(0xE023A000).memory_access().unwrap();
+    assert_eq!(
+        access.data,
+        MemData::Quantized {
+            gqr: GQR(2),
+            single: true
+        }
+    );
+}
+
+#[test]
+fn test_memory_multiple() {
+    // lmw r29, 0x8(r1)
+    let access = ins(0xBBA10008).memory_access().unwrap();
+    assert_eq!(access.data, MemData::Multiple { count: 3 });
+    assert_eq!(access.size(), Some(12));
+    assert!(!access.update);
+    // lswi r5, r4, 0
+    let access = ins(0x7CA404AA).memory_access().unwrap();
+    assert_eq!(access.data, MemData::String { len: Some(32) });
+    assert_eq!(access.offset, MemOffset::Displacement(0));
+}
+
+#[test]
+fn test_memory_none() {
+    // addi r3, r3, 0x1
+    assert_eq!(ins(0x38630001).memory_access(), None);
+    // dcbf r0, r3
+    assert_eq!(ins(0x7C0018AC).memory_access(), None);
+}
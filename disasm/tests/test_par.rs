@@ -0,0 +1,60 @@
+#![cfg(feature = "rayon")]
+
+use ppc750cl::prelude::*;
+use ppc750cl::{disasm_iter, disasm_par, disasm_par_chunks, disasm_par_for, Opcode, Variant};
+use rayon::prelude::*;
+
+fn sample_code() -> Vec<u8> {
+    // Scattered instruction words, followed by a partial word.
+    let mut code = Vec::new();
+    for i in 0..0x10000u32 {
+        code.extend_from_slice(&(i.wrapping_mul(0x9E37_79B9)).to_be_bytes());
+    }
+    code.extend_from_slice(&[0x60, 0x00]);
+    code
+}
+
+#[test]
+fn test_disasm_par() {
+    let code = sample_code();
+    let expected: Vec<Ins> = disasm_iter(&code, 0x8000_3100).collect();
+    assert_eq!(disasm_par(&code, 0x8000_3100), expected);
+    assert!(disasm_par(&code[..3], 0).is_empty());
+}
+
+#[test]
+fn test_disasm_par_for() {
+    // ps_add f1, f2, f3; ps_mr f1, f2; psq_l f1, 0x10(r3), 0, qr0
+    let code = [
+        0x10, 0x22, 0x18, 0x2A, 0x10, 0x20, 0x10, 0x90, 0xE0, 0x23, 0x00, 0x10,
+    ];
+    for variant in Variant::ALL.iter().copied() {
+        let expected: Vec<Ins> = disasm_iter(&code, 0x8000_3100).variant(variant).collect();
+        assert_eq!(disasm_par_for(&code, 0x8000_3100, variant), expected);
+    }
+    let ops: Vec<Opcode> = disasm_par_for(&code, 0, Variant::Ppc750)
+        .into_iter()
+        .map(|ins| ins.op)
+        .collect();
+    assert_eq!(ops, [Opcode::Illegal; 3]);
+}
+
+#[test]
+fn test_disasm_par_chunks() {
+    let code = sample_code();
+    for variant in Variant::ALL.iter().copied() {
+        let expected: Vec<Ins> = disasm_iter(&code, 0x8000_3100).variant(variant).collect();
+        assert_eq!(disasm_par_for(&code, 0x8000_3100, variant), expected);
+        for chunk_len in [1, 7, 0x1000, 0x100000] {
+            let chunks: Vec<Vec<Ins>> = disasm_par_chunks(&code, 0x8000_3100, chunk_len, variant)
+                .map(|chunk| chunk.collect())
+                .collect();
+            assert_eq!(chunks.concat(), expected);
+        }
+    }
+    // ps_add f1, f2, f3
+    let ops: Vec<Opcode> = disasm_par_chunks(&[0x10, 0x22, 0x18, 0x2A], 0, 1, Variant::Ppc750)
+        .flat_map_iter(|chunk| chunk.map(|ins| ins.op))
+        .collect();
+    assert_eq!(ops, [Opcode::Illegal]);
+}
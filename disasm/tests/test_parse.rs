@@ -0,0 +1,88 @@
+use ppc750cl::prelude::*;
+use ppc750cl::{Opcode, ParseError};
+
+#[test]
+fn test_parse_registers() {
+    assert_eq!("r31".parse(), Ok(GPR(31)));
+    assert_eq!("f0".parse(), Ok(FPR(0)));
+    assert_eq!("cr7".parse(), Ok(CRField(7)));
+    assert_eq!("qr2".parse(), Ok(GQR(2)));
+    assert_eq!("15".parse(), Ok(SR(15)));
+    assert_eq!("r32".parse::<GPR>(), Err(ParseError { expected: "GPR" }));
+    assert!("f".parse::<FPR>().is_err());
+    assert!("16".parse::<SR>().is_err());
+    // Register numbers are decimal, without sign or leading zeros.
+    assert!("r+3".parse::<GPR>().is_err());
+    assert!("r-0".parse::<GPR>().is_err());
+    assert!("r01".parse::<GPR>().is_err());
+    assert!("r 1".parse::<GPR>().is_err());
+    assert!("f+1".parse::<FPR>().is_err());
+    assert!("cr00".parse::<CRField>().is_err());
+    assert!("qr+0".parse::<GQR>().is_err());
+    assert!("4*cr01+eq".parse::<CRBit>().is_err());
+    for i in 0..32 {
+        assert_eq!(GPR(i).to_string().parse(), Ok(GPR(i)));
+        assert_eq!(FPR(i).to_string().parse(), Ok(FPR(i)));
+        assert_eq!(CRBit(i).to_string().parse(), Ok(CRBit(i)));
+    }
+}
+
+#[test]
+fn test_parse_crbit() {
+    assert_eq!("4*cr3+eq".parse(), Ok(CRBit(14)));
+    assert_eq!("so".parse(), Ok(CRBit(3)));
+    assert_eq!("31".parse(), Ok(CRBit(31)));
+    assert!("4*cr8+lt".parse::<CRBit>().is_err());
+}
+
+#[test]
+fn test_parse_spr() {
+    assert_eq!("HID2".parse(), Ok(SPR(920)));
+    assert_eq!("gqr2".parse(), Ok(SPR(914)));
+    assert_eq!("1017".parse(), Ok(SPR(1017)));
+    assert_eq!("0x3f0".parse(), Ok(SPR(1008)));
+    assert!("HID9".parse::<SPR>().is_err());
+    assert!("1024".parse::<SPR>().is_err());
+    for i in 0..1024 {
+        assert_eq!(SPR(i).to_string().parse(), Ok(SPR(i)));
+    }
+}
+
+#[test]
+fn test_parse_opcode() {
+    assert_eq!("addi".parse(), Ok(Opcode::Addi));
+    assert_eq!("andi.".parse(), Ok(Opcode::Andi_));
+    assert_eq!("stwcx.".parse(), Ok(Opcode::Stwcx_));
+    assert_eq!("bclr".parse(), Ok(Opcode::Bclr));
+    assert!("nop".parse::<Opcode>().is_err());
+    assert!("add+".parse::<Opcode>().is_err());
+    assert_eq!("addo.".parse(), Ok(Opcode::Add));
+    assert_eq!("bclrl".parse(), Ok(Opcode::Bclr));
+    assert_eq!("bc+".parse(), Ok(Opcode::Bc));
+    assert_eq!("bla".parse(), Ok(Opcode::B));
+    for op in Opcode::ALL {
+        assert_eq!(op.mnemonic().parse(), Ok(op), "{}", op);
+    }
+    // addo., bclrl, bla, bc+
+    for code in [0x7C642E15, 0x4E800021, 0x48000003, 0x41E00010] {
+        let ins = Ins::new(code, 0x8000_0000);
+        let mnemonic = format!("{}{}", ins.op.mnemonic(), ins.suffix());
+        assert_eq!(mnemonic.parse(), Ok(ins.op), "{}", mnemonic);
+    }
+
+    let (op, modifiers) = Opcode::from_mnemonic("addo.").unwrap();
+    assert_eq!(op, Opcode::Add);
+    assert!(modifiers.contains(Modifier::OE));
+    assert!(modifiers.contains(Modifier::Rc));
+    let (op, modifiers) = Opcode::from_mnemonic("bla").unwrap();
+    assert_eq!(op, Opcode::B);
+    assert!(modifiers.contains(Modifier::LK));
+    assert!(modifiers.contains(Modifier::AA));
+    let (op, modifiers) = Opcode::from_mnemonic("bc+").unwrap();
+    assert_eq!(op, Opcode::Bc);
+    assert!(modifiers.contains(Modifier::BP));
+    let (op, modifiers) = Opcode::from_mnemonic("subfco").unwrap();
+    assert_eq!(op, Opcode::Subfc);
+    assert!(modifiers.contains(Modifier::OE));
+    assert!(!modifiers.contains(Modifier::Rc));
+}
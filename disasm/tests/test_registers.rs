@@ -0,0 +1,163 @@
+use ppc750cl::prelude::*;
+
+fn ins(code: u32) -> Ins {
+    Ins::new(code, 0x8000_0000)
+}
+
+#[test]
+fn test_registers_explicit() {
+    // lwzu r0, 0x8(r3)
+    let lwzu = ins(0x84030008);
+    assert_eq!(lwzu.reads(), [Register::GPR(GPR(3))]);
+    assert_eq!(
+        lwzu.writes(),
+        [Register::GPR(GPR(0)), Register::GPR(GPR(3))]
+    );
+    // lmw r29, 0x8(r1)
+    assert_eq!(
+        ins(0xBBA10008).writes(),
+        [
+            Register::GPR(GPR(29)),
+            Register::GPR(GPR(30)),
+            Register::GPR(GPR(31))
+        ]
+    );
+    // lswx r3, r4, r5 may write any GPR, depending on XER[BC].
+    let writes = ins(0x7C642C2A).writes();
+    assert_eq!(writes.len(), 32);
+    assert_eq!(writes[0], Register::GPR(GPR(3)));
+    assert!((0..32).all(|i| writes.contains(&Register::GPR(GPR(i)))));
+    assert!(ins(0x7C642C2A).reads().contains(&Register::XerBC));
+    // psq_l f31, 0xc(r1), 0, qr0
+    assert_eq!(
+        ins(0xE3E1000C).reads(),
+        [Register::GPR(GPR(1)), Register::GQR(GQR(0))]
+    );
+}
+
+#[test]
+fn test_registers_fixed_point() {
+    // add. r3, r4, r5
+    let add = ins(0x7c642a15);
+    assert_eq!(
+        add.reads(),
+        [
+            Register::GPR(GPR(4)),
+            Register::GPR(GPR(5)),
+            Register::XerSO
+        ]
+    );
+    assert_eq!(add.writes(), [Register::GPR(GPR(3)), Register::CR0]);
+    // addc r3, r4, r5
+    assert_eq!(
+        ins(0x7c642814).writes(),
+        [Register::GPR(GPR(3)), Register::XerCA]
+    );
+    // addeo r3, r4, r5
+    let addeo = ins(0x7c642d14);
+    assert_eq!(
+        addeo.reads(),
+        [
+            Register::GPR(GPR(4)),
+            Register::GPR(GPR(5)),
+            Register::XerCA,
+            Register::XerSO
+        ]
+    );
+    assert_eq!(
+        addeo.writes(),
+        [
+            Register::GPR(GPR(3)),
+            Register::XerCA,
+            Register::XerSO,
+            Register::XerOV
+        ]
+    );
+}
+
+#[test]
+fn test_registers_floating_point() {
+    // fadd. f1, f2, f3
+    let fadd = ins(0xFC22182B);
+    assert_eq!(
+        fadd.reads(),
+        [
+            Register::FPR(FPR(2)),
+            Register::FPR(FPR(3)),
+            Register::FPSCR
+        ]
+    );
+    assert_eq!(
+        fadd.writes(),
+        [Register::FPR(FPR(1)), Register::FPSCR, Register::CR1]
+    );
+    // fmr f1, f2
+    assert_eq!(ins(0xFC201090).writes(), [Register::FPR(FPR(1))]);
+}
+
+#[test]
+fn test_registers_branch() {
+    // bl 0x0
+    assert_eq!(ins(0x48000001).reads(), []);
+    assert_eq!(ins(0x48000001).writes(), [Register::LR]);
+    // bdnz -0x8
+    assert_eq!(ins(0x4200FFF8).reads(), [Register::CTR]);
+    assert_eq!(ins(0x4200FFF8).writes(), [Register::CTR]);
+    // beq cr1, 0x10
+    assert_eq!(ins(0x41860010).reads(), [Register::CR(CRField(1))]);
+    assert_eq!(ins(0x41860010).writes(), []);
+    // blr
+    assert_eq!(ins(0x4E800020).reads(), [Register::LR]);
+}
+
+#[test]
+fn test_registers_condition_register() {
+    // crand 4*cr1+eq, lt, 4*cr7+so
+    let crand = ins(0x4cc0fa02);
+    assert_eq!(
+        crand.reads(),
+        [
+            Register::CR(CRField(0)),
+            Register::CR(CRField(7)),
+            Register::CR(CRField(1))
+        ]
+    );
+    assert_eq!(crand.writes(), [Register::CR(CRField(1))]);
+    // mtcrf 128, r3
+    assert_eq!(ins(0x7C680120).writes(), [Register::CR0]);
+    // mfcr r3
+    assert_eq!(ins(0x7C600026).reads().len(), 8);
+}
+
+#[test]
+fn test_registers_spr() {
+    // mtlr r0
+    assert_eq!(ins(0x7C0803A6).writes(), [Register::LR]);
+    // mtspr GQR7, r3
+    assert_eq!(ins(0x7C77E3A6).writes(), [Register::GQR(GQR(7))]);
+    // mfspr r3, HID2
+    assert_eq!(ins(0x7C78E2A6).reads(), [Register::SPR(SPR(920))]);
+    // mtxer r3
+    assert_eq!(
+        ins(0x7C6103A6).writes(),
+        [
+            Register::XerSO,
+            Register::XerOV,
+            Register::XerCA,
+            Register::XerBC
+        ]
+    );
+    // mftbu r4
+    assert_eq!(ins(0x7C8D42E6).reads(), [Register::SPR(SPR(285))]);
+}
+
+#[test]
+fn test_registers_capacity() {
+    for op in ppc750cl::Opcode::ALL {
+        for fill in [0, u32::MAX, 0x5555_5555, 0xaaaa_aaaa] {
+            let ins = ins(op.pattern() | (fill & !op.bitmask()));
+            ins.reads();
+            ins.writes();
+        }
+    }
+}
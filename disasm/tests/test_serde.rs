@@ -0,0 +1,74 @@
+#![cfg(feature = "serde")]
+
+use ppc750cl::prelude::*;
+use ppc750cl::{Opcode, Variant};
+use serde_json::json;
+
+#[test]
+fn test_serde_ins() {
+    let ins = Ins::new(0x38010140, 0x8000_0000);
+    let value = serde_json::to_value(ins).unwrap();
+    assert_eq!(
+        value,
+        json!({"code": 0x38010140, "addr": 0x8000_0000u32, "op": "addi"})
+    );
+    assert_eq!(serde_json::from_value::<Ins>(value).unwrap(), ins);
+    let illegal = serde_json::to_value(Ins::new(0, 0)).unwrap();
+    assert_eq!(illegal["op"], "<illegal>");
+    assert!(serde_json::from_value::<Ins>(json!({"code": 0, "addr": 0, "op": "nop"})).is_err());
+    // The opcode must match the code.
+    assert!(serde_json::from_value::<Ins>(
+        json!({"code": 0x38010140, "addr": 0x8000_0000u32, "op": "add"})
+    )
+    .is_err());
+    // ps_add f1, f2, f3, which is illegal on the generic 750.
+    let ins = Ins::new_for(0x1022182A, 0x8000_0000, Variant::Ppc750);
+    assert_eq!(ins.op, Opcode::Illegal);
+    assert_eq!(
+        serde_json::from_value::<Ins>(serde_json::to_value(ins).unwrap()).unwrap(),
+        ins
+    );
+}
+
+#[test]
+fn test_serde_fields() {
+    let ins = Ins::new(0x38010140, 0x8000_0000);
+    let value = serde_json::to_value(ins.fields()).unwrap();
+    assert_eq!(value, json!([{"rD": 0}, {"rA": 1}, {"simm": 0x140}]));
+    assert_eq!(
+        serde_json::from_value::<Fields>(value).unwrap(),
+        ins.fields()
+    );
+    assert_eq!(serde_json::to_value(xer).unwrap(), json!("xer"));
+    let arg = Argument::CRBit(CRBit(14));
+    assert_eq!(serde_json::to_value(arg).unwrap(), json!({"CRBit": 14}));
+    assert_eq!(
+        serde_json::from_value::<Argument>(json!({"CRBit": 14})).unwrap(),
+        arg
+    );
+}
+
+#[test]
+fn test_serde_simplified() {
+    // mr. r3, r4
+    let simplified = Ins::new(0x7C832379, 0x8000_0000).simplified();
+    assert_eq!(
+        serde_json::to_value(simplified).unwrap(),
+        json!({
+            "ins": {"code": 0x7C832379, "addr": 0x8000_0000u32, "op": "or"},
+            "mnemonic": "mr",
+            "suffix": ["Rc"],
+            "args": [{"GPR": 3}, {"GPR": 4}],
+            "access": ["Write", "Read"],
+        })
+    );
+    let mut modifiers = Modifiers::empty();
+    modifiers.insert(Modifier::OE);
+    modifiers.insert(Modifier::Rc);
+    let value = serde_json::to_value(modifiers).unwrap();
+    assert_eq!(value, json!(["OE", "Rc"]));
+    assert_eq!(
+        serde_json::from_value::<Modifiers>(value).unwrap(),
+        modifiers
+    );
+}
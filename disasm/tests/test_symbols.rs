@@ -0,0 +1,113 @@
+#![cfg(feature = "alloc")]
+
+use std::collections::{BTreeMap, HashMap};
+
+use ppc750cl::formatter::FormatOptions;
+use ppc750cl::prelude::*;
+use ppc750cl::symbols::{find_address_halves, Half, SymbolResolver, WithHalves};
+
+fn format(ins: Ins, symbols: &dyn SymbolResolver) -> String {
+    FormatOptions::default()
+        .display(ins.simplified())
+        .with_symbols(symbols)
+        .to_string()
+}
+
+#[test]
+fn test_symbols_branch() {
+    let symbols = HashMap::from([(0x80004000, "OSReport")]);
+    assert_eq!(
+        format(Ins::new(0x48001001, 0x80003000), &symbols),
+        "bl OSReport"
+    );
+    // Unknown targets keep the relative offset.
+    assert_eq!(
+        format(Ins::new(0x48000011, 0x80003000), &symbols),
+        "bl 0x10"
+    );
+
+    let symbols = BTreeMap::from([(0x80003000, ("func", 0x40))]);
+    assert_eq!(
+        format(Ins::new(0x4bffffe4, 0x80003038), &symbols),
+        "b func+0x1c"
+    );
+    assert_eq!(
+        format(Ins::new(0x4182fff0, 0x80003020), &symbols),
+        "beq func+0x10"
+    );
+    // Addresses past the end of the last symbol are not resolved.
+    assert_eq!(symbols.resolve(0x80003040), None);
+    assert_eq!(
+        format(Ins::new(0x48001001, 0x80003000), &symbols),
+        "bl 0x1000"
+    );
+
+    let symbols = |addr: u32| (addr == 0x80004000).then_some(("main", 0));
+    assert_eq!(
+        format(Ins::new(0x48001001, 0x80003000), &symbols),
+        "bl main"
+    );
+}
+
+#[test]
+fn test_symbols_halves() {
+    let code = [
+        Ins::new(0x3c608000, 0x80003000), // lis r3, 0x8000
+        Ins::new(0x38631234, 0x80003004), // addi r3, r3, 0x1234
+        Ins::new(0x3c808001, 0x80003008), // lis r4, 0x8001
+        Ins::new(0x80a4fff0, 0x8000300c), // lwz r5, -0x10(r4)
+        Ins::new(0x3cc08000, 0x80003010), // lis r6, 0x8000
+        Ins::new(0x60c61000, 0x80003014), // ori r6, r6, 0x1000
+        Ins::new(0x3ce08001, 0x80003018), // lis r7, 0x8001
+        Ins::new(0x38e7fff0, 0x8000301c), // subi r7, r7, 0x10
+        Ins::new(0x3d008001, 0x80003020), // lis r8, 0x8001
+        Ins::new(0xe0280ff0, 0x80003024), // psq_l f1, -0x10(r8), 0, qr0
+    ];
+    let halves = find_address_halves(code);
+    assert_eq!(halves[&0x80003000], (0x80001234, Half::Ha));
+    assert_eq!(halves[&0x80003004], (0x80001234, Half::Lo));
+    assert_eq!(halves[&0x80003008], (0x8000fff0, Half::Ha));
+    assert_eq!(halves[&0x8000300c], (0x8000fff0, Half::Lo));
+    assert_eq!(halves[&0x80003010], (0x80001000, Half::Hi));
+    assert_eq!(halves[&0x80003014], (0x80001000, Half::Lo));
+    assert_eq!(halves[&0x80003020], (0x8000fff0, Half::Ha));
+    assert_eq!(halves[&0x80003024], (0x8000fff0, Half::Lo));
+
+    let symbols = WithHalves::new(
+        BTreeMap::from([
+            (0x80001000, ("buf", 0x10)),
+            (0x80001230, ("data", 0x10)),
+            (0x8000fff0, ("table", 0x100)),
+        ]),
+        code,
+    );
+    let text: Vec<String> = code.iter().map(|&ins| format(ins, &symbols)).collect();
+    assert_eq!(
+        text,
+        [
+            "lis r3, data+0x4@ha",
+            "addi r3, r3, data+0x4@l",
+            "lis r4, table@ha",
+            "lwz r5, table@l(r4)",
+            "lis r6, buf@h",
+            "ori r6, r6, buf@l",
+            "lis r7, table@ha",
+            "addi r7, r7, table@l",
+            "lis r8, table@ha",
+            "psq_l f1, table@l(r8), 0, qr0",
+        ]
+    );
+}
+
+#[test]
+fn test_symbols_halves_clobbered() {
+    let code = [
+        Ins::new(0x3c608000, 0x80003000), // lis r3, 0x8000
+        Ins::new(0x38600000, 0x80003004), // li r3, 0x0
+        Ins::new(0x38631234, 0x80003008), // addi r3, r3, 0x1234
+        Ins::new(0x3c808000, 0x8000300c), // lis r4, 0x8000
+        Ins::new(0x4e800020, 0x80003010), // blr
+        Ins::new(0x38841234, 0x80003014), // addi r4, r4, 0x1234
+    ];
+    assert!(find_address_halves(code).is_empty());
+}
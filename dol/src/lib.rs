@@ -120,7 +120,7 @@ impl Dol {
         }
 
         let offset = (virtual_addr - self.memory_offset) as usize;
-        if offset + (read_len as usize) < self.memory.len() {
+        if offset + (read_len as usize) <= self.memory.len() {
             Ok(&self.memory[offset..offset + (read_len as usize)])
         } else {
             Err(Error::OutOfBounds(virtual_addr + read_len))
@@ -135,7 +135,7 @@ impl Dol {
 
         let offset = (virtual_addr - self.memory_offset) as usize;
         let read_len = data.len();
-        if offset + read_len < self.memory.len() {
+        if offset + read_len <= self.memory.len() {
             data.copy_from_slice(&self.memory[offset..offset + data.len()]);
             Ok(())
         } else {
@@ -0,0 +1,76 @@
+//! Branch and condition register instructions.
+
+use ppc750cl::{BranchKind, CtrTest, Ins, Opcode};
+
+use crate::{Cpu, Error, Result};
+
+impl Cpu {
+    pub(crate) fn execute_branch(&mut self, ins: Ins) {
+        let branch = ins
+            .branch_info()
+            .expect("branch category without branch info");
+        // The target register is read before the link register is written.
+        let target = match branch.kind {
+            BranchKind::Direct => branch.target.unwrap_or_default(),
+            BranchKind::Lr => self.lr & !3,
+            BranchKind::Ctr => self.ctr & !3,
+        };
+        let ctr_ok = match branch.ctr {
+            None => true,
+            Some(test) => {
+                self.ctr = self.ctr.wrapping_sub(1);
+                (self.ctr == 0) == (test == CtrTest::Zero)
+            }
+        };
+        let cr_ok = branch
+            .cr
+            .is_none_or(|test| self.cr_bit(test.bit.0 as usize) == test.value);
+        if branch.link {
+            self.lr = ins.addr.wrapping_add(4);
+        }
+        if ctr_ok && cr_ok {
+            self.pc = target;
+        }
+    }
+
+    pub(crate) fn execute_cr(&mut self, ins: Ins) -> Result<()> {
+        let a = self.cr_bit(ins.field_crbA());
+        let b = self.cr_bit(ins.field_crbB());
+        let bit = match ins.op {
+            Opcode::Crand => a & b,
+            Opcode::Crandc => a & !b,
+            Opcode::Creqv => a == b,
+            Opcode::Crnand => !(a & b),
+            Opcode::Crnor => !(a | b),
+            Opcode::Cror => a | b,
+            Opcode::Crorc => a | !b,
+            Opcode::Crxor => a ^ b,
+            Opcode::Mcrf => {
+                let value = self.cr_field(ins.field_crfS());
+                self.set_cr_field(ins.field_crfD(), value);
+                return Ok(());
+            }
+            Opcode::Mcrxr => {
+                self.set_cr_field(ins.field_crfD(), self.xer >> 28);
+                self.xer &= 0x0FFF_FFFF;
+                return Ok(());
+            }
+            Opcode::Mfcr => {
+                self.gpr[ins.field_rD()] = self.cr;
+                return Ok(());
+            }
+            Opcode::Mtcrf => {
+                let rs = self.gpr[ins.field_rS()];
+                for field in 0..8 {
+                    if ins.field_crm() & (0x80 >> field) != 0 {
+                        self.set_cr_field(field, rs >> (28 - 4 * field));
+                    }
+                }
+                return Ok(());
+            }
+            _ => return Err(Error::Unimplemented(ins)),
+        };
+        self.set_cr_bit(ins.field_crbD(), bit);
+        Ok(())
+    }
+}
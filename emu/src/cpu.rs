@@ -0,0 +1,246 @@
+use ppc750cl::{Category, Ins};
+
+use crate::exception::DEC;
+use crate::{msr, AccessType, Error, FloatRegister, Memory, Result, Translated};
+
+/// Summary overflow bit of XER.
+pub(crate) const XER_SO: u32 = 0x8000_0000;
+/// Overflow bit of XER.
+pub(crate) const XER_OV: u32 = 0x4000_0000;
+/// Carry bit of XER.
+pub(crate) const XER_CA: u32 = 0x2000_0000;
+/// Byte count of XER, used by `lswx` and `stswx`.
+pub(crate) const XER_BC: u32 = 0x7F;
+
+/// Register state of a PowerPC 750CL.
+#[derive(Clone, Debug)]
+pub struct Cpu {
+    /// Address of the next instruction.
+    pub pc: u32,
+    pub gpr: [u32; 32],
+    pub cr: u32,
+    pub xer: u32,
+    pub lr: u32,
+    pub ctr: u32,
+    pub msr: u32,
+    pub srr0: u32,
+    pub srr1: u32,
+    pub sr: [u32; 16],
+    pub fpr: [FloatRegister; 32],
+    /// Floating-point status and control register, see [`crate::fpscr`].
+    pub fpscr: u32,
+    /// Other special-purpose registers, indexed by number.
+    ///
+    /// The time base is stored at its write numbers, `TBL` 284 and `TBU` 285.
+    /// The GQRs used by quantized loads and stores are 912 to 919.
+    pub spr: [u32; 1024],
+    /// Address reserved by `lwarx`.
+    pub reservation: Option<u32>,
+    /// Set when the decrementer passes zero, cleared when its exception is taken.
+    pub decrementer_pending: bool,
+    /// State of the external interrupt line, held by the interrupt controller.
+    pub external_interrupt: bool,
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self {
+            pc: 0,
+            gpr: [0; 32],
+            cr: 0,
+            xer: 0,
+            lr: 0,
+            ctr: 0,
+            msr: 0,
+            srr0: 0,
+            srr1: 0,
+            sr: [0; 16],
+            fpr: [FloatRegister::default(); 32],
+            fpscr: 0,
+            spr: [0; 1024],
+            reservation: None,
+            decrementer_pending: false,
+            external_interrupt: false,
+        }
+    }
+}
+
+impl Cpu {
+    /// Creates a processor with zeroed registers, starting at the given address.
+    pub fn new(pc: u32) -> Self {
+        Self {
+            pc,
+            ..Self::default()
+        }
+    }
+
+    /// Fetches and executes the instruction at the program counter.
+    pub fn step<M: Memory>(&mut self, mem: &mut M) -> Result<()> {
+        let ins = self.fetch(mem)?;
+        self.execute(ins, mem)
+    }
+
+    /// Fetches the instruction at the program counter, translated if enabled by `MSR[IR]`.
+    pub(crate) fn fetch<M: Memory>(&self, mem: &mut M) -> Result<Ins> {
+        let addr = if self.msr & msr::IR != 0 {
+            let translation =
+                self.mmu()
+                    .translate(mem, self.pc, AccessType::Execute, self.supervisor())?;
+            translation.addr
+        } else {
+            self.pc
+        };
+        Ok(Ins::new(mem.read_u32(addr)?, self.pc))
+    }
+
+    /// Returns whether the processor is in supervisor mode, with `MSR[PR]` clear.
+    pub fn supervisor(&self) -> bool {
+        self.msr & msr::PR == 0
+    }
+
+    /// Executes a decoded instruction, setting the program counter to the next one.
+    ///
+    /// Memory is accessed by physical addresses, translated by the MMU if enabled by
+    /// `MSR[DR]`. On error, the program counter is left at the instruction, whose
+    /// effects may have been partially applied.
+    pub fn execute<M: Memory>(&mut self, ins: Ins, mem: &mut M) -> Result<()> {
+        if self.msr & msr::DR != 0 {
+            let mut mem = Translated {
+                mmu: self.mmu(),
+                supervisor: self.supervisor(),
+                mem,
+            };
+            self.dispatch(ins, &mut mem)
+        } else {
+            self.dispatch(ins, mem)
+        }
+    }
+
+    fn dispatch<M: Memory>(&mut self, ins: Ins, mem: &mut M) -> Result<()> {
+        self.pc = ins.addr.wrapping_add(4);
+        let result = match ins.op.category() {
+            _ if !self.supervisor() && Self::is_privileged(ins) => Err(Error::Privileged(ins.addr)),
+            None => Err(Error::IllegalInstruction {
+                addr: ins.addr,
+                code: ins.code,
+            }),
+            Some(Category::Arithmetic | Category::Logical) => self.execute_integer(ins),
+            Some(Category::ConditionRegister) => self.execute_cr(ins),
+            Some(Category::Branch) => {
+                self.execute_branch(ins);
+                Ok(())
+            }
+            Some(Category::Load | Category::Store) => self.execute_load_store(ins, mem),
+            Some(Category::Cache) => self.execute_cache(ins, mem),
+            Some(Category::System) => self.execute_system(ins),
+            Some(Category::FloatingPoint) => self.execute_float(ins),
+            Some(Category::PairedSingle) => self.execute_paired(ins),
+        };
+        if result.is_err() {
+            self.pc = ins.addr;
+        }
+        result
+    }
+
+    /// Reads a GPR, or zero for `r0` as used by `rA|0` operands.
+    pub(crate) fn gpr_or_zero(&self, reg: usize) -> u32 {
+        if reg == 0 {
+            0
+        } else {
+            self.gpr[reg]
+        }
+    }
+
+    /// Returns a condition register field, `cr0` being the most significant.
+    pub fn cr_field(&self, field: usize) -> u32 {
+        (self.cr >> (28 - 4 * field)) & 0xF
+    }
+
+    pub fn set_cr_field(&mut self, field: usize, value: u32) {
+        let shift = 28 - 4 * field;
+        self.cr = (self.cr & !(0xF << shift)) | ((value & 0xF) << shift);
+    }
+
+    /// Returns a condition register bit, bit 0 being the most significant.
+    pub fn cr_bit(&self, bit: usize) -> bool {
+        self.cr & (0x8000_0000 >> bit) != 0
+    }
+
+    pub fn set_cr_bit(&mut self, bit: usize, value: bool) {
+        if value {
+            self.cr |= 0x8000_0000 >> bit;
+        } else {
+            self.cr &= !(0x8000_0000 >> bit);
+        }
+    }
+
+    /// Returns the `LT`, `GT` and `EQ` bits of a signed comparison, along with `SO`.
+    pub(crate) fn compare<T: Ord>(&self, a: T, b: T) -> u32 {
+        let bits = match a.cmp(&b) {
+            core::cmp::Ordering::Less => 0b1000,
+            core::cmp::Ordering::Greater => 0b0100,
+            core::cmp::Ordering::Equal => 0b0010,
+        };
+        bits | (self.xer >> 31)
+    }
+
+    /// Records the comparison of a result with zero in `cr0`.
+    pub(crate) fn record(&mut self, result: u32) {
+        let bits = self.compare(result as i32, 0);
+        self.set_cr_field(0, bits);
+    }
+
+    pub(crate) fn carry(&self) -> bool {
+        self.xer & XER_CA != 0
+    }
+
+    pub(crate) fn set_carry(&mut self, carry: bool) {
+        if carry {
+            self.xer |= XER_CA;
+        } else {
+            self.xer &= !XER_CA;
+        }
+    }
+
+    /// Sets the overflow bit, and the summary overflow bit if it is set.
+    pub(crate) fn set_overflow(&mut self, overflow: bool) {
+        if overflow {
+            self.xer |= XER_OV | XER_SO;
+        } else {
+            self.xer &= !XER_OV;
+        }
+    }
+
+    /// Reads a special-purpose register by number.
+    pub fn read_spr(&self, num: usize) -> u32 {
+        match num {
+            1 => self.xer,
+            8 => self.lr,
+            9 => self.ctr,
+            26 => self.srr0,
+            27 => self.srr1,
+            268 => self.spr[284],
+            269 => self.spr[285],
+            _ => self.spr[num],
+        }
+    }
+
+    /// Writes a special-purpose register by number.
+    pub fn write_spr(&mut self, num: usize, value: u32) {
+        match num {
+            1 => self.xer = value & (XER_SO | XER_OV | XER_CA | XER_BC),
+            8 => self.lr = value,
+            9 => self.ctr = value,
+            26 => self.srr0 = value,
+            27 => self.srr1 = value,
+            // Setting the most significant bit signals a decrementer exception.
+            DEC => {
+                if value & !self.spr[DEC] & 0x8000_0000 != 0 {
+                    self.decrementer_pending = true;
+                }
+                self.spr[DEC] = value;
+            }
+            _ => self.spr[num] = value,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Reciprocal and reciprocal square root estimates, matching the tables of Broadway.
+//!
+//! Each table entry covers a range of the input mantissa with a base value and a
+//! decrement per step, linearly interpolating the estimate.
+
+use crate::float::DEFAULT_NAN;
+
+/// Entries of `fres` by the 5 most significant bits of the mantissa.
+const RECIPROCAL: [(u32, u32); 32] = [
+    (0x7ff800, 0x3e1),
+    (0x783800, 0x3a7),
+    (0x70ea00, 0x371),
+    (0x6a0800, 0x340),
+    (0x638800, 0x313),
+    (0x5d6200, 0x2ea),
+    (0x579000, 0x2c4),
+    (0x520800, 0x2a0),
+    (0x4cc800, 0x27f),
+    (0x47ca00, 0x261),
+    (0x430800, 0x245),
+    (0x3e8000, 0x22a),
+    (0x3a2c00, 0x212),
+    (0x360800, 0x1fb),
+    (0x321400, 0x1e5),
+    (0x2e4a00, 0x1d1),
+    (0x2aa800, 0x1be),
+    (0x272c00, 0x1ac),
+    (0x23d600, 0x19b),
+    (0x209e00, 0x18b),
+    (0x1d8800, 0x17c),
+    (0x1a9000, 0x16e),
+    (0x17ae00, 0x15b),
+    (0x14f800, 0x15b),
+    (0x124400, 0x143),
+    (0x0fbe00, 0x143),
+    (0x0d3800, 0x12d),
+    (0x0ade00, 0x12d),
+    (0x088400, 0x11a),
+    (0x065000, 0x11a),
+    (0x041c00, 0x108),
+    (0x020c00, 0x106),
+];
+
+/// Entries of `frsqrte` by the least significant bit of the exponent, then the 4 most
+/// significant bits of the mantissa.
+const RECIPROCAL_SQRT: [(u32, u32); 32] = [
+    (0x1a7e800, 0x568),
+    (0x17cb800, 0x4f3),
+    (0x1552800, 0x48d),
+    (0x130c000, 0x435),
+    (0x10f2000, 0x3e7),
+    (0x0eff000, 0x3a2),
+    (0x0d2e000, 0x365),
+    (0x0b7c000, 0x32e),
+    (0x09e5000, 0x2fc),
+    (0x0867000, 0x2d0),
+    (0x06ff000, 0x2a8),
+    (0x05ab800, 0x283),
+    (0x046a000, 0x261),
+    (0x0339800, 0x243),
+    (0x0218800, 0x226),
+    (0x0105800, 0x20b),
+    (0x3ffa000, 0x7a4),
+    (0x3c29000, 0x700),
+    (0x38aa000, 0x670),
+    (0x3572000, 0x5f2),
+    (0x3279000, 0x584),
+    (0x2fb7000, 0x524),
+    (0x2d26000, 0x4cc),
+    (0x2ac0000, 0x47e),
+    (0x2881000, 0x43a),
+    (0x2665000, 0x3fa),
+    (0x2468000, 0x3c2),
+    (0x2287000, 0x38e),
+    (0x20c1000, 0x35e),
+    (0x1f12000, 0x332),
+    (0x1d79000, 0x30a),
+    (0x1bf4000, 0x2e6),
+];
+
+const SIGN: u64 = 1 << 63;
+const EXPONENT: u64 = 0x7FF << 52;
+const MANTISSA: u64 = (1 << 52) - 1;
+
+/// Returns the estimate of `1 / value` computed by `fres` and `ps_res`.
+pub(crate) fn reciprocal_estimate(value: f64) -> f64 {
+    let bits = value.to_bits();
+    let sign = bits & SIGN;
+    let exponent = bits & EXPONENT;
+    let mantissa = bits & MANTISSA;
+    if exponent == 0 && mantissa == 0 {
+        return f64::INFINITY.copysign(value);
+    }
+    if exponent == EXPONENT {
+        return if mantissa == 0 {
+            0.0f64.copysign(value)
+        } else {
+            f64::from_bits(bits | 1 << 51)
+        };
+    }
+    // Results out of the single-precision range saturate.
+    if exponent < 895 << 52 {
+        return (f32::MAX as f64).copysign(value);
+    }
+    if exponent >= 1149 << 52 {
+        return 0.0f64.copysign(value);
+    }
+    let i = (mantissa >> 37) as u32;
+    let (base, dec) = RECIPROCAL[i as usize / 1024];
+    let estimate = base - (dec * (i % 1024)).div_ceil(2);
+    f64::from_bits(sign | ((0x7FD << 52) - exponent) | (estimate as u64) << 29)
+}
+
+/// Returns the estimate of `1 / sqrt(value)` computed by `frsqrte` and `ps_rsqrte`.
+pub(crate) fn reciprocal_sqrt_estimate(value: f64) -> f64 {
+    let bits = value.to_bits();
+    let exponent = bits & EXPONENT;
+    let mut mantissa = bits & MANTISSA;
+    if exponent == 0 && mantissa == 0 {
+        return f64::INFINITY.copysign(value);
+    }
+    if exponent == EXPONENT {
+        return if mantissa != 0 {
+            f64::from_bits(bits | 1 << 51)
+        } else if bits & SIGN != 0 {
+            f64::from_bits(DEFAULT_NAN)
+        } else {
+            0.0
+        };
+    }
+    if bits & SIGN != 0 {
+        return f64::from_bits(DEFAULT_NAN);
+    }
+    // The exponent is signed to allow normalizing a denormalized input.
+    let mut exponent = exponent as i64;
+    if exponent == 0 {
+        let shift = mantissa.leading_zeros() - 11;
+        mantissa = (mantissa << shift) & MANTISSA;
+        exponent = (1 - shift as i64) << 52;
+    }
+    let odd = exponent as u64 & (1 << 52);
+    let exponent = ((0x3FF << 52) - (exponent - (0x3FE << 52)) / 2) as u64 & EXPONENT;
+    let i = ((odd | mantissa) >> 37) as u32;
+    let (base, dec) = RECIPROCAL_SQRT[i as usize / 2048];
+    let estimate = base - dec * (i % 2048);
+    f64::from_bits(exponent | (estimate as u64) << 26)
+}
@@ -0,0 +1,290 @@
+//! Exceptions and interrupts, taken by [`Cpu::step_with_exceptions`].
+//!
+//! Taking an exception saves the return address and MSR to SRR0 and SRR1, disables
+//! address translation and interrupts, and continues at the vector of the exception.
+//! Vectors are physical addresses, at zero or at `0xFFF00000` if `MSR[IP]` is set.
+
+use ppc750cl::{Category, Ins, MemData, Opcode};
+
+use crate::{fpscr, AccessType, Cpu, Error, Fault, Memory, Result};
+
+/// Data storage interrupt status register, set by DSI and alignment exceptions.
+pub(crate) const DSISR: usize = 18;
+/// Data address register, holding the address of a failed data access.
+pub(crate) const DAR: usize = 19;
+/// Decrementer.
+pub(crate) const DEC: usize = 22;
+/// Lower half of the time base, at its write number.
+const TBL: usize = 284;
+/// Upper half of the time base, at its write number.
+const TBU: usize = 285;
+
+/// Base of the vectors if `MSR[IP]` is set.
+const PREFIX: u32 = 0xFFF0_0000;
+
+/// Bits of the machine state register.
+pub mod msr {
+    /// Power management enable.
+    pub const POW: u32 = 1 << 18;
+    /// Exception little-endian mode, copied to `LE` when taking an exception.
+    pub const ILE: u32 = 1 << 16;
+    /// External interrupt enable, which also enables the decrementer.
+    pub const EE: u32 = 1 << 15;
+    /// Problem state, set in user mode.
+    pub const PR: u32 = 1 << 14;
+    /// Floating-point available.
+    pub const FP: u32 = 1 << 13;
+    /// Machine check enable.
+    pub const ME: u32 = 1 << 12;
+    /// Floating-point exception mode 0.
+    pub const FE0: u32 = 1 << 11;
+    /// Single-step trace enable.
+    pub const SE: u32 = 1 << 10;
+    /// Branch trace enable.
+    pub const BE: u32 = 1 << 9;
+    /// Floating-point exception mode 1.
+    pub const FE1: u32 = 1 << 8;
+    /// Exception prefix, placing the vectors at `0xFFF00000`.
+    pub const IP: u32 = 1 << 6;
+    /// Instruction address translation.
+    pub const IR: u32 = 1 << 5;
+    /// Data address translation.
+    pub const DR: u32 = 1 << 4;
+    /// Performance monitor marked mode.
+    pub const PM: u32 = 1 << 2;
+    /// Recoverable exception.
+    pub const RI: u32 = 1 << 1;
+    /// Little-endian mode.
+    pub const LE: u32 = 1;
+}
+
+/// Exception-specific bits of SRR1.
+pub mod srr1 {
+    /// Instruction fetch without a page table entry.
+    pub const PAGE_NOT_FOUND: u32 = 1 << 30;
+    /// Instruction fetch from a no-execute or direct-store segment.
+    pub const NO_EXECUTE: u32 = 1 << 28;
+    /// Instruction fetch violating page protection.
+    pub const PROTECTION: u32 = 1 << 27;
+    /// Enabled IEEE floating-point exception.
+    pub const FLOAT: u32 = 1 << 20;
+    /// Illegal instruction.
+    pub const ILLEGAL: u32 = 1 << 19;
+    /// Privileged instruction in user mode.
+    pub const PRIVILEGED: u32 = 1 << 18;
+    /// Trap instruction.
+    pub const TRAP: u32 = 1 << 17;
+    /// Bits of MSR saved to SRR1 and restored by `rfi`.
+    pub const MSR: u32 = 0x87C0_FF73;
+}
+
+/// Bits of DSISR set by data storage exceptions.
+pub mod dsisr {
+    /// Data access without a page table entry.
+    pub const PAGE_NOT_FOUND: u32 = 1 << 30;
+    /// Data access violating page protection.
+    pub const PROTECTION: u32 = 1 << 27;
+    /// Data access to a direct-store segment.
+    pub const DIRECT_STORE: u32 = 1 << 26;
+    /// Set for stores, clear for loads.
+    pub const STORE: u32 = 1 << 25;
+}
+
+/// An exception of the 750CL.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Exception {
+    SystemReset,
+    MachineCheck,
+    /// Data storage interrupt, a failed data address translation.
+    Dsi,
+    /// Instruction storage interrupt, a failed instruction address translation.
+    Isi,
+    External,
+    Alignment,
+    /// Illegal or privileged instruction, trap or enabled floating-point exception.
+    Program,
+    FloatUnavailable,
+    Decrementer,
+    SystemCall,
+    Trace,
+    PerformanceMonitor,
+}
+
+impl Exception {
+    /// Returns the offset of the vector from the exception prefix.
+    pub fn offset(self) -> u32 {
+        match self {
+            Self::SystemReset => 0x100,
+            Self::MachineCheck => 0x200,
+            Self::Dsi => 0x300,
+            Self::Isi => 0x400,
+            Self::External => 0x500,
+            Self::Alignment => 0x600,
+            Self::Program => 0x700,
+            Self::FloatUnavailable => 0x800,
+            Self::Decrementer => 0x900,
+            Self::SystemCall => 0xC00,
+            Self::Trace => 0xD00,
+            Self::PerformanceMonitor => 0xF00,
+        }
+    }
+}
+
+/// Returns whether an instruction accesses the FPRs, requiring `MSR[FP]`.
+fn uses_fpr(ins: Ins) -> bool {
+    match ins.op.category() {
+        Some(Category::FloatingPoint | Category::PairedSingle) => true,
+        _ if ins.op == Opcode::Stfiwx => true,
+        _ => ins.memory_access().is_some_and(|access| {
+            matches!(
+                access.data,
+                MemData::Single | MemData::Double | MemData::Quantized { .. }
+            )
+        }),
+    }
+}
+
+/// Returns the DSISR of an alignment exception, identifying the instruction.
+fn alignment_dsisr(code: u32) -> u32 {
+    let opcode = if code >> 26 == 31 {
+        // Bits 29 and 30, 25 and 21 to 24 of X-form instructions.
+        (code >> 1 & 0b11) << 5 | (code >> 6 & 1) << 4 | (code >> 7 & 0xF)
+    } else {
+        // Bits 5 and 1 to 4 of D-form instructions.
+        (code >> 26 & 1) << 4 | (code >> 27 & 0xF)
+    };
+    opcode << 10 | (code >> 21 & 0x1F) << 5 | (code >> 16 & 0x1F)
+}
+
+impl Cpu {
+    /// Takes an exception, continuing at its vector.
+    ///
+    /// `srr0` is the address at which execution resumes after `rfi`, and `flags` the
+    /// exception-specific bits of SRR1 such as [`srr1::TRAP`].
+    pub fn take_exception(&mut self, exception: Exception, srr0: u32, flags: u32) {
+        self.srr0 = srr0;
+        self.srr1 = self.msr & srr1::MSR | flags;
+        let mut kept = msr::ILE | msr::IP | msr::ME;
+        // A second machine check stops the processor.
+        if exception == Exception::MachineCheck {
+            kept &= !msr::ME;
+        }
+        self.msr &= kept;
+        if self.msr & msr::ILE != 0 {
+            self.msr |= msr::LE;
+        }
+        let base = if self.msr & msr::IP != 0 { PREFIX } else { 0 };
+        self.pc = base | exception.offset();
+    }
+
+    /// Returns the 64-bit time base.
+    pub fn time_base(&self) -> u64 {
+        (self.spr[TBU] as u64) << 32 | self.spr[TBL] as u64
+    }
+
+    /// Advances the time base and the decrementer.
+    ///
+    /// The decrementer exception becomes pending when the decrementer passes zero.
+    pub fn advance_time(&mut self, ticks: u32) {
+        let time_base = self.time_base().wrapping_add(ticks as u64);
+        self.spr[TBL] = time_base as u32;
+        self.spr[TBU] = (time_base >> 32) as u32;
+        let dec = self.spr[DEC];
+        self.spr[DEC] = dec.wrapping_sub(ticks);
+        if (dec as i32) >= 0 && ticks > dec {
+            self.decrementer_pending = true;
+        }
+    }
+
+    /// Executes the instruction at the program counter like [`Cpu::step`], taking the
+    /// exceptions it raises as well as pending interrupts enabled by `MSR[EE]`.
+    ///
+    /// Returns the exception taken, if any. Errors that are not exceptions, such as
+    /// unimplemented instructions or unmapped memory, are returned as they are. Unlike
+    /// [`Cpu::execute`], floating-point instructions require `MSR[FP]`. The time base
+    /// and decrementer advance by one tick per instruction.
+    pub fn step_with_exceptions<M: Memory>(&mut self, mem: &mut M) -> Result<Option<Exception>> {
+        if self.msr & msr::EE != 0 && (self.external_interrupt || self.decrementer_pending) {
+            // The external interrupt takes priority, its line is held until acknowledged.
+            let exception = if self.external_interrupt {
+                Exception::External
+            } else {
+                self.decrementer_pending = false;
+                Exception::Decrementer
+            };
+            self.take_exception(exception, self.pc, 0);
+            return Ok(Some(exception));
+        }
+        let ins = match self.fetch(mem) {
+            Ok(ins) => ins,
+            Err(Error::Translation { fault, .. }) => {
+                let flags = match fault {
+                    Fault::PageNotFound => srr1::PAGE_NOT_FOUND,
+                    Fault::Protection => srr1::PROTECTION,
+                    Fault::NoExecute | Fault::DirectStore => srr1::NO_EXECUTE,
+                };
+                self.take_exception(Exception::Isi, self.pc, flags);
+                return Ok(Some(Exception::Isi));
+            }
+            Err(error) => return Err(error),
+        };
+        if self.msr & msr::FP == 0 && uses_fpr(ins) {
+            self.take_exception(Exception::FloatUnavailable, ins.addr, 0);
+            return Ok(Some(Exception::FloatUnavailable));
+        }
+        let fex = self.fpscr & fpscr::FEX;
+        let result = self.execute(ins, mem);
+        self.advance_time(1);
+        if let Err(error) = result {
+            return self.take_error(ins, error).map(Some);
+        }
+        // Floating-point exceptions are precise if either mode bit is set.
+        if self.msr & (msr::FE0 | msr::FE1) != 0 && self.fpscr & !fex & fpscr::FEX != 0 {
+            self.take_exception(Exception::Program, ins.addr, srr1::FLOAT);
+            return Ok(Some(Exception::Program));
+        }
+        let trace = (self.msr & msr::SE != 0 && ins.op != Opcode::Rfi)
+            || (self.msr & msr::BE != 0 && ins.op.category() == Some(Category::Branch));
+        if trace {
+            self.take_exception(Exception::Trace, self.pc, 0);
+            return Ok(Some(Exception::Trace));
+        }
+        Ok(None)
+    }
+
+    /// Takes the exception corresponding to an error raised by an instruction.
+    fn take_error(&mut self, ins: Ins, error: Error) -> Result<Exception> {
+        let (exception, srr0, flags) = match error {
+            Error::IllegalInstruction { .. } => (Exception::Program, ins.addr, srr1::ILLEGAL),
+            Error::Privileged(_) => (Exception::Program, ins.addr, srr1::PRIVILEGED),
+            Error::Trap(_) => (Exception::Program, ins.addr, srr1::TRAP),
+            // Returns to the instruction after `sc`.
+            Error::SystemCall(_) => (Exception::SystemCall, ins.addr.wrapping_add(4), 0),
+            Error::Misaligned(addr) => {
+                self.spr[DAR] = addr;
+                self.spr[DSISR] = alignment_dsisr(ins.code);
+                (Exception::Alignment, ins.addr, 0)
+            }
+            Error::Translation {
+                addr,
+                access,
+                fault,
+            } => {
+                let mut dsisr = match fault {
+                    Fault::PageNotFound => dsisr::PAGE_NOT_FOUND,
+                    Fault::Protection => dsisr::PROTECTION,
+                    Fault::NoExecute | Fault::DirectStore => dsisr::DIRECT_STORE,
+                };
+                if access == AccessType::Write {
+                    dsisr |= dsisr::STORE;
+                }
+                self.spr[DAR] = addr;
+                self.spr[DSISR] = dsisr;
+                (Exception::Dsi, ins.addr, 0)
+            }
+            _ => return Err(error),
+        };
+        self.take_exception(exception, srr0, flags);
+        Ok(exception)
+    }
+}
@@ -0,0 +1,639 @@
+//! Floating-point instructions, conversions and the floating-point status register.
+//!
+//! Arithmetic is computed in double precision and rounds to nearest. The rounding mode
+//! of FPSCR applies when rounding to single precision and when converting to integers.
+//! Single-precision multiplications round the fraction of the `frC` operand to 25 bits
+//! first, as the 750CL does.
+
+use ppc750cl::{Ins, Modifier, Opcode};
+
+use crate::estimate::{reciprocal_estimate, reciprocal_sqrt_estimate};
+use crate::{Cpu, Error, Result};
+
+/// Bits of the floating-point status and control register.
+pub mod fpscr {
+    /// Exception summary, set when any exception bit is newly set.
+    pub const FX: u32 = 1 << 31;
+    /// Enabled exception summary.
+    pub const FEX: u32 = 1 << 30;
+    /// Invalid operation exception summary.
+    pub const VX: u32 = 1 << 29;
+    pub const OX: u32 = 1 << 28;
+    pub const UX: u32 = 1 << 27;
+    pub const ZX: u32 = 1 << 26;
+    pub const XX: u32 = 1 << 25;
+    /// Invalid operation on a signaling NaN.
+    pub const VXSNAN: u32 = 1 << 24;
+    /// Invalid operation `∞ - ∞`.
+    pub const VXISI: u32 = 1 << 23;
+    /// Invalid operation `∞ / ∞`.
+    pub const VXIDI: u32 = 1 << 22;
+    /// Invalid operation `0 / 0`.
+    pub const VXZDZ: u32 = 1 << 21;
+    /// Invalid operation `∞ * 0`.
+    pub const VXIMZ: u32 = 1 << 20;
+    /// Invalid ordered comparison with a NaN.
+    pub const VXVC: u32 = 1 << 19;
+    /// Fraction rounded, not modelled.
+    pub const FR: u32 = 1 << 18;
+    /// Fraction inexact.
+    pub const FI: u32 = 1 << 17;
+    /// Result flags, the class and sign of the result or the comparison outcome.
+    pub const FPRF: u32 = 0x1F << 12;
+    /// Invalid operation requested by software.
+    pub const VXSOFT: u32 = 1 << 10;
+    /// Invalid square root of a negative number.
+    pub const VXSQRT: u32 = 1 << 9;
+    /// Invalid conversion to integer.
+    pub const VXCVI: u32 = 1 << 8;
+    pub const VE: u32 = 1 << 7;
+    pub const OE: u32 = 1 << 6;
+    pub const UE: u32 = 1 << 5;
+    pub const ZE: u32 = 1 << 4;
+    pub const XE: u32 = 1 << 3;
+    /// Non-IEEE mode, flushing denormalized results to zero.
+    pub const NI: u32 = 1 << 2;
+    /// Rounding mode.
+    pub const RN: u32 = 0b11;
+
+    /// All invalid operation exception bits.
+    pub const VX_ALL: u32 = VXSNAN | VXISI | VXIDI | VXZDZ | VXIMZ | VXVC | VXSOFT | VXSQRT | VXCVI;
+    /// All sticky exception bits.
+    pub const EXCEPTIONS: u32 = OX | UX | ZX | XX | VX_ALL;
+}
+
+use fpscr::*;
+
+/// A floating-point register, holding two singles in paired-single instructions.
+///
+/// Double-precision instructions only use `ps0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FloatRegister {
+    pub ps0: f64,
+    pub ps1: f64,
+}
+
+impl FloatRegister {
+    /// Creates a register holding the same value in both halves.
+    pub fn splat(value: f64) -> Self {
+        Self {
+            ps0: value,
+            ps1: value,
+        }
+    }
+}
+
+/// Rounding modes of FPSCR.
+const ROUND_NEAREST: u32 = 0;
+const ROUND_ZERO: u32 = 1;
+const ROUND_UP: u32 = 2;
+
+/// Quiet bit of a double-precision NaN.
+const QUIET: u64 = 1 << 51;
+/// Default NaN produced by invalid operations.
+pub(crate) const DEFAULT_NAN: u64 = 0x7FF8_0000_0000_0000;
+
+pub(crate) fn is_snan(value: f64) -> bool {
+    value.is_nan() && value.to_bits() & QUIET == 0
+}
+
+fn quiet(value: f64) -> f64 {
+    f64::from_bits(value.to_bits() | QUIET)
+}
+
+/// Converts a single to a double as `lfs` does, preserving NaN payloads.
+pub fn single_to_double(word: u32) -> f64 {
+    let sign = (word as u64 & 0x8000_0000) << 32;
+    let exponent = (word >> 23) & 0xFF;
+    let fraction = word & 0x7F_FFFF;
+    let bits = if exponent == 0 && fraction != 0 {
+        // Normalize a denormalized single.
+        let shift = fraction.leading_zeros() - 8;
+        let exponent = (1023 - 126 - shift as u64) << 52;
+        let fraction = ((fraction << shift) as u64 & 0x7F_FFFF) << 29;
+        sign | exponent | fraction
+    } else {
+        // The second bit of the exponent is widened to three, inverted for normal values.
+        let high = word & 0x4000_0000 != 0;
+        let widened = if exponent == 0 || exponent == 0xFF {
+            high
+        } else {
+            !high
+        };
+        let middle = if widened { 0x7 << 59 } else { 0 };
+        sign | ((high as u64) << 62) | middle | ((word as u64 & 0x3FFF_FFFF) << 29)
+    };
+    f64::from_bits(bits)
+}
+
+/// Converts a double to a single as `stfs` does, truncating the extra fraction bits.
+pub fn double_to_single(value: f64) -> u32 {
+    let bits = value.to_bits();
+    let exponent = (bits >> 52) & 0x7FF;
+    if (874..=896).contains(&exponent) {
+        // Denormalize values below the single-precision range.
+        let sign = (bits >> 32) as u32 & 0x8000_0000;
+        let fraction = (bits & 0xF_FFFF_FFFF_FFFF) | (1 << 52);
+        sign | (fraction >> (29 + 897 - exponent)) as u32
+    } else {
+        ((bits >> 32) as u32 & 0xC000_0000) | ((bits >> 29) as u32 & 0x3FFF_FFFF)
+    }
+}
+
+fn next_up(value: f32) -> f32 {
+    if value == 0.0 {
+        f32::from_bits(1)
+    } else if value > 0.0 {
+        f32::from_bits(value.to_bits() + 1)
+    } else {
+        f32::from_bits(value.to_bits() - 1)
+    }
+}
+
+fn next_down(value: f32) -> f32 {
+    -next_up(-value)
+}
+
+/// Rounds a double to single precision with the given rounding mode.
+///
+/// Returns the rounded value and whether it is inexact.
+pub(crate) fn round_single(value: f64, mode: u32) -> (f64, bool) {
+    if value.is_nan() {
+        return (single_to_double(double_to_single(value)), false);
+    }
+    let mut single = value as f32;
+    let exact = single as f64 == value;
+    if !exact {
+        let above = single as f64 > value;
+        match mode & RN {
+            ROUND_NEAREST => {}
+            ROUND_ZERO if single.abs() as f64 > value.abs() => {
+                single = if above {
+                    next_down(single)
+                } else {
+                    next_up(single)
+                }
+            }
+            ROUND_UP if !above => single = next_up(single),
+            3 if above => single = next_down(single),
+            _ => {}
+        }
+    }
+    (single as f64, !exact)
+}
+
+/// Rounds the fraction of a double to 25 bits, as the 750CL does with the `frC`
+/// operand of single-precision multiplications.
+pub(crate) fn round_25bit(value: f64) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let bits = value.to_bits();
+    f64::from_bits((bits & !0x7FF_FFFF).wrapping_add(bits & 0x800_0000))
+}
+
+/// Returns the result flags of FPRF for a value.
+fn class(value: f64, single: bool) -> u32 {
+    let negative = value.is_sign_negative();
+    let denormal = if single {
+        value != 0.0 && value.abs() < f32::MIN_POSITIVE as f64
+    } else {
+        value.is_subnormal()
+    };
+    match () {
+        _ if value.is_nan() => 0b10001,
+        _ if value.is_infinite() => [0b00101, 0b01001][negative as usize],
+        _ if value == 0.0 => [0b00010, 0b10010][negative as usize],
+        _ if denormal => [0b10100, 0b11000][negative as usize],
+        _ => [0b00100, 0b01000][negative as usize],
+    }
+}
+
+/// Returns the condition bits `FL`, `FG`, `FE` and `FU` of a comparison.
+fn compare(a: f64, b: f64) -> u32 {
+    if a.is_nan() || b.is_nan() {
+        0b0001
+    } else if a < b {
+        0b1000
+    } else if a > b {
+        0b0100
+    } else {
+        0b0010
+    }
+}
+
+/// An arithmetic operation of the floating-point and paired-single units.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub(crate) enum Arith {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * c`
+    Mul,
+    /// `a / b`
+    Div,
+    /// `±(a * c ± b)`
+    MulAdd { subtract: bool, negate: bool },
+}
+
+/// The result of an arithmetic operation.
+pub(crate) struct Outcome {
+    pub value: f64,
+    /// Exception bits raised, including `FI`.
+    pub exceptions: u32,
+}
+
+/// Computes an arithmetic operation, rounding to single precision if `single`.
+pub(crate) fn arith(op: Arith, a: f64, b: f64, c: f64, single: bool, mode: u32) -> Outcome {
+    let c = if single { round_25bit(c) } else { c };
+    // Operands in order of NaN precedence, before negating the subtrahend.
+    let operands: &[f64] = match op {
+        Arith::Add | Arith::Sub | Arith::Div => &[a, b],
+        Arith::Mul => &[a, c],
+        Arith::MulAdd { .. } => &[a, b, c],
+    };
+    let mut exceptions = 0;
+    if operands.iter().any(|&x| is_snan(x)) {
+        exceptions |= VXSNAN;
+    }
+    let b = match op {
+        Arith::Sub | Arith::MulAdd { subtract: true, .. } => -b,
+        _ => b,
+    };
+    let (value, inexact) = match op {
+        Arith::Add | Arith::Sub => {
+            if a.is_infinite() && b.is_infinite() && a.is_sign_negative() != b.is_sign_negative() {
+                exceptions |= VXISI;
+            }
+            let sum = a + b;
+            // Error-free transformation of the sum.
+            let b_virtual = sum - a;
+            let error = (a - (sum - b_virtual)) + (b - b_virtual);
+            (sum, sum.is_finite() && error != 0.0)
+        }
+        Arith::Mul => {
+            if (a.is_infinite() && c == 0.0) || (a == 0.0 && c.is_infinite()) {
+                exceptions |= VXIMZ;
+            }
+            let product = a * c;
+            (
+                product,
+                product.is_finite() && a.mul_add(c, -product) != 0.0,
+            )
+        }
+        Arith::Div => {
+            if a.is_infinite() && b.is_infinite() {
+                exceptions |= VXIDI;
+            } else if a == 0.0 && b == 0.0 {
+                exceptions |= VXZDZ;
+            } else if b == 0.0 && a.is_finite() {
+                exceptions |= ZX;
+            }
+            let quotient = a / b;
+            (
+                quotient,
+                quotient.is_finite() && (-quotient).mul_add(b, a) != 0.0,
+            )
+        }
+        Arith::MulAdd { .. } => {
+            let product = a * c;
+            if (a.is_infinite() && c == 0.0) || (a == 0.0 && c.is_infinite()) {
+                exceptions |= VXIMZ;
+            } else if product.is_infinite()
+                && b.is_infinite()
+                && product.is_sign_negative() != b.is_sign_negative()
+            {
+                exceptions |= VXISI;
+            }
+            let value = a.mul_add(c, b);
+            // Exact if the product and the sum are, which misses some exact results.
+            let sum = product + b;
+            let inexact = a.mul_add(c, -product) != 0.0 || sum - product != b;
+            (value, value.is_finite() && inexact)
+        }
+    };
+    // NaN operands propagate before the result is negated.
+    let nan = operands.iter().find(|x| x.is_nan()).map(|&x| quiet(x));
+    let mut value = match nan {
+        Some(nan) => nan,
+        None if exceptions & VX_ALL != 0 => f64::from_bits(DEFAULT_NAN),
+        None => value,
+    };
+    let mut inexact = inexact && !value.is_nan();
+    if single {
+        let (rounded, single_inexact) = round_single(value, mode);
+        value = rounded;
+        inexact |= single_inexact;
+    }
+    if let Arith::MulAdd { negate: true, .. } = op {
+        if !value.is_nan() {
+            value = -value;
+        }
+    }
+    let finite_operands = operands.iter().all(|x| x.is_finite());
+    if value.is_infinite() && finite_operands && exceptions & ZX == 0 {
+        exceptions |= OX;
+        inexact = true;
+    }
+    let min_normal = if single {
+        f32::MIN_POSITIVE as f64
+    } else {
+        f64::MIN_POSITIVE
+    };
+    if inexact && value.abs() < min_normal {
+        exceptions |= UX;
+    }
+    if inexact {
+        exceptions |= XX | FI;
+    }
+    Outcome { value, exceptions }
+}
+
+impl Cpu {
+    /// Returns the rounding mode of FPSCR.
+    pub(crate) fn rounding_mode(&self) -> u32 {
+        self.fpscr & RN
+    }
+
+    /// Raises exceptions, replacing the `FI` and `FR` bits and updating the summaries.
+    pub(crate) fn raise(&mut self, exceptions: u32) {
+        if exceptions & EXCEPTIONS & !self.fpscr != 0 {
+            self.fpscr |= FX;
+        }
+        self.fpscr = (self.fpscr & !(FI | FR)) | exceptions;
+        self.update_summary();
+    }
+
+    /// Recomputes the `VX` and `FEX` summary bits.
+    pub(crate) fn update_summary(&mut self) {
+        let mut fpscr = self.fpscr & !(VX | FEX);
+        if fpscr & VX_ALL != 0 {
+            fpscr |= VX;
+        }
+        // Enable bits are in the same order as the exception bits, shifted down by 22.
+        if (fpscr >> 22) & fpscr & (VE | OE | UE | ZE | XE) != 0 {
+            fpscr |= FEX;
+        }
+        self.fpscr = fpscr;
+    }
+
+    /// Returns whether enabled exceptions suppress writing the result.
+    pub(crate) fn suppresses_result(&self, exceptions: u32) -> bool {
+        (exceptions & VX_ALL != 0 && self.fpscr & VE != 0)
+            || (exceptions & ZX != 0 && self.fpscr & ZE != 0)
+    }
+
+    /// Sets the result flags of FPRF.
+    pub(crate) fn set_fprf(&mut self, value: f64, single: bool) {
+        self.fpscr = (self.fpscr & !FPRF) | class(value, single) << 12;
+    }
+
+    /// Compares two values into a CR field and the condition bits of FPRF.
+    pub(crate) fn compare_float(&mut self, field: usize, a: f64, b: f64, ordered: bool) {
+        let bits = compare(a, b);
+        self.set_cr_field(field, bits);
+        self.fpscr = (self.fpscr & !(0xF << 12)) | bits << 12;
+        let mut exceptions = self.fpscr & (FI | FR);
+        if is_snan(a) || is_snan(b) {
+            exceptions |= VXSNAN;
+            // An ordered comparison only raises VXVC for a signaling NaN if VE is clear.
+            if ordered && self.fpscr & VE == 0 {
+                exceptions |= VXVC;
+            }
+        } else if ordered && (a.is_nan() || b.is_nan()) {
+            exceptions |= VXVC;
+        }
+        self.raise(exceptions);
+    }
+
+    /// Copies the exception summaries to `cr1` for record forms.
+    pub(crate) fn record_float(&mut self, ins: Ins) {
+        if ins.suffix().contains(Modifier::Rc) {
+            self.set_cr_field(1, self.fpscr >> 28);
+        }
+    }
+
+    pub(crate) fn execute_float(&mut self, ins: Ins) -> Result<()> {
+        let a = self.fpr[ins.field_frA()].ps0;
+        let b = self.fpr[ins.field_frB()].ps0;
+        let c = self.fpr[ins.field_frC()].ps0;
+        let d = ins.field_frD();
+        let op = match ins.op {
+            Opcode::Fadd | Opcode::Fadds => Arith::Add,
+            Opcode::Fsub | Opcode::Fsubs => Arith::Sub,
+            Opcode::Fmul | Opcode::Fmuls => Arith::Mul,
+            Opcode::Fdiv | Opcode::Fdivs => Arith::Div,
+            Opcode::Fmadd | Opcode::Fmadds => Arith::MulAdd {
+                subtract: false,
+                negate: false,
+            },
+            Opcode::Fmsub | Opcode::Fmsubs => Arith::MulAdd {
+                subtract: true,
+                negate: false,
+            },
+            Opcode::Fnmadd | Opcode::Fnmadds => Arith::MulAdd {
+                subtract: false,
+                negate: true,
+            },
+            Opcode::Fnmsub | Opcode::Fnmsubs => Arith::MulAdd {
+                subtract: true,
+                negate: true,
+            },
+            _ => return self.execute_float_other(ins),
+        };
+        let single = matches!(
+            ins.op,
+            Opcode::Fadds
+                | Opcode::Fsubs
+                | Opcode::Fmuls
+                | Opcode::Fdivs
+                | Opcode::Fmadds
+                | Opcode::Fmsubs
+                | Opcode::Fnmadds
+                | Opcode::Fnmsubs
+        );
+        let outcome = arith(op, a, b, c, single, self.rounding_mode());
+        self.write_float(d, outcome, single);
+        self.record_float(ins);
+        Ok(())
+    }
+
+    /// Writes the result of an arithmetic instruction, unless suppressed by an
+    /// enabled exception.
+    ///
+    /// Single-precision results are written to both halves of the register.
+    fn write_float(&mut self, d: usize, outcome: Outcome, single: bool) {
+        if !self.suppresses_result(outcome.exceptions) {
+            if single {
+                self.fpr[d] = FloatRegister::splat(outcome.value);
+            } else {
+                self.fpr[d].ps0 = outcome.value;
+            }
+            self.set_fprf(outcome.value, single);
+        }
+        self.raise(outcome.exceptions);
+    }
+
+    /// Executes moves, conversions, comparisons, estimates and FPSCR instructions.
+    fn execute_float_other(&mut self, ins: Ins) -> Result<()> {
+        let b = self.fpr[ins.field_frB()].ps0;
+        let d = ins.field_frD();
+        match ins.op {
+            Opcode::Fmr => self.fpr[d].ps0 = b,
+            Opcode::Fneg => self.fpr[d].ps0 = -b,
+            Opcode::Fabs => self.fpr[d].ps0 = b.abs(),
+            Opcode::Fnabs => self.fpr[d].ps0 = -b.abs(),
+            Opcode::Fsel => {
+                let a = self.fpr[ins.field_frA()].ps0;
+                let c = self.fpr[ins.field_frC()].ps0;
+                self.fpr[d].ps0 = if a >= 0.0 { c } else { b };
+            }
+            Opcode::Frsp => {
+                let mut exceptions = if is_snan(b) { VXSNAN } else { 0 };
+                let (value, inexact) = round_single(quiet_if_nan(b), self.rounding_mode());
+                if inexact {
+                    exceptions |= XX | FI;
+                    if value.is_infinite() {
+                        exceptions |= OX;
+                    } else if value.abs() < f32::MIN_POSITIVE as f64 {
+                        exceptions |= UX;
+                    }
+                }
+                let outcome = Outcome { value, exceptions };
+                self.write_float(d, outcome, true);
+            }
+            Opcode::Fctiw | Opcode::Fctiwz => {
+                let mode = if ins.op == Opcode::Fctiwz {
+                    ROUND_ZERO
+                } else {
+                    self.rounding_mode()
+                };
+                let (value, exceptions) = to_integer(b, mode);
+                if !self.suppresses_result(exceptions) {
+                    self.fpr[d].ps0 = f64::from_bits(value);
+                }
+                self.raise(exceptions);
+            }
+            Opcode::Fcmpu | Opcode::Fcmpo => {
+                let a = self.fpr[ins.field_frA()].ps0;
+                self.compare_float(ins.field_crfD(), a, b, ins.op == Opcode::Fcmpo);
+                return Ok(());
+            }
+            Opcode::Fres => {
+                let mut exceptions = 0;
+                if is_snan(b) {
+                    exceptions |= VXSNAN;
+                } else if b == 0.0 {
+                    exceptions |= ZX;
+                }
+                let value = reciprocal_estimate(b);
+                let outcome = Outcome { value, exceptions };
+                self.write_float(d, outcome, true);
+            }
+            Opcode::Frsqrte => {
+                let mut exceptions = 0;
+                if is_snan(b) {
+                    exceptions |= VXSNAN;
+                } else if b == 0.0 {
+                    exceptions |= ZX;
+                } else if b < 0.0 {
+                    exceptions |= VXSQRT;
+                }
+                let value = reciprocal_sqrt_estimate(b);
+                let outcome = Outcome { value, exceptions };
+                self.write_float(d, outcome, false);
+            }
+            Opcode::Mffs => {
+                // The high word is undefined, the 750CL sets it like this.
+                self.fpr[d].ps0 = f64::from_bits(0xFFF8_0000_0000_0000 | self.fpscr as u64);
+            }
+            Opcode::Mtfsf => {
+                let value = b.to_bits() as u32;
+                let mut mask = 0;
+                for field in 0..8 {
+                    if ins.field_mtfsf_FM() & (0x80 >> field) != 0 {
+                        mask |= 0xF000_0000 >> (4 * field);
+                    }
+                }
+                // FEX and VX are always computed from the other bits.
+                self.fpscr = (self.fpscr & !mask) | (value & mask);
+                self.update_summary();
+            }
+            Opcode::Mtfsfi => {
+                let shift = 28 - 4 * ins.field_crfD();
+                let mask = 0xF << shift;
+                let value = (ins.field_mtfsf_IMM() as u32) << shift;
+                self.fpscr = (self.fpscr & !mask) | (value & mask);
+                self.update_summary();
+            }
+            Opcode::Mtfsb0 | Opcode::Mtfsb1 => {
+                let bit = 0x8000_0000 >> ins.field_crbD();
+                if ins.op == Opcode::Mtfsb0 {
+                    self.fpscr &= !bit;
+                } else {
+                    if bit & EXCEPTIONS & !self.fpscr != 0 {
+                        self.fpscr |= FX;
+                    }
+                    self.fpscr |= bit;
+                }
+                self.update_summary();
+            }
+            Opcode::Mcrfs => {
+                let shift = 28 - 4 * ins.field_crfS();
+                self.set_cr_field(ins.field_crfD(), self.fpscr >> shift);
+                // Exception bits copied are cleared.
+                self.fpscr &= !((FX | EXCEPTIONS) & (0xF << shift));
+                self.update_summary();
+                return Ok(());
+            }
+            _ => return Err(Error::Unimplemented(ins)),
+        }
+        self.record_float(ins);
+        Ok(())
+    }
+}
+
+fn quiet_if_nan(value: f64) -> f64 {
+    if value.is_nan() {
+        quiet(value)
+    } else {
+        value
+    }
+}
+
+/// Converts a double to a signed word as `fctiw` does, returning the register bits
+/// and the raised exceptions.
+fn to_integer(value: f64, mode: u32) -> (u64, u32) {
+    let rounded = match mode {
+        ROUND_NEAREST => value.round_ties_even(),
+        ROUND_ZERO => value.trunc(),
+        ROUND_UP => value.ceil(),
+        _ => value.floor(),
+    };
+    let mut exceptions = 0;
+    let word = if value.is_nan() {
+        exceptions |= VXCVI;
+        if is_snan(value) {
+            exceptions |= VXSNAN;
+        }
+        i32::MIN
+    } else if rounded > i32::MAX as f64 {
+        exceptions |= VXCVI;
+        i32::MAX
+    } else if rounded < i32::MIN as f64 {
+        exceptions |= VXCVI;
+        i32::MIN
+    } else {
+        if rounded != value {
+            exceptions |= XX | FI;
+        }
+        rounded as i32
+    };
+    // The high word is undefined, the 750CL sets it like this.
+    let mut bits = 0xFFF8_0000_0000_0000 | word as u32 as u64;
+    if word == 0 && value.is_sign_negative() {
+        bits |= 1 << 32;
+    }
+    (bits, exceptions)
+}
@@ -0,0 +1,315 @@
+//! Execution of single functions of a DOL executable in isolation.
+//!
+//! A [`Harness`] calls a function with arguments passed as specified by the PowerPC
+//! EABI, running until it returns to [`RETURN_ADDRESS`]. Calls to addresses with a
+//! stub run the stub instead, which is useful for functions that are not modelled
+//! or touch hardware.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use dol::Dol;
+use ppc750cl::Ins;
+
+use crate::{Cpu, Error, Memory, Ram, Result};
+
+/// Link register value of a call, the function has returned when it is reached.
+pub const RETURN_ADDRESS: u32 = 0xFFFF_FFFC;
+
+/// Default top of the stack, at the end of main memory.
+const STACK_TOP: u32 = 0x8180_0000;
+/// Default stack size.
+const STACK_SIZE: u32 = 0x1_0000;
+/// Default limit of instructions executed by a call.
+const MAX_STEPS: usize = 1_000_000;
+
+/// First stack argument, after the back chain and the LR save word of the caller's frame.
+const STACK_ARGS: u32 = 8;
+
+/// An argument of a function call.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Arg {
+    /// Word passed in one of `r3` to `r10`.
+    Int(u32),
+    /// Doubleword passed in an odd-even register pair such as `r3:r4`.
+    Long(u64),
+    /// Double passed in one of `f1` to `f8`.
+    Float(f64),
+}
+
+impl From<u32> for Arg {
+    fn from(value: u32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for Arg {
+    fn from(value: i32) -> Self {
+        Self::Int(value as u32)
+    }
+}
+
+impl From<u64> for Arg {
+    fn from(value: u64) -> Self {
+        Self::Long(value)
+    }
+}
+
+impl From<f32> for Arg {
+    fn from(value: f32) -> Self {
+        Self::Float(value as f64)
+    }
+}
+
+impl From<f64> for Arg {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+/// A write to memory, in the order performed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryWrite {
+    pub addr: u32,
+    pub data: Vec<u8>,
+}
+
+/// Memory of a DOL executable and a stack, recording writes.
+///
+/// The executable is left untouched, written bytes are kept separately.
+pub struct DolMemory<'a> {
+    dol: &'a Dol,
+    stack: Ram,
+    /// Bytes of the executable that have been written.
+    modified: HashMap<u32, u8>,
+    pub writes: Vec<MemoryWrite>,
+}
+
+impl<'a> DolMemory<'a> {
+    pub fn new(dol: &'a Dol, stack: Ram) -> Self {
+        Self {
+            dol,
+            stack,
+            modified: HashMap::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    /// Returns the address range of the stack.
+    pub fn stack(&self) -> Range<u32> {
+        self.stack.base..self.stack.base + self.stack.data.len() as u32
+    }
+
+    fn in_stack(&self, addr: u32, len: usize) -> bool {
+        let stack = self.stack();
+        stack.contains(&addr) && (addr as u64 + len as u64) <= stack.end as u64
+    }
+}
+
+impl Memory for DolMemory<'_> {
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<()> {
+        if self.in_stack(addr, buf.len()) {
+            return self.stack.read(addr, buf);
+        }
+        self.dol
+            .virtual_read(buf, addr)
+            .map_err(|_| Error::Unmapped(addr))?;
+        for (i, byte) in buf.iter_mut().enumerate() {
+            if let Some(&value) = self.modified.get(&addr.wrapping_add(i as u32)) {
+                *byte = value;
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<()> {
+        if self.in_stack(addr, data.len()) {
+            self.stack.write(addr, data)?;
+        } else {
+            self.dol
+                .virtual_data_at(addr, data.len() as u32)
+                .map_err(|_| Error::Unmapped(addr))?;
+            for (i, &value) in data.iter().enumerate() {
+                self.modified.insert(addr.wrapping_add(i as u32), value);
+            }
+        }
+        self.writes.push(MemoryWrite {
+            addr,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+}
+
+/// A function replacing calls to an address, returning to the link register.
+pub type Stub<'a> = Box<dyn FnMut(&mut Cpu, &mut dyn Memory) -> Result<()> + 'a>;
+
+/// Calls functions of a DOL executable.
+pub struct Harness<'a> {
+    dol: &'a Dol,
+    /// Registers at the start of each call, such as the small data bases `r2` and `r13`.
+    pub cpu: Cpu,
+    stack_top: u32,
+    stack_size: u32,
+    max_steps: usize,
+    stubs: HashMap<u32, Stub<'a>>,
+}
+
+impl<'a> Harness<'a> {
+    pub fn new(dol: &'a Dol) -> Self {
+        Self {
+            dol,
+            cpu: Cpu::default(),
+            stack_top: STACK_TOP,
+            stack_size: STACK_SIZE,
+            max_steps: MAX_STEPS,
+            stubs: HashMap::new(),
+        }
+    }
+
+    /// Sets the top and size of the stack, which takes precedence over the executable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stack would extend below address zero.
+    pub fn stack(mut self, top: u32, size: u32) -> Self {
+        assert!(
+            size <= top,
+            "stack of size {:#X} does not fit below {:#010X}",
+            size,
+            top
+        );
+        self.stack_top = top;
+        self.stack_size = size;
+        self
+    }
+
+    /// Sets the number of instructions after which a call fails with [`Error::StepLimit`].
+    pub fn max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Replaces calls to an address with a function.
+    pub fn stub<F>(mut self, addr: u32, stub: F) -> Self
+    where
+        F: FnMut(&mut Cpu, &mut dyn Memory) -> Result<()> + 'a,
+    {
+        self.stubs.insert(addr, Box::new(stub));
+        self
+    }
+
+    /// Calls the function at an address, returning once it returns.
+    ///
+    /// Each call starts from the registers of [`Harness::cpu`] and the unmodified executable.
+    pub fn call(&mut self, addr: u32, args: &[Arg]) -> Result<Call<'a>> {
+        let stack_base = self.stack_top - self.stack_size;
+        let mut memory = DolMemory::new(self.dol, Ram::new(stack_base, self.stack_size as usize));
+        let mut cpu = self.cpu.clone();
+        cpu.pc = addr;
+        cpu.lr = RETURN_ADDRESS;
+        pass_args(&mut cpu, &mut memory, self.stack_top, args)?;
+        // Writes setting up the call are not part of it.
+        memory.writes.clear();
+
+        let mut trace = Vec::new();
+        while cpu.pc != RETURN_ADDRESS {
+            if let Some(stub) = self.stubs.get_mut(&cpu.pc) {
+                stub(&mut cpu, &mut memory)?;
+                cpu.pc = cpu.lr & !3;
+                continue;
+            }
+            if trace.len() >= self.max_steps {
+                return Err(Error::StepLimit(cpu.pc));
+            }
+            let ins = Ins::new(memory.read_u32(cpu.pc)?, cpu.pc);
+            trace.push(ins);
+            cpu.execute(ins, &mut memory)?;
+        }
+        Ok(Call { cpu, memory, trace })
+    }
+}
+
+/// Passes arguments in registers, then in the parameter area of the stack.
+///
+/// The stack pointer is set below the arguments, with a null back chain.
+fn pass_args(cpu: &mut Cpu, memory: &mut DolMemory, stack_top: u32, args: &[Arg]) -> Result<()> {
+    let mut gpr = 3;
+    let mut fpr = 1;
+    let mut stack = Vec::new();
+    for &arg in args {
+        match arg {
+            Arg::Int(value) if gpr <= 10 => {
+                cpu.gpr[gpr] = value;
+                gpr += 1;
+            }
+            // Register pairs start at an odd register.
+            Arg::Long(value) if gpr | 1 <= 9 => {
+                gpr |= 1;
+                cpu.gpr[gpr] = (value >> 32) as u32;
+                cpu.gpr[gpr + 1] = value as u32;
+                gpr += 2;
+            }
+            Arg::Float(value) if fpr <= 8 => {
+                cpu.fpr[fpr].ps0 = value;
+                fpr += 1;
+            }
+            Arg::Int(value) => stack.extend_from_slice(&value.to_be_bytes()),
+            Arg::Long(value) => {
+                // A doubleword on the stack leaves no registers for words.
+                gpr = 11;
+                stack.resize(stack.len().next_multiple_of(8), 0);
+                stack.extend_from_slice(&value.to_be_bytes());
+            }
+            Arg::Float(value) => {
+                stack.resize(stack.len().next_multiple_of(8), 0);
+                stack.extend_from_slice(&value.to_bits().to_be_bytes());
+            }
+        }
+    }
+    let sp = (stack_top - STACK_ARGS - stack.len() as u32) & !0xF;
+    cpu.gpr[1] = sp;
+    memory.write_u32(sp, 0)?;
+    memory.write(sp + STACK_ARGS, &stack)
+}
+
+/// The outcome of a function call.
+pub struct Call<'a> {
+    /// Registers after the function returned.
+    pub cpu: Cpu,
+    /// Memory after the function returned, with the writes performed.
+    pub memory: DolMemory<'a>,
+    /// Instructions executed, excluding stubs.
+    pub trace: Vec<Ins>,
+}
+
+impl Call<'_> {
+    /// Returns the word returned in `r3`.
+    pub fn ret(&self) -> u32 {
+        self.cpu.gpr[3]
+    }
+
+    /// Returns the doubleword returned in `r3:r4`.
+    pub fn ret_long(&self) -> u64 {
+        (self.cpu.gpr[3] as u64) << 32 | self.cpu.gpr[4] as u64
+    }
+
+    /// Returns the float returned in `f1`.
+    pub fn ret_float(&self) -> f64 {
+        self.cpu.fpr[1].ps0
+    }
+
+    /// Returns the writes to memory in the order performed.
+    pub fn writes(&self) -> &[MemoryWrite] {
+        &self.memory.writes
+    }
+
+    /// Returns the writes outside of the stack, which do not depend on stack frame layout.
+    pub fn writes_outside_stack(&self) -> impl Iterator<Item = &MemoryWrite> {
+        let stack = self.memory.stack();
+        self.memory
+            .writes
+            .iter()
+            .filter(move |write| !stack.contains(&write.addr))
+    }
+}
@@ -0,0 +1,177 @@
+//! Integer arithmetic, compare, logical, rotate and shift instructions.
+
+use ppc750cl::{Ins, Modifier, Opcode};
+
+use crate::{Cpu, Error, Result};
+
+/// Adds three values, returning the sum, the carry and the signed overflow of `a + b`.
+fn add3(a: u32, b: u32, c: u32) -> (u32, bool, bool) {
+    let sum = a as u64 + b as u64 + c as u64;
+    let result = sum as u32;
+    let overflow = (a ^ result) & (b ^ result) & 0x8000_0000 != 0;
+    (result, sum > u32::MAX as u64, overflow)
+}
+
+/// Marks the carry of an addition as written to XER.
+fn with_carry((result, ca, ov): (u32, bool, bool)) -> (u32, Option<bool>, bool) {
+    (result, Some(ca), ov)
+}
+
+/// Returns the mask of bits `mb` to `me` inclusive, wrapping around if `mb > me`.
+fn mask(mb: usize, me: usize) -> u32 {
+    let begin = u32::MAX >> mb;
+    let end = u32::MAX << (31 - me);
+    if mb <= me {
+        begin & end
+    } else {
+        begin | end
+    }
+}
+
+impl Cpu {
+    pub(crate) fn execute_integer(&mut self, ins: Ins) -> Result<()> {
+        let suffix = ins.suffix();
+        let ra = self.gpr[ins.field_rA()];
+        let rb = self.gpr[ins.field_rB()];
+        // Register sources of logical instructions, which write rA.
+        let rs = self.gpr[ins.field_rS()];
+        let simm = ins.field_simm() as i32 as u32;
+        let uimm = ins.field_uimm() as u32;
+        let carry = self.carry() as u32;
+
+        // Arithmetic writing rD, with the carry if any and the overflow.
+        let (result, ca, ov) = match ins.op {
+            Opcode::Add => {
+                let (result, _, ov) = add3(ra, rb, 0);
+                (result, None, ov)
+            }
+            Opcode::Addc => with_carry(add3(ra, rb, 0)),
+            Opcode::Adde => with_carry(add3(ra, rb, carry)),
+            Opcode::Addme => with_carry(add3(ra, u32::MAX, carry)),
+            Opcode::Addze => with_carry(add3(ra, 0, carry)),
+            Opcode::Subf => {
+                let (result, _, ov) = add3(!ra, rb, 1);
+                (result, None, ov)
+            }
+            Opcode::Neg => {
+                let (result, _, ov) = add3(!ra, 0, 1);
+                (result, None, ov)
+            }
+            Opcode::Subfc => with_carry(add3(!ra, rb, 1)),
+            Opcode::Subfe => with_carry(add3(!ra, rb, carry)),
+            Opcode::Subfme => with_carry(add3(!ra, u32::MAX, carry)),
+            Opcode::Subfze => with_carry(add3(!ra, 0, carry)),
+            Opcode::Addic | Opcode::Addic_ => with_carry(add3(ra, simm, 0)),
+            Opcode::Subfic => with_carry(add3(!ra, simm, 1)),
+            Opcode::Addi => (
+                self.gpr_or_zero(ins.field_rA()).wrapping_add(simm),
+                None,
+                false,
+            ),
+            Opcode::Addis => (
+                self.gpr_or_zero(ins.field_rA()).wrapping_add(uimm << 16),
+                None,
+                false,
+            ),
+            Opcode::Mulli => ((ra as i32).wrapping_mul(simm as i32) as u32, None, false),
+            Opcode::Mullw => {
+                let product = ra as i32 as i64 * rb as i32 as i64;
+                (product as u32, None, product != product as i32 as i64)
+            }
+            Opcode::Mulhw => (
+                ((ra as i32 as i64 * rb as i32 as i64) >> 32) as u32,
+                None,
+                false,
+            ),
+            Opcode::Mulhwu => (((ra as u64 * rb as u64) >> 32) as u32, None, false),
+            // The 750CL yields -1 for negative dividends and 0 otherwise on overflow.
+            Opcode::Divw => match (ra as i32).checked_div(rb as i32) {
+                Some(quotient) => (quotient as u32, None, false),
+                None if (ra as i32) < 0 => (u32::MAX, None, true),
+                None => (0, None, true),
+            },
+            Opcode::Divwu => match ra.checked_div(rb) {
+                Some(quotient) => (quotient, None, false),
+                None => (0, None, true),
+            },
+            Opcode::Cmp | Opcode::Cmpi | Opcode::Cmpl | Opcode::Cmpli => {
+                let bits = match ins.op {
+                    Opcode::Cmp => self.compare(ra as i32, rb as i32),
+                    Opcode::Cmpi => self.compare(ra as i32, simm as i32),
+                    Opcode::Cmpl => self.compare(ra, rb),
+                    _ => self.compare(ra, uimm),
+                };
+                self.set_cr_field(ins.field_crfD(), bits);
+                return Ok(());
+            }
+            _ => return self.execute_logical(ins, rs, rb),
+        };
+        self.gpr[ins.field_rD()] = result;
+        if let Some(ca) = ca {
+            self.set_carry(ca);
+        }
+        if suffix.contains(Modifier::OE) {
+            self.set_overflow(ov);
+        }
+        if suffix.contains(Modifier::Rc) || ins.op == Opcode::Addic_ {
+            self.record(result);
+        }
+        Ok(())
+    }
+
+    /// Executes a logical, rotate or shift instruction, which writes rA.
+    fn execute_logical(&mut self, ins: Ins, rs: u32, rb: u32) -> Result<()> {
+        let uimm = ins.field_uimm() as u32;
+        let sh = ins.field_SH() as u32;
+        let rotate_mask = mask(ins.field_MB(), ins.field_ME());
+        let result = match ins.op {
+            Opcode::And => rs & rb,
+            Opcode::Andc => rs & !rb,
+            Opcode::Andi_ => rs & uimm,
+            Opcode::Andis_ => rs & (uimm << 16),
+            Opcode::Or => rs | rb,
+            Opcode::Orc => rs | !rb,
+            Opcode::Ori => rs | uimm,
+            Opcode::Oris => rs | (uimm << 16),
+            Opcode::Xor => rs ^ rb,
+            Opcode::Xori => rs ^ uimm,
+            Opcode::Xoris => rs ^ (uimm << 16),
+            Opcode::Nand => !(rs & rb),
+            Opcode::Nor => !(rs | rb),
+            Opcode::Eqv => !(rs ^ rb),
+            Opcode::Cntlzw => rs.leading_zeros(),
+            Opcode::Extsb => rs as i8 as i32 as u32,
+            Opcode::Extsh => rs as i16 as i32 as u32,
+            Opcode::Rlwinm => rs.rotate_left(sh) & rotate_mask,
+            Opcode::Rlwnm => rs.rotate_left(rb & 31) & rotate_mask,
+            Opcode::Rlwimi => {
+                let ra = self.gpr[ins.field_rA()];
+                (rs.rotate_left(sh) & rotate_mask) | (ra & !rotate_mask)
+            }
+            // Shift amounts of 32 to 63 clear the result.
+            Opcode::Slw => rs.checked_shl(rb & 0x3F).unwrap_or(0),
+            Opcode::Srw => rs.checked_shr(rb & 0x3F).unwrap_or(0),
+            Opcode::Sraw | Opcode::Srawi => {
+                let n = if ins.op == Opcode::Srawi {
+                    sh
+                } else {
+                    rb & 0x3F
+                };
+                let (result, lost) = if n >= 32 {
+                    ((rs as i32 >> 31) as u32, rs != 0)
+                } else {
+                    ((rs as i32 >> n) as u32, rs & ((1 << n) - 1) != 0)
+                };
+                // The carry is set if a negative value loses one bits.
+                self.set_carry((rs as i32) < 0 && lost);
+                result
+            }
+            _ => return Err(Error::Unimplemented(ins)),
+        };
+        self.gpr[ins.field_rA()] = result;
+        if ins.suffix().contains(Modifier::Rc) || matches!(ins.op, Opcode::Andi_ | Opcode::Andis_) {
+            self.record(result);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Interpreter for PowerPC 750CL instructions decoded by [`ppc750cl`].
+//!
+//! The [`Cpu`] holds the architectural register state and executes one
+//! instruction at a time against a [`Memory`].
+
+use ppc750cl::Ins;
+use thiserror::Error;
+
+pub use crate::cpu::Cpu;
+pub use crate::exception::{dsisr, msr, srr1, Exception};
+pub use crate::float::{double_to_single, fpscr, single_to_double, FloatRegister};
+pub use crate::harness::{Arg, Call, DolMemory, Harness, MemoryWrite, Stub, RETURN_ADDRESS};
+pub use crate::memory::{Memory, Ram};
+pub use crate::mmu::{
+    wimg, AccessType, Bat, Fault, Mmu, Protection, Segment, Translated, Translation,
+};
+
+mod branch;
+mod cpu;
+mod estimate;
+mod exception;
+mod float;
+mod harness;
+mod integer;
+mod load_store;
+mod memory;
+mod mmu;
+mod paired;
+mod system;
+
+/// An error that stops execution of an instruction.
+#[derive(Error, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    #[error("Illegal instruction {code:08X} at {addr:08X}")]
+    IllegalInstruction { addr: u32, code: u32 },
+    #[error("Unimplemented instruction {} at {:08X}", .0.op.mnemonic(), .0.addr)]
+    Unimplemented(Ins),
+    #[error("Attempted to access unmapped address {0:08X}")]
+    Unmapped(u32),
+    #[error("Trap at {0:08X}")]
+    Trap(u32),
+    #[error("System call at {0:08X}")]
+    SystemCall(u32),
+    #[error("Privileged instruction at {0:08X} in user mode")]
+    Privileged(u32),
+    #[error("Misaligned access to {0:08X}")]
+    Misaligned(u32),
+    #[error("Step limit reached at {0:08X}")]
+    StepLimit(u32),
+    #[error("Translation of {addr:08X} failed: {fault}")]
+    Translation {
+        addr: u32,
+        access: AccessType,
+        fault: Fault,
+    },
+}
+
+/// The result of executing an instruction.
+pub type Result<V> = std::result::Result<V, Error>;
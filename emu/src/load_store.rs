@@ -0,0 +1,161 @@
+//! Loads and stores, and cache management.
+
+use ppc750cl::{Ins, MemData, MemKind, MemOffset, Opcode};
+
+use crate::float::{double_to_single, single_to_double};
+use crate::{Cpu, Error, FloatRegister, Memory, Result};
+
+/// Size of a cache block in bytes.
+const CACHE_BLOCK: u32 = 32;
+
+impl Cpu {
+    /// Returns the effective address of `rA|0 + rB`.
+    fn indexed_address(&self, ins: Ins) -> u32 {
+        self.gpr_or_zero(ins.field_rA())
+            .wrapping_add(self.gpr[ins.field_rB()])
+    }
+
+    pub(crate) fn execute_load_store<M: Memory>(&mut self, ins: Ins, mem: &mut M) -> Result<()> {
+        match ins.op {
+            Opcode::Lwarx => {
+                let ea = self.indexed_address(ins);
+                if ea & 3 != 0 {
+                    return Err(Error::Misaligned(ea));
+                }
+                self.gpr[ins.field_rD()] = mem.read_u32(ea)?;
+                self.reservation = Some(ea);
+                return Ok(());
+            }
+            Opcode::Stwcx_ => {
+                let ea = self.indexed_address(ins);
+                if ea & 3 != 0 {
+                    return Err(Error::Misaligned(ea));
+                }
+                let stored = self.reservation.take().is_some();
+                if stored {
+                    mem.write_u32(ea, self.gpr[ins.field_rS()])?;
+                }
+                self.set_cr_field(0, (stored as u32) << 1 | self.xer >> 31);
+                return Ok(());
+            }
+            Opcode::Stfiwx => {
+                let ea = self.indexed_address(ins);
+                mem.write_u32(ea, self.fpr[ins.field_frS()].ps0.to_bits() as u32)?;
+                return Ok(());
+            }
+            // External control requires a device behind EAR.
+            Opcode::Eciwx | Opcode::Ecowx => return Err(Error::Unimplemented(ins)),
+            _ => {}
+        }
+        let access = ins
+            .memory_access()
+            .expect("load or store category without memory access");
+        let base = access.base.map_or(0, |reg| self.gpr[reg.0 as usize]);
+        let ea = base.wrapping_add(match access.offset {
+            MemOffset::Displacement(offset) => offset as i32 as u32,
+            MemOffset::Index(reg) => self.gpr[reg.0 as usize],
+        });
+        match (access.kind, access.data) {
+            (MemKind::Load, MemData::Int { width, signed }) => {
+                let mut value = match width {
+                    1 => mem.read_u8(ea)? as u32,
+                    2 => mem.read_u16(ea)? as u32,
+                    _ => mem.read_u32(ea)?,
+                };
+                if access.byte_reversed {
+                    value = value.swap_bytes() >> (32 - 8 * width as u32);
+                }
+                // Only halfwords are loaded algebraically.
+                if signed {
+                    value = value as i16 as i32 as u32;
+                }
+                self.gpr[ins.field_rD()] = value;
+            }
+            (MemKind::Store, MemData::Int { width, .. }) => {
+                let mut value = self.gpr[ins.field_rS()];
+                if access.byte_reversed {
+                    value = value.swap_bytes() >> (32 - 8 * width as u32);
+                }
+                match width {
+                    1 => mem.write_u8(ea, value as u8)?,
+                    2 => mem.write_u16(ea, value as u16)?,
+                    _ => mem.write_u32(ea, value)?,
+                }
+            }
+            // Multiple words must be aligned, unlike other accesses.
+            (_, MemData::Multiple { .. }) if ea & 3 != 0 => return Err(Error::Misaligned(ea)),
+            (MemKind::Load, MemData::Multiple { count }) => {
+                let first = ins.field_rD();
+                for i in 0..count as usize {
+                    self.gpr[first + i] = mem.read_u32(ea.wrapping_add(4 * i as u32))?;
+                }
+            }
+            (MemKind::Store, MemData::Multiple { count }) => {
+                let first = ins.field_rS();
+                for i in 0..count as usize {
+                    mem.write_u32(ea.wrapping_add(4 * i as u32), self.gpr[first + i])?;
+                }
+            }
+            (MemKind::Load, MemData::String { len }) => {
+                let len = len.map_or(self.xer & 0x7F, |len| len as u32);
+                let first = ins.field_rD();
+                // Bytes fill registers from the most significant, zeroing the rest of the last.
+                for i in 0..len {
+                    let reg = (first + i as usize / 4) % 32;
+                    let shift = 24 - 8 * (i % 4);
+                    if i % 4 == 0 {
+                        self.gpr[reg] = 0;
+                    }
+                    self.gpr[reg] |= (mem.read_u8(ea.wrapping_add(i))? as u32) << shift;
+                }
+            }
+            (MemKind::Store, MemData::String { len }) => {
+                let len = len.map_or(self.xer & 0x7F, |len| len as u32);
+                let first = ins.field_rS();
+                for i in 0..len {
+                    let reg = (first + i as usize / 4) % 32;
+                    let shift = 24 - 8 * (i % 4);
+                    mem.write_u8(ea.wrapping_add(i), (self.gpr[reg] >> shift) as u8)?;
+                }
+            }
+            // Singles are loaded into both halves of the register.
+            (MemKind::Load, MemData::Single) => {
+                let value = single_to_double(mem.read_u32(ea)?);
+                self.fpr[ins.field_frD()] = FloatRegister::splat(value);
+            }
+            (MemKind::Store, MemData::Single) => {
+                mem.write_u32(ea, double_to_single(self.fpr[ins.field_frS()].ps0))?;
+            }
+            (MemKind::Load, MemData::Double) => {
+                self.fpr[ins.field_frD()].ps0 = f64::from_bits(mem.read_u64(ea)?);
+            }
+            (MemKind::Store, MemData::Double) => {
+                mem.write_u64(ea, self.fpr[ins.field_frS()].ps0.to_bits())?;
+            }
+            (MemKind::Load, MemData::Quantized { gqr, single }) => {
+                self.load_quantized(mem, ea, ins.field_frD(), gqr, single)?;
+            }
+            (MemKind::Store, MemData::Quantized { gqr, single }) => {
+                self.store_quantized(mem, ea, ins.field_frS(), gqr, single)?;
+            }
+        }
+        if access.update {
+            self.gpr[ins.field_rA()] = ea;
+        }
+        Ok(())
+    }
+
+    /// Executes a cache management instruction, which is a no-op without a cache model
+    /// except for clearing a block with `dcbz`.
+    pub(crate) fn execute_cache<M: Memory>(&mut self, ins: Ins, mem: &mut M) -> Result<()> {
+        match ins.op {
+            Opcode::Dcbz => {
+                let ea = self.indexed_address(ins) & !(CACHE_BLOCK - 1);
+                mem.write(ea, &[0; CACHE_BLOCK as usize])
+            }
+            // Locked cache blocks are not backed by memory.
+            Opcode::DcbzL => Err(Error::Unimplemented(ins)),
+            _ => Ok(()),
+        }
+    }
+}
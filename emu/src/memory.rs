@@ -0,0 +1,93 @@
+use crate::{Error, Result};
+
+/// Big-endian memory accessed by the interpreter.
+///
+/// Only [`Memory::read`] and [`Memory::write`] need to be implemented,
+/// accesses are not required to be aligned.
+pub trait Memory {
+    /// Reads bytes starting at the given address.
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<()>;
+
+    /// Writes bytes starting at the given address.
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<()>;
+
+    fn read_u8(&mut self, addr: u32) -> Result<u8> {
+        let mut buf = [0u8; 1];
+        self.read(addr, &mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_u16(&mut self, addr: u32) -> Result<u16> {
+        let mut buf = [0u8; 2];
+        self.read(addr, &mut buf)?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    fn read_u32(&mut self, addr: u32) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        self.read(addr, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn read_u64(&mut self, addr: u32) -> Result<u64> {
+        let mut buf = [0u8; 8];
+        self.read(addr, &mut buf)?;
+        Ok(u64::from_be_bytes(buf))
+    }
+
+    fn write_u8(&mut self, addr: u32, value: u8) -> Result<()> {
+        self.write(addr, &[value])
+    }
+
+    fn write_u16(&mut self, addr: u32, value: u16) -> Result<()> {
+        self.write(addr, &value.to_be_bytes())
+    }
+
+    fn write_u32(&mut self, addr: u32, value: u32) -> Result<()> {
+        self.write(addr, &value.to_be_bytes())
+    }
+
+    fn write_u64(&mut self, addr: u32, value: u64) -> Result<()> {
+        self.write(addr, &value.to_be_bytes())
+    }
+}
+
+/// A contiguous block of memory starting at `base`.
+#[derive(Clone, Debug, Default)]
+pub struct Ram {
+    pub base: u32,
+    pub data: Vec<u8>,
+}
+
+impl Ram {
+    /// Creates zeroed memory of the given size.
+    pub fn new(base: u32, size: usize) -> Self {
+        Self {
+            base,
+            data: vec![0; size],
+        }
+    }
+
+    /// Returns the offsets of an access, if it is within bounds.
+    fn range(&self, addr: u32, len: usize) -> Result<std::ops::Range<usize>> {
+        let start = addr.wrapping_sub(self.base) as usize;
+        match start.checked_add(len) {
+            Some(end) if addr >= self.base && end <= self.data.len() => Ok(start..end),
+            _ => Err(Error::Unmapped(addr)),
+        }
+    }
+}
+
+impl Memory for Ram {
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<()> {
+        let range = self.range(addr, buf.len())?;
+        buf.copy_from_slice(&self.data[range]);
+        Ok(())
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<()> {
+        let range = self.range(addr, data.len())?;
+        self.data[range].copy_from_slice(data);
+        Ok(())
+    }
+}
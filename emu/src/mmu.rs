@@ -0,0 +1,399 @@
+//! Address translation by block address translation (BAT) registers, segment
+//! registers and the hashed page table.
+//!
+//! Translations are not cached, so `tlbie` and `tlbsync` have no effect and page
+//! table changes are visible immediately.
+
+use core::fmt;
+
+use crate::{Cpu, Error, Memory, Result};
+
+/// Number of the first IBAT register, `IBAT0U`.
+const IBAT0: usize = 528;
+/// Number of the first DBAT register, `DBAT0U`.
+const DBAT0: usize = 536;
+/// Number of the fifth IBAT register on Broadway, `IBAT4U`.
+const IBAT4: usize = 560;
+/// Number of the fifth DBAT register on Broadway, `DBAT4U`.
+const DBAT4: usize = 568;
+const SDR1: usize = 25;
+const HID4: usize = 1011;
+/// Enables the BATs 4 to 7 of Broadway.
+const HID4_SBE: u32 = 0x0200_0000;
+
+/// Write-through, caching-inhibited, memory coherence and guarded bits.
+pub mod wimg {
+    pub const W: u8 = 0b1000;
+    pub const I: u8 = 0b0100;
+    pub const M: u8 = 0b0010;
+    pub const G: u8 = 0b0001;
+}
+
+/// Kind of memory access being translated.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AccessType {
+    Read,
+    Write,
+    /// Instruction fetch, translated by the IBATs.
+    Execute,
+}
+
+/// Reason a translation failed.
+#[derive(thiserror::Error, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Fault {
+    #[error("no page table entry")]
+    PageNotFound,
+    #[error("protection violation")]
+    Protection,
+    #[error("fetch from no-execute segment")]
+    NoExecute,
+    #[error("direct-store segment")]
+    DirectStore,
+}
+
+/// Permissions granted by the PP bits.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Protection {
+    NoAccess,
+    ReadOnly,
+    ReadWrite,
+}
+
+impl Protection {
+    fn allows(self, access: AccessType) -> bool {
+        match self {
+            Self::NoAccess => false,
+            Self::ReadOnly => access != AccessType::Write,
+            Self::ReadWrite => true,
+        }
+    }
+}
+
+impl fmt::Display for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NoAccess => "--",
+            Self::ReadOnly => "R-",
+            Self::ReadWrite => "RW",
+        })
+    }
+}
+
+/// A pair of BAT registers mapping a block of 128 KiB to 256 MiB.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Bat {
+    pub upper: u32,
+    pub lower: u32,
+}
+
+impl Bat {
+    /// Returns the block effective page index.
+    pub fn bepi(self) -> u32 {
+        self.upper & 0xFFFE_0000
+    }
+
+    /// Returns the mask of effective address bits passed through to the physical address.
+    pub fn block_mask(self) -> u32 {
+        ((self.upper >> 2) & 0x7FF) << 17 | 0x1_FFFF
+    }
+
+    /// Returns the size of the block in bytes.
+    pub fn size(self) -> u32 {
+        self.block_mask().wrapping_add(1)
+    }
+
+    /// Returns whether the block is valid in supervisor mode.
+    pub fn vs(self) -> bool {
+        self.upper & 0b10 != 0
+    }
+
+    /// Returns whether the block is valid in user mode.
+    pub fn vp(self) -> bool {
+        self.upper & 0b01 != 0
+    }
+
+    /// Returns the block real page number.
+    pub fn brpn(self) -> u32 {
+        self.lower & 0xFFFE_0000
+    }
+
+    pub fn wimg(self) -> u8 {
+        ((self.lower >> 3) & 0xF) as u8
+    }
+
+    pub fn protection(self) -> Protection {
+        match self.lower & 0b11 {
+            0b00 => Protection::NoAccess,
+            0b10 => Protection::ReadWrite,
+            _ => Protection::ReadOnly,
+        }
+    }
+
+    /// Returns whether the block is valid in the given mode.
+    pub fn is_valid(self, supervisor: bool) -> bool {
+        if supervisor {
+            self.vs()
+        } else {
+            self.vp()
+        }
+    }
+
+    /// Returns the physical address of an effective address within the block.
+    pub fn translate(self, ea: u32, supervisor: bool) -> Option<u32> {
+        let mask = self.block_mask();
+        if !self.is_valid(supervisor) || (ea & !mask) != (self.bepi() & !mask) {
+            return None;
+        }
+        Some(self.brpn() | (ea & mask))
+    }
+}
+
+impl fmt::Display for Bat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mask = self.block_mask();
+        write!(
+            f,
+            "{:08X}-{:08X} -> {:08X} WIMG={:04b} {}",
+            self.bepi() & !mask,
+            (self.bepi() & !mask) | mask,
+            self.brpn(),
+            self.wimg(),
+            self.protection(),
+        )?;
+        if self.vs() {
+            f.write_str(" Vs")?;
+        }
+        if self.vp() {
+            f.write_str(" Vp")?;
+        }
+        Ok(())
+    }
+}
+
+/// A segment register, selecting a virtual segment of 256 MiB.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Segment(pub u32);
+
+impl Segment {
+    /// Returns whether the segment is a direct-store segment.
+    pub fn t(self) -> bool {
+        self.0 & 0x8000_0000 != 0
+    }
+
+    /// Returns the supervisor-state protection key.
+    pub fn ks(self) -> bool {
+        self.0 & 0x4000_0000 != 0
+    }
+
+    /// Returns the user-state protection key.
+    pub fn kp(self) -> bool {
+        self.0 & 0x2000_0000 != 0
+    }
+
+    /// Returns whether instruction fetches are prohibited.
+    pub fn n(self) -> bool {
+        self.0 & 0x1000_0000 != 0
+    }
+
+    /// Returns the virtual segment ID.
+    pub fn vsid(self) -> u32 {
+        self.0 & 0xFF_FFFF
+    }
+}
+
+/// A successful translation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Translation {
+    pub addr: u32,
+    pub wimg: u8,
+    /// Whether the address was translated by a BAT rather than the page table.
+    pub block: bool,
+}
+
+/// The state of the memory management unit.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mmu {
+    /// Instruction BATs, the last four being enabled by `HID4[SBE]` on Broadway.
+    pub ibat: [Bat; 8],
+    pub dbat: [Bat; 8],
+    /// Whether the BATs 4 to 7 are enabled.
+    pub extended: bool,
+    pub sr: [Segment; 16],
+    /// Page table base and mask.
+    pub sdr1: u32,
+}
+
+/// Set in PTEs on access.
+const PTE_R: u32 = 0x100;
+/// Set in PTEs on writes.
+const PTE_C: u32 = 0x80;
+
+impl Mmu {
+    /// Returns the BATs used by an access, valid or not.
+    pub fn bats(&self, access: AccessType) -> &[Bat] {
+        let bats = if access == AccessType::Execute {
+            &self.ibat
+        } else {
+            &self.dbat
+        };
+        &bats[..if self.extended { 8 } else { 4 }]
+    }
+
+    /// Translates an effective address, trying the BATs then the page table.
+    ///
+    /// The page table is read from physical memory, and its referenced and changed
+    /// bits are updated as done by the processor.
+    pub fn translate<M: Memory>(
+        &self,
+        mem: &mut M,
+        ea: u32,
+        access: AccessType,
+        supervisor: bool,
+    ) -> Result<Translation> {
+        let fault = |fault| {
+            Err(Error::Translation {
+                addr: ea,
+                access,
+                fault,
+            })
+        };
+        for bat in self.bats(access) {
+            if let Some(addr) = bat.translate(ea, supervisor) {
+                if !bat.protection().allows(access) {
+                    return fault(Fault::Protection);
+                }
+                return Ok(Translation {
+                    addr,
+                    wimg: bat.wimg(),
+                    block: true,
+                });
+            }
+        }
+        let segment = self.sr[(ea >> 28) as usize];
+        if segment.t() {
+            return fault(Fault::DirectStore);
+        }
+        if access == AccessType::Execute && segment.n() {
+            return fault(Fault::NoExecute);
+        }
+        let page_index = (ea >> 12) & 0xFFFF;
+        let hash = (segment.vsid() & 0x7_FFFF) ^ page_index;
+        for (secondary, hash) in [(false, hash), (true, !hash & 0x7_FFFF)] {
+            let pteg = self.pteg(hash);
+            for i in 0..8 {
+                let addr = pteg + 8 * i;
+                let word0 = mem.read_u32(addr)?;
+                let expected =
+                    0x8000_0000 | segment.vsid() << 7 | (secondary as u32) << 6 | (ea >> 22) & 0x3F;
+                if word0 != expected {
+                    continue;
+                }
+                let word1 = mem.read_u32(addr + 4)?;
+                let key = if supervisor {
+                    segment.ks()
+                } else {
+                    segment.kp()
+                };
+                let protection = match (key, word1 & 0b11) {
+                    (false, 0b11) | (true, 0b01) | (true, 0b11) => Protection::ReadOnly,
+                    (false, _) | (true, 0b10) => Protection::ReadWrite,
+                    (true, _) => Protection::NoAccess,
+                };
+                if !protection.allows(access) {
+                    return fault(Fault::Protection);
+                }
+                let changed = if access == AccessType::Write {
+                    PTE_C
+                } else {
+                    0
+                };
+                if word1 & (PTE_R | changed) != PTE_R | changed {
+                    mem.write_u32(addr + 4, word1 | PTE_R | changed)?;
+                }
+                return Ok(Translation {
+                    addr: (word1 & 0xFFFF_F000) | (ea & 0xFFF),
+                    wimg: ((word1 >> 3) & 0xF) as u8,
+                    block: false,
+                });
+            }
+        }
+        fault(Fault::PageNotFound)
+    }
+
+    /// Returns the physical address of the page table entry group of a hash.
+    fn pteg(&self, hash: u32) -> u32 {
+        let htaborg = self.sdr1 & 0xFFFF_0000;
+        let htabmask = self.sdr1 & 0x1FF;
+        // The upper 9 bits of the hash are masked and ORed into the table origin.
+        let upper = ((hash >> 10) & htabmask) << 16;
+        htaborg | upper | (hash & 0x3FF) << 6
+    }
+}
+
+/// Memory accessed by effective addresses, translated by an MMU.
+///
+/// Accesses crossing a page boundary are translated page by page.
+pub struct Translated<'a, M> {
+    pub mmu: Mmu,
+    pub supervisor: bool,
+    pub mem: &'a mut M,
+}
+
+impl<M: Memory> Translated<'_, M> {
+    /// Calls a function with the physical address and range of each page accessed.
+    fn for_pages<F>(&mut self, ea: u32, len: usize, access: AccessType, mut f: F) -> Result<()>
+    where
+        F: FnMut(&mut M, u32, core::ops::Range<usize>) -> Result<()>,
+    {
+        let mut offset = 0;
+        while offset < len {
+            let addr = ea.wrapping_add(offset as u32);
+            let end = (offset + 0x1000 - (addr & 0xFFF) as usize).min(len);
+            let translation = self
+                .mmu
+                .translate(self.mem, addr, access, self.supervisor)?;
+            f(self.mem, translation.addr, offset..end)?;
+            offset = end;
+        }
+        Ok(())
+    }
+}
+
+impl<M: Memory> Memory for Translated<'_, M> {
+    fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<()> {
+        self.for_pages(addr, buf.len(), AccessType::Read, |mem, pa, range| {
+            mem.read(pa, &mut buf[range])
+        })
+    }
+
+    fn write(&mut self, addr: u32, data: &[u8]) -> Result<()> {
+        self.for_pages(addr, data.len(), AccessType::Write, |mem, pa, range| {
+            mem.write(pa, &data[range])
+        })
+    }
+}
+
+impl Cpu {
+    /// Returns the state of the memory management unit.
+    pub fn mmu(&self) -> Mmu {
+        let bat = |num: usize| Bat {
+            upper: self.spr[num],
+            lower: self.spr[num + 1],
+        };
+        let mut mmu = Mmu {
+            extended: self.spr[HID4] & HID4_SBE != 0,
+            sdr1: self.spr[SDR1],
+            ..Mmu::default()
+        };
+        for i in 0..4 {
+            mmu.ibat[i] = bat(IBAT0 + 2 * i);
+            mmu.dbat[i] = bat(DBAT0 + 2 * i);
+            mmu.ibat[4 + i] = bat(IBAT4 + 2 * i);
+            mmu.dbat[4 + i] = bat(DBAT4 + 2 * i);
+        }
+        for (segment, &value) in mmu.sr.iter_mut().zip(&self.sr) {
+            *segment = Segment(value);
+        }
+        mmu
+    }
+}
@@ -0,0 +1,284 @@
+//! Paired-single instructions and quantized loads and stores.
+//!
+//! Both halves of a register are computed independently in single precision,
+//! exceptions of both are accumulated and FPRF is set from `ps0`.
+
+use ppc750cl::{Ins, Opcode, GQR};
+
+use crate::estimate::{reciprocal_estimate, reciprocal_sqrt_estimate};
+use crate::float::fpscr::*;
+use crate::float::{arith, double_to_single, is_snan, round_single, single_to_double, Arith};
+use crate::{Cpu, Error, FloatRegister, Memory, Result};
+
+/// Number of the first GQR special-purpose register.
+pub(crate) const GQR0: usize = 912;
+
+/// Data types of quantized values.
+const TYPE_U8: u32 = 4;
+const TYPE_U16: u32 = 5;
+const TYPE_S8: u32 = 6;
+const TYPE_S16: u32 = 7;
+
+/// A type and scale of quantized values, half of a GQR.
+#[derive(Copy, Clone)]
+struct Quantization {
+    ty: u32,
+    /// Power of two by which values are multiplied when stored and divided when loaded.
+    scale: i32,
+}
+
+impl Quantization {
+    /// Decodes the half of a GQR at the given shift, 0 for stores and 16 for loads.
+    fn new(gqr: u32, shift: u32) -> Self {
+        let half = gqr >> shift;
+        Self {
+            ty: half & 0x7,
+            // The scale is a signed 6-bit field.
+            scale: ((half >> 8) as i32) << 26 >> 26,
+        }
+    }
+
+    /// Returns the size of a value in bytes, types 1 to 3 being undefined and
+    /// treated as floats.
+    fn size(self) -> u32 {
+        match self.ty {
+            TYPE_U8 | TYPE_S8 => 1,
+            TYPE_U16 | TYPE_S16 => 2,
+            _ => 4,
+        }
+    }
+
+    fn load<M: Memory>(self, mem: &mut M, ea: u32) -> Result<f64> {
+        let value = match self.ty {
+            TYPE_U8 => mem.read_u8(ea)? as f32,
+            TYPE_U16 => mem.read_u16(ea)? as f32,
+            TYPE_S8 => mem.read_u8(ea)? as i8 as f32,
+            TYPE_S16 => mem.read_u16(ea)? as i16 as f32,
+            _ => return Ok(single_to_double(mem.read_u32(ea)?)),
+        };
+        Ok((value * (-self.scale as f32).exp2()) as f64)
+    }
+
+    /// Stores a value, saturating to the range of integer types.
+    ///
+    /// NaNs are stored as zero to integer types.
+    fn store<M: Memory>(self, mem: &mut M, ea: u32, value: f64) -> Result<()> {
+        let scaled = value as f32 * (self.scale as f32).exp2();
+        match self.ty {
+            TYPE_U8 => mem.write_u8(ea, scaled as u8),
+            TYPE_U16 => mem.write_u16(ea, scaled as u16),
+            TYPE_S8 => mem.write_u8(ea, scaled as i8 as u8),
+            TYPE_S16 => mem.write_u16(ea, scaled as i16 as u16),
+            _ => mem.write_u32(ea, double_to_single(value)),
+        }
+    }
+}
+
+impl Cpu {
+    /// Loads a quantized paired single, or only `ps0` with `ps1` set to 1.0 if `single`.
+    pub(crate) fn load_quantized<M: Memory>(
+        &mut self,
+        mem: &mut M,
+        ea: u32,
+        d: usize,
+        gqr: GQR,
+        single: bool,
+    ) -> Result<()> {
+        let quantization = Quantization::new(self.spr[GQR0 + gqr.0 as usize], 16);
+        let ps0 = quantization.load(mem, ea)?;
+        let ps1 = if single {
+            1.0
+        } else {
+            quantization.load(mem, ea.wrapping_add(quantization.size()))?
+        };
+        self.fpr[d] = FloatRegister { ps0, ps1 };
+        Ok(())
+    }
+
+    /// Stores a quantized paired single, or only `ps0` if `single`.
+    pub(crate) fn store_quantized<M: Memory>(
+        &mut self,
+        mem: &mut M,
+        ea: u32,
+        s: usize,
+        gqr: GQR,
+        single: bool,
+    ) -> Result<()> {
+        let quantization = Quantization::new(self.spr[GQR0 + gqr.0 as usize], 0);
+        let value = self.fpr[s];
+        quantization.store(mem, ea, value.ps0)?;
+        if !single {
+            quantization.store(mem, ea.wrapping_add(quantization.size()), value.ps1)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn execute_paired(&mut self, ins: Ins) -> Result<()> {
+        let a = self.fpr[ins.field_frA()];
+        let b = self.fpr[ins.field_frB()];
+        let c = self.fpr[ins.field_frC()];
+        let d = ins.field_frD();
+        let mode = self.rounding_mode();
+        let madd = |subtract, negate| Arith::MulAdd { subtract, negate };
+        // Operations of each half, with the operands `a`, `b` and `c` of the half.
+        let (op, ps0, ps1) = match ins.op {
+            Opcode::PsAdd => (Arith::Add, (a.ps0, b.ps0, c.ps0), (a.ps1, b.ps1, c.ps1)),
+            Opcode::PsSub => (Arith::Sub, (a.ps0, b.ps0, c.ps0), (a.ps1, b.ps1, c.ps1)),
+            Opcode::PsMul => (Arith::Mul, (a.ps0, b.ps0, c.ps0), (a.ps1, b.ps1, c.ps1)),
+            Opcode::PsDiv => (Arith::Div, (a.ps0, b.ps0, c.ps0), (a.ps1, b.ps1, c.ps1)),
+            Opcode::PsMuls0 => (Arith::Mul, (a.ps0, b.ps0, c.ps0), (a.ps1, b.ps1, c.ps0)),
+            Opcode::PsMuls1 => (Arith::Mul, (a.ps0, b.ps0, c.ps1), (a.ps1, b.ps1, c.ps1)),
+            Opcode::PsMadd => (
+                madd(false, false),
+                (a.ps0, b.ps0, c.ps0),
+                (a.ps1, b.ps1, c.ps1),
+            ),
+            Opcode::PsMsub => (
+                madd(true, false),
+                (a.ps0, b.ps0, c.ps0),
+                (a.ps1, b.ps1, c.ps1),
+            ),
+            Opcode::PsNmadd => (
+                madd(false, true),
+                (a.ps0, b.ps0, c.ps0),
+                (a.ps1, b.ps1, c.ps1),
+            ),
+            Opcode::PsNmsub => (
+                madd(true, true),
+                (a.ps0, b.ps0, c.ps0),
+                (a.ps1, b.ps1, c.ps1),
+            ),
+            Opcode::PsMadds0 => (
+                madd(false, false),
+                (a.ps0, b.ps0, c.ps0),
+                (a.ps1, b.ps1, c.ps0),
+            ),
+            Opcode::PsMadds1 => (
+                madd(false, false),
+                (a.ps0, b.ps0, c.ps1),
+                (a.ps1, b.ps1, c.ps1),
+            ),
+            Opcode::PsSum0 | Opcode::PsSum1 => {
+                let sum = arith(Arith::Add, a.ps0, b.ps1, 0.0, true, mode);
+                let (ps0, ps1) = if ins.op == Opcode::PsSum0 {
+                    (sum.value, round_single(c.ps1, mode).0)
+                } else {
+                    (round_single(c.ps0, mode).0, sum.value)
+                };
+                // FPRF describes the computed half.
+                self.write_paired(d, FloatRegister { ps0, ps1 }, sum.value, sum.exceptions);
+                self.record_float(ins);
+                return Ok(());
+            }
+            _ => return self.execute_paired_other(ins, a, b, c),
+        };
+        let r0 = arith(op, ps0.0, ps0.1, ps0.2, true, mode);
+        let r1 = arith(op, ps1.0, ps1.1, ps1.2, true, mode);
+        let result = FloatRegister {
+            ps0: r0.value,
+            ps1: r1.value,
+        };
+        self.write_paired(d, result, result.ps0, r0.exceptions | r1.exceptions);
+        self.record_float(ins);
+        Ok(())
+    }
+
+    /// Writes the result of a paired-single instruction, unless suppressed by an
+    /// enabled exception, setting FPRF from `fprf`.
+    fn write_paired(&mut self, d: usize, result: FloatRegister, fprf: f64, exceptions: u32) {
+        if !self.suppresses_result(exceptions) {
+            self.fpr[d] = result;
+            self.set_fprf(fprf, true);
+        }
+        self.raise(exceptions);
+    }
+
+    /// Executes moves, merges, selects, estimates and comparisons.
+    fn execute_paired_other(
+        &mut self,
+        ins: Ins,
+        a: FloatRegister,
+        b: FloatRegister,
+        c: FloatRegister,
+    ) -> Result<()> {
+        let d = ins.field_frD();
+        let select = |a: f64, b, c| if a >= 0.0 { c } else { b };
+        let result = match ins.op {
+            Opcode::PsMr => b,
+            Opcode::PsNeg => FloatRegister {
+                ps0: -b.ps0,
+                ps1: -b.ps1,
+            },
+            Opcode::PsAbs => FloatRegister {
+                ps0: b.ps0.abs(),
+                ps1: b.ps1.abs(),
+            },
+            Opcode::PsNabs => FloatRegister {
+                ps0: -b.ps0.abs(),
+                ps1: -b.ps1.abs(),
+            },
+            Opcode::PsMerge00 => FloatRegister {
+                ps0: a.ps0,
+                ps1: b.ps0,
+            },
+            Opcode::PsMerge01 => FloatRegister {
+                ps0: a.ps0,
+                ps1: b.ps1,
+            },
+            Opcode::PsMerge10 => FloatRegister {
+                ps0: a.ps1,
+                ps1: b.ps0,
+            },
+            Opcode::PsMerge11 => FloatRegister {
+                ps0: a.ps1,
+                ps1: b.ps1,
+            },
+            Opcode::PsSel => FloatRegister {
+                ps0: select(a.ps0, b.ps0, c.ps0),
+                ps1: select(a.ps1, b.ps1, c.ps1),
+            },
+            Opcode::PsRes | Opcode::PsRsqrte => {
+                let halves = [b.ps0, b.ps1];
+                let mut exceptions = 0;
+                if halves.iter().any(|&x| is_snan(x)) {
+                    exceptions |= VXSNAN;
+                }
+                if halves.contains(&0.0) {
+                    exceptions |= ZX;
+                }
+                let result = if ins.op == Opcode::PsRes {
+                    FloatRegister {
+                        ps0: reciprocal_estimate(b.ps0),
+                        ps1: reciprocal_estimate(b.ps1),
+                    }
+                } else {
+                    if halves.iter().any(|&x| x < 0.0) {
+                        exceptions |= VXSQRT;
+                    }
+                    let mode = self.rounding_mode();
+                    FloatRegister {
+                        ps0: round_single(reciprocal_sqrt_estimate(b.ps0), mode).0,
+                        ps1: round_single(reciprocal_sqrt_estimate(b.ps1), mode).0,
+                    }
+                };
+                self.write_paired(d, result, result.ps0, exceptions);
+                self.record_float(ins);
+                return Ok(());
+            }
+            Opcode::PsCmpu0 | Opcode::PsCmpo0 => {
+                let ordered = ins.op == Opcode::PsCmpo0;
+                self.compare_float(ins.field_crfD(), a.ps0, b.ps0, ordered);
+                return Ok(());
+            }
+            Opcode::PsCmpu1 | Opcode::PsCmpo1 => {
+                let ordered = ins.op == Opcode::PsCmpo1;
+                self.compare_float(ins.field_crfD(), a.ps1, b.ps1, ordered);
+                return Ok(());
+            }
+            _ => return Err(Error::Unimplemented(ins)),
+        };
+        self.fpr[d] = result;
+        self.record_float(ins);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Special-purpose register moves, synchronization, traps and system calls.
+
+use ppc750cl::{Ins, Opcode};
+
+use crate::{msr, srr1, Cpu, Error, Result};
+
+impl Cpu {
+    /// Returns whether a trap condition of `tw` or `twi` is met.
+    fn trap_condition(to: usize, a: u32, b: u32) -> bool {
+        ((a as i32) < (b as i32) && to & 0b10000 != 0)
+            || ((a as i32) > (b as i32) && to & 0b01000 != 0)
+            || (a == b && to & 0b00100 != 0)
+            || (a < b && to & 0b00010 != 0)
+            || (a > b && to & 0b00001 != 0)
+    }
+
+    /// Returns whether an instruction may only be executed in supervisor mode.
+    pub(crate) fn is_privileged(ins: Ins) -> bool {
+        match ins.op {
+            // SPRs with the upper bit of their lower half set are privileged.
+            Opcode::Mfspr | Opcode::Mtspr => ins.field_spr() & 0x10 != 0,
+            Opcode::Mfmsr
+            | Opcode::Mtmsr
+            | Opcode::Mfsr
+            | Opcode::Mtsr
+            | Opcode::Mfsrin
+            | Opcode::Mtsrin
+            | Opcode::Rfi
+            | Opcode::Tlbie
+            | Opcode::Tlbsync
+            | Opcode::Dcbi => true,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn execute_system(&mut self, ins: Ins) -> Result<()> {
+        match ins.op {
+            Opcode::Mfspr => self.gpr[ins.field_rD()] = self.read_spr(ins.field_spr()),
+            Opcode::Mtspr => self.write_spr(ins.field_spr(), self.gpr[ins.field_rS()]),
+            Opcode::Mftb => self.gpr[ins.field_rD()] = self.read_spr(ins.field_tbr()),
+            Opcode::Mfmsr => self.gpr[ins.field_rD()] = self.msr,
+            Opcode::Mtmsr => self.msr = self.gpr[ins.field_rS()],
+            Opcode::Mfsr => self.gpr[ins.field_rD()] = self.sr[ins.field_sr()],
+            Opcode::Mtsr => self.sr[ins.field_sr()] = self.gpr[ins.field_rS()],
+            Opcode::Mfsrin => {
+                self.gpr[ins.field_rD()] = self.sr[(self.gpr[ins.field_rB()] >> 28) as usize]
+            }
+            Opcode::Mtsrin => {
+                self.sr[(self.gpr[ins.field_rB()] >> 28) as usize] = self.gpr[ins.field_rS()]
+            }
+            Opcode::Tw | Opcode::Twi => {
+                let a = self.gpr[ins.field_rA()];
+                let b = if ins.op == Opcode::Twi {
+                    ins.field_simm() as i32 as u32
+                } else {
+                    self.gpr[ins.field_rB()]
+                };
+                if Self::trap_condition(ins.field_TO(), a, b) {
+                    return Err(Error::Trap(ins.addr));
+                }
+            }
+            Opcode::Sc => return Err(Error::SystemCall(ins.addr)),
+            Opcode::Rfi => {
+                self.msr = (self.msr & !srr1::MSR | self.srr1 & srr1::MSR) & !msr::POW;
+                self.pc = self.srr0 & !3;
+            }
+            // Synchronization has no effect without a pipeline, nor TLB management as
+            // translations are not cached.
+            _ => {}
+        }
+        Ok(())
+    }
+}
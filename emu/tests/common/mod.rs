@@ -0,0 +1,35 @@
+//! Fixtures shared by the emulator tests.
+
+// Each test crate only uses some of the fixtures.
+#![allow(dead_code)]
+
+use ppc750cl::asm::assemble;
+use ppc750cl::Ins;
+use ppc750cl_emu::{Cpu, Error, Memory, Ram};
+
+/// Start of the cached mirror of main memory, where most tests run.
+pub const BASE: u32 = 0x8000_0000;
+
+/// FPRF flags of a positive normal number.
+pub const POSITIVE: u32 = 0x04 << 12;
+
+/// Returns a CPU at `BASE` with `size` bytes of RAM there, and r4 pointing to `BASE + 0x10`.
+pub fn setup(size: usize) -> (Cpu, Ram) {
+    let mut cpu = Cpu::new(BASE);
+    cpu.gpr[4] = BASE + 0x10;
+    (cpu, Ram::new(BASE, size))
+}
+
+/// Assembles an instruction and executes it at the program counter.
+pub fn exec<M: Memory>(cpu: &mut Cpu, mem: &mut M, text: &str) -> Result<(), Error> {
+    let code = assemble(text).unwrap();
+    cpu.execute(Ins::new(code, cpu.pc), mem)
+}
+
+/// Assembles instructions and writes them to memory from `addr` on.
+pub fn write_code<M: Memory>(mem: &mut M, addr: u32, text: &[&str]) {
+    for (i, text) in text.iter().enumerate() {
+        mem.write_u32(addr + 4 * i as u32, assemble(text).unwrap())
+            .unwrap();
+    }
+}
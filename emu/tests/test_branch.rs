@@ -0,0 +1,188 @@
+mod common;
+
+use ppc750cl::asm::assemble;
+use ppc750cl::Ins;
+use ppc750cl_emu::{Cpu, Error, Ram};
+
+use common::BASE;
+
+/// Executes an instruction without memory.
+fn exec(cpu: &mut Cpu, text: &str) -> Result<(), Error> {
+    common::exec(cpu, &mut Ram::default(), text)
+}
+
+#[test]
+fn test_emu_branch() {
+    let mut cpu = Cpu::new(BASE);
+    exec(&mut cpu, "b 0x100").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x100);
+    exec(&mut cpu, "bl -0x10").unwrap();
+    assert_eq!((cpu.pc, cpu.lr), (BASE + 0xF0, BASE + 0x104));
+    exec(&mut cpu, "blr").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x104);
+    cpu.ctr = BASE + 0x203;
+    exec(&mut cpu, "bctrl").unwrap();
+    assert_eq!((cpu.pc, cpu.lr), (BASE + 0x200, BASE + 0x108));
+    // The link register is read before it is written.
+    exec(&mut cpu, "blrl").unwrap();
+    assert_eq!((cpu.pc, cpu.lr), (BASE + 0x108, BASE + 0x204));
+}
+
+#[test]
+fn test_emu_branch_conditional() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.set_cr_field(1, 0b0010);
+    exec(&mut cpu, "beq cr1, 0x20").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x20);
+    exec(&mut cpu, "bne cr1, 0x20").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x24);
+    exec(&mut cpu, "blt 0x20").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x28);
+    // Not taken, but still links.
+    exec(&mut cpu, "bltl 0x20").unwrap();
+    assert_eq!((cpu.pc, cpu.lr), (BASE + 0x2C, BASE + 0x2C));
+}
+
+#[test]
+fn test_emu_branch_ctr() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.ctr = 2;
+    exec(&mut cpu, "bdnz -0x8").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE - 0x8, 1));
+    exec(&mut cpu, "bdnz -0x8").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE - 0x4, 0));
+    // The count register wraps around.
+    exec(&mut cpu, "bdz 0x8").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE, u32::MAX));
+    cpu.ctr = 1;
+    cpu.set_cr_bit(2, true);
+    exec(&mut cpu, "bdzt eq, 0x10").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x10);
+}
+
+#[test]
+fn test_emu_branch_basic() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.ctr = 2;
+    // BO 20 and 28 branch always, without decrementing the count register.
+    exec(&mut cpu, "bc 20, 0, 0x20").unwrap();
+    exec(&mut cpu, "bc 28, 0, 0x20").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE + 0x40, 2));
+    // The hint bit of BO does not change the outcome.
+    cpu.set_cr_bit(2, true);
+    exec(&mut cpu, "bc 13, 2, 0x20").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x60);
+    exec(&mut cpu, "bc 5, 2, 0x20").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x64);
+    // Decrement, then branch if the count is non-zero and the condition is false.
+    exec(&mut cpu, "bc 0, 0, 0x20").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE + 0x84, 1));
+    // Decrement, then branch if the count is zero and the condition is true.
+    exec(&mut cpu, "bc 10, 2, 0x20").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE + 0xA4, 0));
+    exec(&mut cpu, "bc 18, 0, 0x20").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE + 0xA8, u32::MAX));
+
+    cpu.lr = BASE + 0x200;
+    cpu.ctr = 5;
+    exec(&mut cpu, "bclr 4, 6, 0").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x200);
+    exec(&mut cpu, "bclr 16, 0, 0").unwrap();
+    assert_eq!((cpu.pc, cpu.ctr), (BASE + 0x200, 4));
+    exec(&mut cpu, "bclr 12, 2, 0").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x200);
+    cpu.ctr = BASE + 0x300;
+    exec(&mut cpu, "bcctr 4, 2, 0").unwrap();
+    assert_eq!(cpu.pc, BASE + 0x204);
+    exec(&mut cpu, "bcctrl 20, 0, 0").unwrap();
+    assert_eq!(
+        (cpu.pc, cpu.lr, cpu.ctr),
+        (BASE + 0x300, BASE + 0x208, BASE + 0x300)
+    );
+}
+
+#[test]
+fn test_emu_cr() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.cr = 0xA000_0000;
+    exec(&mut cpu, "crand 4, 0, 2").unwrap();
+    assert_eq!(cpu.cr, 0xA800_0000);
+    exec(&mut cpu, "crnor 1, 0, 3").unwrap();
+    assert_eq!(cpu.cr, 0xA800_0000);
+    exec(&mut cpu, "crorc 3, 1, 1").unwrap();
+    assert_eq!(cpu.cr, 0xB800_0000);
+    exec(&mut cpu, "crxor 0, 0, 0").unwrap();
+    assert_eq!(cpu.cr, 0x3800_0000);
+    exec(&mut cpu, "creqv 5, 5, 5").unwrap();
+    assert_eq!(cpu.cr, 0x3C00_0000);
+    exec(&mut cpu, "crandc 0, 2, 6").unwrap();
+    assert_eq!(cpu.cr, 0xBC00_0000);
+    exec(&mut cpu, "crnand 2, 0, 2").unwrap();
+    assert_eq!(cpu.cr, 0x9C00_0000);
+    exec(&mut cpu, "crnand 6, 1, 2").unwrap();
+    assert_eq!(cpu.cr, 0x9E00_0000);
+    exec(&mut cpu, "cror 1, 1, 2").unwrap();
+    assert_eq!(cpu.cr, 0x9E00_0000);
+    exec(&mut cpu, "cror 1, 1, 4").unwrap();
+    assert_eq!(cpu.cr, 0xDE00_0000);
+    exec(&mut cpu, "crxor 0, 0, 0").unwrap();
+    exec(&mut cpu, "crxor 1, 1, 1").unwrap();
+    exec(&mut cpu, "crxor 6, 6, 6").unwrap();
+    exec(&mut cpu, "creqv 2, 2, 2").unwrap();
+    assert_eq!(cpu.cr, 0x3C00_0000);
+    exec(&mut cpu, "mcrf cr7, cr0").unwrap();
+    assert_eq!(cpu.cr, 0x3C00_0003);
+    cpu.xer = 0xE000_0005;
+    exec(&mut cpu, "mcrxr cr2").unwrap();
+    assert_eq!((cpu.cr, cpu.xer), (0x3CE0_0003, 5));
+    exec(&mut cpu, "mfcr r3").unwrap();
+    assert_eq!(cpu.gpr[3], 0x3CE0_0003);
+    cpu.gpr[4] = 0x1234_5678;
+    exec(&mut cpu, "mtcrf 0x81, r4").unwrap();
+    assert_eq!(cpu.cr, 0x1CE0_0008);
+}
+
+#[test]
+fn test_emu_system() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.gpr[3] = 0x1234;
+    exec(&mut cpu, "mtspr 912, r3").unwrap();
+    exec(&mut cpu, "mtctr r3").unwrap();
+    exec(&mut cpu, "mtxer r3").unwrap();
+    assert_eq!((cpu.spr[912], cpu.ctr, cpu.xer), (0x1234, 0x1234, 0x34));
+    exec(&mut cpu, "mfspr r4, 912").unwrap();
+    assert_eq!(cpu.gpr[4], 0x1234);
+    cpu.spr[284] = 7;
+    exec(&mut cpu, "mftb r5").unwrap();
+    assert_eq!(cpu.gpr[5], 7);
+    exec(&mut cpu, "mtsr 3, r3").unwrap();
+    cpu.gpr[6] = 0x3000_0000;
+    exec(&mut cpu, "mfsrin r7, r6").unwrap();
+    assert_eq!(cpu.gpr[7], 0x1234);
+}
+
+#[test]
+fn test_emu_trap() {
+    let mut cpu = Cpu::new(BASE);
+    cpu.gpr[3] = -1i32 as u32;
+    // Signed less than.
+    assert_eq!(exec(&mut cpu, "twi 16, r3, 0"), Err(Error::Trap(BASE)));
+    assert_eq!(cpu.pc, BASE);
+    // Unsigned less than.
+    exec(&mut cpu, "twi 2, r3, 0").unwrap();
+    cpu.pc = BASE;
+    assert_eq!(exec(&mut cpu, "tweq r3, r3"), Err(Error::Trap(BASE)));
+    assert_eq!(exec(&mut cpu, "sc"), Err(Error::SystemCall(BASE)));
+    let code = assemble("eciwx r3, r4, r5").unwrap();
+    assert_eq!(
+        exec(&mut cpu, "eciwx r3, r4, r5"),
+        Err(Error::Unimplemented(Ins::new(code, BASE)))
+    );
+    assert_eq!(
+        cpu.execute(Ins::new(0, BASE), &mut Ram::default()),
+        Err(Error::IllegalInstruction {
+            addr: BASE,
+            code: 0
+        })
+    );
+}
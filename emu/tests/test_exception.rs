@@ -0,0 +1,177 @@
+mod common;
+
+use ppc750cl_emu::{dsisr, fpscr, msr, srr1, Cpu, Error, Exception, Ram};
+
+use common::write_code;
+
+const CODE: u32 = 0x3000;
+
+/// Places instructions at `CODE` in physical memory holding the vectors and an empty page table.
+fn setup(text: &[&str], msr: u32) -> (Cpu, Ram) {
+    let mut ram = Ram::new(0, 0x1_0000);
+    write_code(&mut ram, CODE, text);
+    let mut cpu = Cpu::new(CODE);
+    cpu.msr = msr;
+    (cpu, ram)
+}
+
+/// Steps once, returning the exception taken.
+fn step(cpu: &mut Cpu, ram: &mut Ram) -> Option<Exception> {
+    cpu.step_with_exceptions(ram).unwrap()
+}
+
+#[test]
+fn test_exception_system_call() {
+    let user = msr::EE | msr::PR | msr::FP | msr::ME | msr::RI;
+    let (mut cpu, mut ram) = setup(&["sc"], user);
+    write_code(&mut ram, 0xC00, &["rfi"]);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::SystemCall));
+    assert_eq!((cpu.pc, cpu.srr0, cpu.srr1), (0xC00, CODE + 4, user));
+    // Interrupts are disabled, machine checks stay enabled.
+    assert_eq!(cpu.msr, msr::ME);
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert_eq!((cpu.pc, cpu.msr), (CODE + 4, user));
+
+    // The exception prefix and little-endian mode are kept.
+    let (mut cpu, mut ram) = setup(&["sc"], msr::IP | msr::ILE);
+    step(&mut cpu, &mut ram);
+    assert_eq!(cpu.pc, 0xFFF0_0C00);
+    assert_eq!(cpu.msr, msr::IP | msr::ILE | msr::LE);
+}
+
+#[test]
+fn test_exception_program() {
+    let (mut cpu, mut ram) = setup(&["twi 31, r0, 0", "tweqi r3, 0"], 0);
+    cpu.gpr[3] = 1;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Program));
+    assert_eq!((cpu.pc, cpu.srr0, cpu.srr1), (0x700, CODE, srr1::TRAP));
+    // Trap conditions that are not met continue.
+    cpu.pc = CODE + 4;
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert_eq!(cpu.pc, CODE + 8);
+
+    // Illegal instructions.
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Program));
+    assert_eq!((cpu.srr0, cpu.srr1), (CODE + 8, srr1::ILLEGAL));
+
+    // Privileged instructions in user mode, including privileged SPRs.
+    let text = ["mflr r3", "mfspr r3, 26", "mfmsr r3"];
+    let (mut cpu, mut ram) = setup(&text, msr::PR);
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Program));
+    assert_eq!((cpu.srr0, cpu.srr1), (CODE + 4, msr::PR | srr1::PRIVILEGED));
+    // The instruction raises an error when stepped without exceptions.
+    cpu.msr = msr::PR;
+    cpu.pc = CODE + 8;
+    assert_eq!(cpu.step(&mut ram), Err(Error::Privileged(CODE + 8)));
+}
+
+#[test]
+fn test_exception_float() {
+    let (mut cpu, mut ram) = setup(&["fdiv f1, f2, f3", "lfs f1, 0x0(r4)"], 0);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::FloatUnavailable));
+    assert_eq!((cpu.pc, cpu.srr0), (0x800, CODE));
+    cpu.pc = CODE + 4;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::FloatUnavailable));
+
+    // Enabled exceptions are taken once set.
+    let (mut cpu, mut ram) = setup(&["fdiv f1, f2, f3"], msr::FP | msr::FE0 | msr::FE1);
+    cpu.fpscr = fpscr::ZE;
+    cpu.fpr[2].ps0 = 1.0;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Program));
+    assert_eq!(cpu.srr0, CODE);
+    assert_eq!(cpu.srr1 & !srr1::MSR, srr1::FLOAT);
+}
+
+#[test]
+fn test_exception_alignment() {
+    let (mut cpu, mut ram) = setup(&["lmw r29, 0x2(r4)", "stwcx. r3, r4, r5"], 0);
+    cpu.gpr[4] = 0x1000;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Alignment));
+    assert_eq!((cpu.pc, cpu.srr0), (0x600, CODE));
+    // DSISR holds bits 5 and 1 to 4 of the opcode, rD and rA.
+    assert_eq!(cpu.read_spr(19), 0x1002);
+    assert_eq!(cpu.read_spr(18), 0b0111 << 10 | 29 << 5 | 4);
+    cpu.pc = CODE + 4;
+    cpu.gpr[5] = 1;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Alignment));
+    // Bits 29 and 30, 25 and 21 to 24 of the extended opcode.
+    assert_eq!(cpu.read_spr(18), 0b100_0010 << 10 | 3 << 5 | 4);
+}
+
+#[test]
+fn test_exception_storage() {
+    // Without BATs or a page table, every translation fails.
+    let (mut cpu, mut ram) = setup(&["stw r3, 0x0(r4)"], msr::DR);
+    cpu.gpr[4] = 0x1234;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Dsi));
+    assert_eq!((cpu.pc, cpu.srr0, cpu.msr), (0x300, CODE, 0));
+    assert_eq!(cpu.read_spr(19), 0x1234);
+    assert_eq!(cpu.read_spr(18), dsisr::PAGE_NOT_FOUND | dsisr::STORE);
+
+    let (mut cpu, mut ram) = setup(&["nop"], msr::IR);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Isi));
+    assert_eq!((cpu.pc, cpu.srr0), (0x400, CODE));
+    assert_eq!(cpu.srr1, msr::IR | srr1::PAGE_NOT_FOUND);
+
+    // Other errors are not exceptions.
+    let (mut cpu, mut ram) = setup(&["lwz r3, 0x0(r4)"], 0);
+    cpu.gpr[4] = 0x8000_0000;
+    assert_eq!(
+        cpu.step_with_exceptions(&mut ram),
+        Err(Error::Unmapped(0x8000_0000))
+    );
+}
+
+#[test]
+fn test_exception_interrupts() {
+    let (mut cpu, mut ram) = setup(&["nop", "nop", "mtdec r3", "nop"], msr::EE);
+    cpu.write_spr(22, 1);
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert!(!cpu.decrementer_pending);
+    // The decrementer passes zero.
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert!(cpu.decrementer_pending);
+    assert_eq!((cpu.read_spr(22), cpu.time_base()), (u32::MAX, 2));
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Decrementer));
+    assert_eq!((cpu.pc, cpu.srr0), (0x900, CODE + 8));
+    assert!(!cpu.decrementer_pending);
+
+    // Writing a negative value signals the exception, held while disabled.
+    cpu.write_spr(22, 5);
+    cpu.gpr[3] = 0x8000_0000;
+    cpu.pc = CODE + 8;
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert!(cpu.decrementer_pending);
+    cpu.msr = msr::EE;
+    // The external interrupt takes priority.
+    cpu.external_interrupt = true;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::External));
+    assert_eq!(cpu.srr0, CODE + 12);
+    cpu.external_interrupt = false;
+    cpu.msr = msr::EE;
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Decrementer));
+}
+
+#[test]
+fn test_exception_trace() {
+    let (mut cpu, mut ram) = setup(&["nop", "b 0x8"], msr::SE);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Trace));
+    assert_eq!((cpu.pc, cpu.srr0), (0xD00, CODE + 4));
+    // Branch tracing only follows branches.
+    let (mut cpu, mut ram) = setup(&["nop", "b 0x8"], msr::BE);
+    assert_eq!(step(&mut cpu, &mut ram), None);
+    assert_eq!(step(&mut cpu, &mut ram), Some(Exception::Trace));
+    assert_eq!(cpu.srr0, CODE + 12);
+}
+
+#[test]
+fn test_exception_rfi() {
+    let (mut cpu, mut ram) = setup(&["rfi"], 0);
+    cpu.srr0 = 0x8000_1237;
+    cpu.srr1 = u32::MAX;
+    cpu.step(&mut ram).unwrap();
+    assert_eq!(cpu.pc, 0x8000_1234);
+    // Only the saved bits are restored, without power management.
+    assert_eq!(cpu.msr, srr1::MSR & !msr::POW);
+}
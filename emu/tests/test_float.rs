@@ -0,0 +1,239 @@
+mod common;
+
+use ppc750cl_emu::fpscr::*;
+use ppc750cl_emu::{double_to_single, single_to_double, Cpu, FloatRegister, Ram};
+
+use common::{exec, BASE, POSITIVE};
+
+/// Executes an instruction with the given `ps0` values from f2 on, returning f1.
+fn run(text: &str, args: &[f64], fpscr: u32) -> (Cpu, f64) {
+    let mut cpu = Cpu::new(BASE);
+    for (reg, &value) in cpu.fpr[2..].iter_mut().zip(args) {
+        reg.ps0 = value;
+    }
+    cpu.fpscr = fpscr;
+    exec(&mut cpu, &mut Ram::default(), text).unwrap();
+    let f1 = cpu.fpr[1].ps0;
+    (cpu, f1)
+}
+
+#[test]
+fn test_emu_fadd() {
+    let (cpu, f1) = run("fadd f1, f2, f3", &[1.5, 2.25], 0);
+    assert_eq!((f1, cpu.fpscr), (3.75, POSITIVE));
+    // Double-precision results leave ps1 unchanged.
+    assert_eq!(cpu.fpr[1].ps1, 0.0);
+    let (cpu, f1) = run("fsub f1, f2, f3", &[1.5, 2.25], 0);
+    assert_eq!((f1, cpu.fpscr), (-0.75, 0x08 << 12));
+    let (cpu, f1) = run("fadd f1, f2, f3", &[1.0, f64::EPSILON / 4.0], 0);
+    assert_eq!((f1, cpu.fpscr), (1.0, FX | XX | FI | POSITIVE));
+    // Single-precision results are rounded and fill both halves.
+    let (cpu, f1) = run("fadds f1, f2, f3", &[1.0, 2f64.powi(-30)], 0);
+    assert_eq!(cpu.fpr[1], FloatRegister::splat(1.0));
+    assert_eq!((f1, cpu.fpscr), (1.0, FX | XX | FI | POSITIVE));
+    let (_, f1) = run("fadds f1, f2, f3", &[f32::MAX as f64, f32::MAX as f64], 0);
+    assert_eq!(f1, f64::INFINITY);
+}
+
+#[test]
+fn test_emu_fmul() {
+    assert_eq!(run("fmul f1, f2, f3", &[1.5, -4.0], 0).1, -6.0);
+    assert_eq!(run("fdiv f1, f2, f3", &[1.0, 4.0], 0).1, 0.25);
+    assert_eq!(run("fmadd f1, f2, f3, f4", &[2.0, 3.0, 5.0], 0).1, 11.0);
+    assert_eq!(run("fmsub f1, f2, f3, f4", &[2.0, 3.0, 5.0], 0).1, 1.0);
+    assert_eq!(run("fnmadd f1, f2, f3, f4", &[2.0, 3.0, 5.0], 0).1, -11.0);
+    assert_eq!(run("fnmsub f1, f2, f3, f4", &[2.0, 3.0, 5.0], 0).1, -1.0);
+    // The fraction of frC is rounded to 25 bits, making this product exact.
+    let (cpu, f1) = run("fmuls f1, f2, f3", &[1.0, 1.0 + 2f64.powi(-26)], 0);
+    assert_eq!((f1, cpu.fpscr), (1.0, POSITIVE));
+    let (cpu, f1) = run("fmul f1, f2, f3", &[1.0, 1.0 + 2f64.powi(-26)], 0);
+    assert_eq!((f1, cpu.fpscr), (1.0 + 2f64.powi(-26), POSITIVE));
+}
+
+#[test]
+fn test_emu_float_exceptions() {
+    let (cpu, f1) = run("fdiv f1, f2, f3", &[1.0, 0.0], 0);
+    assert_eq!((f1, cpu.fpscr), (f64::INFINITY, FX | ZX | 0x05 << 12));
+    // Enabled exceptions leave the target unchanged.
+    let (cpu, f1) = run("fdiv f1, f2, f3", &[1.0, 0.0], ZE);
+    assert_eq!((f1, cpu.fpscr), (0.0, FX | FEX | ZX | ZE));
+    let (cpu, f1) = run("fsub f1, f2, f3", &[f64::INFINITY, f64::INFINITY], 0);
+    assert_eq!(f1.to_bits(), 0x7FF8_0000_0000_0000);
+    assert_eq!(cpu.fpscr, FX | VX | VXISI | 0x11 << 12);
+    let (cpu, _) = run("fmul f1, f2, f3", &[f64::INFINITY, 0.0], 0);
+    assert_eq!(cpu.fpscr, FX | VX | VXIMZ | 0x11 << 12);
+    let (cpu, _) = run("fdiv f1, f2, f3", &[0.0, 0.0], 0);
+    assert_eq!(cpu.fpscr, FX | VX | VXZDZ | 0x11 << 12);
+    // Signaling NaNs are quieted, the first NaN operand is propagated.
+    let snan = f64::from_bits(0x7FF0_0000_0000_0001);
+    let (cpu, f1) = run("fadd f1, f2, f3", &[snan, f64::NAN], 0);
+    assert_eq!(f1.to_bits(), 0x7FF8_0000_0000_0001);
+    assert_eq!(cpu.fpscr, FX | VX | VXSNAN | 0x11 << 12);
+    let (_, f1) = run("fnmadd f1, f2, f3, f4", &[1.0, -f64::NAN, 1.0], 0);
+    assert_eq!(f1.to_bits(), (-f64::NAN).to_bits());
+    // FX is only set by new exceptions, FI is not sticky.
+    let (cpu, _) = run("fadd f1, f2, f3", &[1.0, 1.0], XX | FI);
+    assert_eq!(cpu.fpscr, XX | POSITIVE);
+    let (cpu, _) = run("fadds f1, f2, f3", &[f32::MAX as f64, f32::MAX as f64], 0);
+    assert_eq!(cpu.fpscr, FX | OX | XX | FI | 0x05 << 12);
+    let (cpu, _) = run("fmuls f1, f2, f3", &[1e-30, 1e-30], 0);
+    assert_eq!(cpu.fpscr, FX | UX | XX | FI | 0x02 << 12);
+    // Record forms copy the summaries to cr1.
+    let (cpu, _) = run("fdiv. f1, f2, f3", &[1.0, 0.0], 0);
+    assert_eq!(cpu.cr_field(1), 0b1000);
+}
+
+#[test]
+fn test_emu_frsp() {
+    let value = 1.0 + 2f64.powi(-30);
+    let up = 1.0 + f32::EPSILON as f64;
+    assert_eq!(run("frsp f1, f2", &[value], 0).1, 1.0);
+    assert_eq!(run("frsp f1, f2", &[value], 1).1, 1.0);
+    assert_eq!(run("frsp f1, f2", &[value], 2).1, up);
+    assert_eq!(run("frsp f1, f2", &[-value], 2).1, -1.0);
+    assert_eq!(run("frsp f1, f2", &[-value], 3).1, -up);
+    assert_eq!(run("frsp f1, f2", &[1e300], 1).1, f32::MAX as f64);
+    let (cpu, f1) = run("frsp f1, f2", &[1e-40], 0);
+    assert_eq!(f1, 1e-40f32 as f64);
+    assert_eq!(cpu.fpscr, FX | UX | XX | FI | 0x14 << 12);
+}
+
+#[test]
+fn test_emu_fctiw() {
+    let bits = |text, value, fpscr| run(text, &[value], fpscr).1.to_bits();
+    assert_eq!(bits("fctiwz f1, f2", 2.7, 0), 0xFFF8_0000_0000_0002);
+    assert_eq!(bits("fctiwz f1, f2", -2.7, 0), 0xFFF8_0000_FFFF_FFFE);
+    assert_eq!(bits("fctiw f1, f2", 2.5, 0), 0xFFF8_0000_0000_0002);
+    assert_eq!(bits("fctiw f1, f2", 2.5, 2), 0xFFF8_0000_0000_0003);
+    assert_eq!(bits("fctiwz f1, f2", -0.5, 0), 0xFFF8_0001_0000_0000);
+    assert_eq!(bits("fctiwz f1, f2", 1e10, 0), 0xFFF8_0000_7FFF_FFFF);
+    assert_eq!(bits("fctiwz f1, f2", -1e10, 0), 0xFFF8_0000_8000_0000);
+    let (cpu, f1) = run("fctiwz f1, f2", &[f64::NAN], 0);
+    assert_eq!(f1.to_bits(), 0xFFF8_0000_8000_0000);
+    assert_eq!(cpu.fpscr, FX | VX | VXCVI);
+    // stfiwx stores the low word.
+    let mut cpu = Cpu::new(BASE);
+    let mut ram = Ram::new(BASE, 0x10);
+    cpu.fpr[1].ps0 = f64::from_bits(0xFFF8_0000_1234_5678);
+    cpu.gpr[4] = BASE;
+    exec(&mut cpu, &mut ram, "stfiwx f1, r0, r4").unwrap();
+    assert_eq!(ram.data[..4], [0x12, 0x34, 0x56, 0x78]);
+}
+
+#[test]
+fn test_emu_fcmp() {
+    let (cpu, _) = run("fcmpu cr2, f2, f3", &[1.0, 2.0], 0);
+    assert_eq!((cpu.cr_field(2), cpu.fpscr), (0b1000, 0b1000 << 12));
+    let (cpu, _) = run("fcmpu cr2, f2, f3", &[2.0, 2.0], 0);
+    assert_eq!(cpu.cr_field(2), 0b0010);
+    let (cpu, _) = run("fcmpu cr2, f2, f3", &[f64::NAN, 2.0], 0);
+    assert_eq!((cpu.cr_field(2), cpu.fpscr), (0b0001, 0b0001 << 12));
+    // Ordered comparisons with a NaN are invalid.
+    let (cpu, _) = run("fcmpo cr2, f2, f3", &[f64::NAN, 2.0], 0);
+    assert_eq!(cpu.fpscr, FX | VX | VXVC | 0b0001 << 12);
+}
+
+#[test]
+fn test_emu_fmove() {
+    assert_eq!(run("fmr f1, f2", &[-2.0], 0).1, -2.0);
+    assert_eq!(run("fneg f1, f2", &[-2.0], 0).1, 2.0);
+    assert_eq!(run("fabs f1, f2", &[-2.0], 0).1, 2.0);
+    assert_eq!(run("fnabs f1, f2", &[2.0], 0).1, -2.0);
+    assert_eq!(run("fsel f1, f2, f3, f4", &[0.0, 1.0, 2.0], 0).1, 1.0);
+    assert_eq!(run("fsel f1, f2, f3, f4", &[-0.0, 1.0, 2.0], 0).1, 1.0);
+    assert_eq!(run("fsel f1, f2, f3, f4", &[-1.0, 1.0, 2.0], 0).1, 2.0);
+    assert_eq!(run("fsel f1, f2, f3, f4", &[f64::NAN, 1.0, 2.0], 0).1, 2.0);
+    // Moves do not change FPSCR.
+    let (cpu, _) = run("fmr. f1, f2", &[1.0], FX | OX);
+    assert_eq!((cpu.fpscr, cpu.cr_field(1)), (FX | OX, 0b1001));
+}
+
+#[test]
+fn test_emu_estimate() {
+    let (cpu, f1) = run("fres f1, f2", &[2.0], 0);
+    assert_eq!(f1, 0.5 - 2f64.powi(-14));
+    assert_eq!(cpu.fpr[1].ps1, f1);
+    assert_eq!(run("fres f1, f2", &[-0.0], 0).1, f64::NEG_INFINITY);
+    assert_eq!(run("fres f1, f2", &[1e-300], 0).1, f32::MAX as f64);
+    assert_eq!(run("fres f1, f2", &[1e300], 0).1, 0.0);
+    assert_eq!(
+        run("frsqrte f1, f2", &[1.0], 0).1,
+        1.0 - 3.0 * 2f64.powi(-14)
+    );
+    assert_eq!(
+        run("frsqrte f1, f2", &[4.0], 0).1,
+        0.5 - 1.5 * 2f64.powi(-14)
+    );
+    let f1 = run("frsqrte f1, f2", &[2.0], 0).1;
+    assert!((f1 - core::f64::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+    assert_eq!(run("frsqrte f1, f2", &[f64::INFINITY], 0).1, 0.0);
+    let (cpu, f1) = run("frsqrte f1, f2", &[-1.0], 0);
+    assert!(f1.is_nan());
+    assert_eq!(cpu.fpscr, FX | VX | VXSQRT | 0x11 << 12);
+    let (cpu, _) = run("frsqrte f1, f2", &[0.0], 0);
+    assert_eq!(cpu.fpscr, FX | ZX | 0x05 << 12);
+}
+
+#[test]
+fn test_emu_fpscr() {
+    let (_, f1) = run("mffs f1", &[], FX | OX | 0x04 << 12);
+    assert_eq!(
+        f1.to_bits(),
+        0xFFF8_0000_0000_0000 | (FX | OX | 0x04 << 12) as u64
+    );
+    // Summary bits are computed, not written.
+    let (cpu, _) = run("mtfsf 255, f2", &[f64::from_bits(0xFFFF_FFFF)], 0);
+    assert_eq!(cpu.fpscr, 0xFFFF_FFFF);
+    let (cpu, _) = run("mtfsf 1, f2", &[f64::from_bits(0xFFFF_FFFF)], 0);
+    assert_eq!(cpu.fpscr, 0xF);
+    let (cpu, _) = run("mtfsfi cr7, 3", &[], 0);
+    assert_eq!(cpu.fpscr, 3);
+    let (cpu, _) = run("mtfsb1 4", &[], 0);
+    assert_eq!(cpu.fpscr, FX | UX);
+    let (cpu, _) = run("mtfsb0 4", &[], FX | UX);
+    assert_eq!(cpu.fpscr, FX);
+    // Copied exception bits are cleared.
+    let (cpu, _) = run("mcrfs cr2, cr0", &[], FX | OX | XX);
+    assert_eq!((cpu.cr_field(2), cpu.fpscr), (0b1001, XX));
+}
+
+#[test]
+fn test_emu_float_load_store() {
+    let mut cpu = Cpu::new(BASE);
+    let mut ram = Ram::new(BASE, 0x10);
+    cpu.gpr[4] = BASE;
+    cpu.fpr[1].ps0 = 1.5;
+    exec(&mut cpu, &mut ram, "stfd f1, 0x8(r4)").unwrap();
+    exec(&mut cpu, &mut ram, "stfs f1, 0x0(r4)").unwrap();
+    exec(&mut cpu, &mut ram, "lfs f2, 0x0(r4)").unwrap();
+    assert_eq!(cpu.fpr[2], FloatRegister::splat(1.5));
+    exec(&mut cpu, &mut ram, "lfdu f3, 0x8(r4)").unwrap();
+    assert_eq!((cpu.fpr[3].ps0, cpu.gpr[4]), (1.5, BASE + 8));
+}
+
+#[test]
+fn test_emu_float_conversion() {
+    for word in [
+        0x3FC0_0000,
+        0x8000_0000,
+        0x0000_0001,
+        0x807F_FFFF,
+        0x7F80_0000,
+    ] {
+        let value = single_to_double(word);
+        assert_eq!(value, f32::from_bits(word) as f64);
+        assert_eq!(double_to_single(value), word);
+    }
+    // NaN payloads are kept, signaling NaNs stay signaling.
+    assert_eq!(
+        single_to_double(0x7F80_0001).to_bits(),
+        0x7FF0_0000_2000_0000
+    );
+    assert_eq!(
+        double_to_single(f64::from_bits(0x7FF0_0000_2000_0000)),
+        0x7F80_0001
+    );
+    // Extra fraction bits are truncated rather than rounded.
+    let value = 1.0 + 2f64.powi(-23) + 2f64.powi(-24);
+    assert_eq!(double_to_single(value), 0x3F80_0001);
+}
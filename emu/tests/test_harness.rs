@@ -0,0 +1,137 @@
+use std::cell::Cell;
+
+use dol::{Dol, DolHeader, DolSection, DolSectionType};
+use ppc750cl::asm::assemble;
+use ppc750cl_emu::{Arg, Error, Harness, Memory, MemoryWrite};
+
+const TEXT: u32 = 0x8000_3000;
+const DATA: u32 = 0x8000_4000;
+
+/// Builds an executable with the given functions at 0x100 byte intervals and a data section.
+fn build(functions: &[&[&str]]) -> Dol {
+    let mut memory = vec![0; 0x1010];
+    for (i, function) in functions.iter().enumerate() {
+        for (j, text) in function.iter().enumerate() {
+            let offset = 0x100 * i + 4 * j;
+            let code = assemble(text).unwrap();
+            memory[offset..offset + 4].copy_from_slice(&code.to_be_bytes());
+        }
+    }
+    let section = |kind, index, target, size| DolSection {
+        kind,
+        index,
+        offset: 0,
+        target,
+        size,
+    };
+    Dol {
+        header: DolHeader {
+            sections: vec![
+                section(DolSectionType::Text, 0, TEXT, 0x1000),
+                section(DolSectionType::Data, 7, DATA, 0x10),
+            ],
+            entry_point: TEXT,
+        },
+        memory,
+        memory_offset: TEXT,
+    }
+}
+
+#[test]
+fn test_harness_call() {
+    let dol = build(&[&["add r3, r3, r4", "blr"]]);
+    let mut harness = Harness::new(&dol);
+    let call = harness.call(TEXT, &[2.into(), 3.into()]).unwrap();
+    assert_eq!(call.ret(), 5);
+    assert_eq!(call.trace.len(), 2);
+    assert_eq!(call.trace[1].addr, TEXT + 4);
+    assert!(call.writes().is_empty());
+}
+
+#[test]
+fn test_harness_stub() {
+    // Stores its first argument through the second, returning g(a) + 1.
+    let dol = build(&[
+        &[
+            "stwu r1, -0x10(r1)",
+            "mflr r0",
+            "stw r0, 0x14(r1)",
+            "stw r3, 0x0(r4)",
+            "bl 0xF0",
+            "addi r3, r3, 0x1",
+            "lwz r0, 0x14(r1)",
+            "mtlr r0",
+            "addi r1, r1, 0x10",
+            "blr",
+        ],
+        &["trap"],
+    ]);
+    let calls = Cell::new(0);
+    let mut harness = Harness::new(&dol).stub(TEXT + 0x100, |cpu, _| {
+        calls.set(calls.get() + 1);
+        cpu.gpr[3] *= 2;
+        Ok(())
+    });
+    let mut call = harness.call(TEXT, &[Arg::Int(21), Arg::Int(DATA)]).unwrap();
+    assert_eq!(call.ret(), 43);
+    assert_eq!(calls.get(), 1);
+    // The stub is not traced.
+    assert_eq!(call.trace.len(), 10);
+    let writes: Vec<_> = call.writes_outside_stack().cloned().collect();
+    assert_eq!(
+        writes,
+        [MemoryWrite {
+            addr: DATA,
+            data: vec![0, 0, 0, 21]
+        }]
+    );
+    assert_eq!(call.writes().len(), 3);
+    assert_eq!(call.memory.read_u32(DATA).unwrap(), 21);
+    // The executable is not modified, each call starts afresh.
+    assert_eq!(dol.memory[0x1000..0x1004], [0; 4]);
+    let call = harness.call(TEXT, &[Arg::Int(1), Arg::Int(DATA)]).unwrap();
+    assert_eq!(call.ret(), 3);
+}
+
+#[test]
+fn test_harness_args() {
+    let dol = build(&[&["blr"]]);
+    let mut harness = Harness::new(&dol);
+    let call = harness
+        .call(
+            TEXT,
+            &[1.into(), 0x1122_3344_5566_7788u64.into(), 1.5.into()],
+        )
+        .unwrap();
+    // Doublewords are passed in an odd-even register pair.
+    assert_eq!(call.cpu.gpr[3..7], [1, 0, 0x1122_3344, 0x5566_7788]);
+    assert_eq!(call.ret_float(), 1.5);
+
+    // Arguments past the registers are passed on the stack.
+    let mut args: Vec<Arg> = (0..9).map(Arg::Int).collect();
+    args.extend((0..9).map(|i| Arg::Float(i as f64)));
+    let mut call = harness.call(TEXT, &args).unwrap();
+    assert_eq!(call.cpu.gpr[3..11], [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(call.cpu.fpr[8].ps0, 7.0);
+    let sp = call.cpu.gpr[1];
+    assert_eq!(sp % 8, 0);
+    assert_eq!(call.memory.read_u32(sp).unwrap(), 0);
+    assert_eq!(call.memory.read_u32(sp + 8).unwrap(), 8);
+    assert_eq!(call.memory.read_u64(sp + 16).unwrap(), 8f64.to_bits());
+}
+
+#[test]
+fn test_harness_errors() {
+    let dol = build(&[&["b 0x0"], &["stw r3, 0x0(r4)", "blr"]]);
+    let mut harness = Harness::new(&dol).max_steps(100);
+    assert_eq!(harness.call(TEXT, &[]).err(), Some(Error::StepLimit(TEXT)));
+    let result = harness.call(TEXT + 0x100, &[Arg::Int(0), Arg::Int(0x8000_0000)]);
+    assert_eq!(result.err(), Some(Error::Unmapped(0x8000_0000)));
+}
+
+#[test]
+#[should_panic(expected = "does not fit")]
+fn test_harness_stack_underflow() {
+    let dol = build(&[]);
+    let _ = Harness::new(&dol).stack(0x1000, 0x2000);
+}
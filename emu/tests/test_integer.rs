@@ -0,0 +1,204 @@
+mod common;
+
+use ppc750cl_emu::{Cpu, Ram};
+
+use common::exec;
+
+const SO: u32 = 0x8000_0000;
+const OV: u32 = 0x4000_0000;
+const CA: u32 = 0x2000_0000;
+
+/// Executes an instruction with the given GPR values from r3 on, returning r3 and XER.
+fn run(text: &str, args: &[u32], xer: u32) -> (Cpu, u32) {
+    let mut cpu = Cpu::new(0x8000_0000);
+    cpu.gpr[3..3 + args.len()].copy_from_slice(args);
+    cpu.xer = xer;
+    exec(&mut cpu, &mut Ram::default(), text).unwrap();
+    let r3 = cpu.gpr[3];
+    (cpu, r3)
+}
+
+#[test]
+fn test_emu_add() {
+    assert_eq!(run("add r3, r4, r5", &[0, 1, 2], 0).1, 3);
+    // The carry is only written by carrying forms.
+    let (cpu, r3) = run("add r3, r4, r5", &[0, u32::MAX, 1], 0);
+    assert_eq!((r3, cpu.xer), (0, 0));
+    let (cpu, r3) = run("addc r3, r4, r5", &[0, u32::MAX, 1], 0);
+    assert_eq!((r3, cpu.xer), (0, CA));
+    let (cpu, r3) = run("adde r3, r4, r5", &[0, 1, 2], CA);
+    assert_eq!((r3, cpu.xer), (4, 0));
+    let (cpu, r3) = run("addme r3, r4", &[0, 0], CA);
+    assert_eq!((r3, cpu.xer), (0, CA));
+    let (cpu, r3) = run("addze r3, r4", &[0, u32::MAX], CA);
+    assert_eq!((r3, cpu.xer), (0, CA));
+    assert_eq!(run("addi r3, r0, -1", &[], 0).1, u32::MAX);
+    assert_eq!(run("addi r3, r4, -1", &[0, 5], 0).1, 4);
+    assert_eq!(run("addis r3, r4, 0x8000", &[0, 1], 0).1, 0x8000_0001);
+    let (cpu, r3) = run("addic r3, r4, -1", &[0, 1], 0);
+    assert_eq!((r3, cpu.xer), (0, CA));
+    let (cpu, _) = run("addic. r3, r4, -1", &[0, 1], 0);
+    assert_eq!(cpu.cr_field(0), 0b0010);
+}
+
+#[test]
+fn test_emu_overflow() {
+    let (cpu, r3) = run("addo r3, r4, r5", &[0, 0x7FFF_FFFF, 1], 0);
+    assert_eq!((r3, cpu.xer), (0x8000_0000, SO | OV));
+    // The overflow is cleared, the summary overflow is sticky.
+    let (cpu, _) = run("addo r3, r4, r5", &[0, 1, 1], SO | OV);
+    assert_eq!(cpu.xer, SO);
+    // Without OE, the overflow is ignored.
+    let (cpu, _) = run("add r3, r4, r5", &[0, 0x7FFF_FFFF, 1], 0);
+    assert_eq!(cpu.xer, 0);
+    // The summary overflow is copied to cr0.
+    let (cpu, _) = run("addo. r3, r4, r5", &[0, 0x7FFF_FFFF, 1], 0);
+    assert_eq!(cpu.cr_field(0), 0b1001);
+    let (cpu, r3) = run("nego r3, r4", &[0, 0x8000_0000], 0);
+    assert_eq!((r3, cpu.xer), (0x8000_0000, SO | OV));
+}
+
+#[test]
+fn test_emu_subtract() {
+    assert_eq!(run("subf r3, r4, r5", &[0, 3, 10], 0).1, 7);
+    // The carry is set if no borrow occurs.
+    let (cpu, r3) = run("subfc r3, r4, r5", &[0, 3, 10], 0);
+    assert_eq!((r3, cpu.xer), (7, CA));
+    let (cpu, r3) = run("subfc r3, r4, r5", &[0, 10, 3], 0);
+    assert_eq!((r3, cpu.xer), (-7i32 as u32, 0));
+    let (cpu, r3) = run("subfe r3, r4, r5", &[0, 3, 10], 0);
+    assert_eq!((r3, cpu.xer), (6, CA));
+    let (cpu, r3) = run("subfme r3, r4", &[0, 0], 0);
+    assert_eq!((r3, cpu.xer), (-2i32 as u32, CA));
+    let (cpu, r3) = run("subfze r3, r4", &[0, 0], CA);
+    assert_eq!((r3, cpu.xer), (0, CA));
+    let (cpu, r3) = run("subfic r3, r4, 5", &[0, 7], 0);
+    assert_eq!((r3, cpu.xer), (-2i32 as u32, 0));
+    assert_eq!(run("neg r3, r4", &[0, 5], 0).1, -5i32 as u32);
+}
+
+#[test]
+fn test_emu_multiply_divide() {
+    assert_eq!(run("mulli r3, r4, -3", &[0, 7], 0).1, -21i32 as u32);
+    assert_eq!(
+        run("mullw r3, r4, r5", &[0, 0x10000, 0x10001], 0).1,
+        0x10000
+    );
+    let (cpu, _) = run("mullwo r3, r4, r5", &[0, 0x10000, 0x10000], 0);
+    assert_eq!(cpu.xer, SO | OV);
+    assert_eq!(
+        run("mulhw r3, r4, r5", &[0, -2i32 as u32, 0x8000_0000], 0).1,
+        1
+    );
+    assert_eq!(run("mulhwu r3, r4, r5", &[0, u32::MAX, 2], 0).1, 1);
+    assert_eq!(
+        run("divw r3, r4, r5", &[0, -7i32 as u32, 2], 0).1,
+        -3i32 as u32
+    );
+    assert_eq!(
+        run("divwu r3, r4, r5", &[0, -7i32 as u32, 2], 0).1,
+        0x7FFF_FFFC
+    );
+    // Division by zero and overflow.
+    let (cpu, r3) = run("divwo r3, r4, r5", &[0, -7i32 as u32, 0], 0);
+    assert_eq!((r3, cpu.xer), (u32::MAX, SO | OV));
+    let (cpu, r3) = run("divwo r3, r4, r5", &[0, 0x8000_0000, u32::MAX], 0);
+    assert_eq!((r3, cpu.xer), (u32::MAX, SO | OV));
+    assert_eq!(run("divw r3, r4, r5", &[0, 7, 0], 0).1, 0);
+    let (cpu, r3) = run("divwuo r3, r4, r5", &[0, 7, 0], 0);
+    assert_eq!((r3, cpu.xer), (0, SO | OV));
+}
+
+#[test]
+fn test_emu_compare() {
+    let (cpu, _) = run("cmpw cr1, r4, r5", &[0, -1i32 as u32, 1], 0);
+    assert_eq!(cpu.cr_field(1), 0b1000);
+    let (cpu, _) = run("cmplw cr1, r4, r5", &[0, -1i32 as u32, 1], 0);
+    assert_eq!(cpu.cr_field(1), 0b0100);
+    let (cpu, _) = run("cmpwi r4, -1", &[0, -1i32 as u32], SO);
+    assert_eq!(cpu.cr_field(0), 0b0011);
+    let (cpu, _) = run("cmplwi cr7, r4, 0xFFFF", &[0, 0x10000], 0);
+    assert_eq!(cpu.cr_field(7), 0b0100);
+
+    // Basic forms with an explicit L operand.
+    let (cpu, _) = run("cmp cr2, 0, r4, r5", &[0, 1, 2], 0);
+    assert_eq!(cpu.cr_field(2), 0b1000);
+    let (cpu, _) = run("cmpl cr3, 0, r4, r5", &[0, u32::MAX, 1], 0);
+    assert_eq!(cpu.cr_field(3), 0b0100);
+    let (cpu, _) = run("cmpi cr4, 0, r4, -5", &[0, -5i32 as u32], SO);
+    assert_eq!(cpu.cr_field(4), 0b0011);
+    let (cpu, _) = run("cmpli cr5, 0, r4, 0x8000", &[0, 0x7FFF], 0);
+    assert_eq!(cpu.cr_field(5), 0b1000);
+    // The immediate of cmpli is not sign-extended.
+    let (cpu, _) = run("cmpli cr0, 0, r4, 0x8000", &[0, 0xFFFF_8000], 0);
+    assert_eq!(cpu.cr_field(0), 0b0100);
+}
+
+#[test]
+fn test_emu_logical() {
+    let args = [0, 0b1100, 0b1010];
+    assert_eq!(run("and r3, r4, r5", &args, 0).1, 0b1000);
+    assert_eq!(run("andc r3, r4, r5", &args, 0).1, 0b0100);
+    assert_eq!(run("or r3, r4, r5", &args, 0).1, 0b1110);
+    assert_eq!(run("orc r3, r4, r5", &args, 0).1, !0b0010);
+    assert_eq!(run("xor r3, r4, r5", &args, 0).1, 0b0110);
+    assert_eq!(run("nand r3, r4, r5", &args, 0).1, !0b1000);
+    assert_eq!(run("nor r3, r4, r5", &args, 0).1, !0b1110);
+    assert_eq!(run("eqv r3, r4, r5", &args, 0).1, !0b0110);
+    assert_eq!(run("ori r3, r4, 0x8000", &args, 0).1, 0x800C);
+    assert_eq!(run("oris r3, r4, 0x8000", &args, 0).1, 0x8000_000C);
+    assert_eq!(run("xori r3, r4, 0xF", &args, 0).1, 0b0011);
+    assert_eq!(run("xoris r3, r4, 1", &args, 0).1, 0x1_000C);
+    // andi. and andis. always record.
+    let (cpu, r3) = run("andi. r3, r4, 0x3", &args, 0);
+    assert_eq!((r3, cpu.cr_field(0)), (0, 0b0010));
+    let (cpu, r3) = run("andis. r3, r4, 0x8000", &[0, 0x8000_0000], 0);
+    assert_eq!((r3, cpu.cr_field(0)), (0x8000_0000, 0b1000));
+    let (cpu, _) = run("or. r3, r4, r5", &args, 0);
+    assert_eq!(cpu.cr_field(0), 0b0100);
+    assert_eq!(run("cntlzw r3, r4", &[0, 0x0001_0000], 0).1, 15);
+    assert_eq!(run("cntlzw r3, r4", &[0, 0], 0).1, 32);
+    assert_eq!(run("extsb r3, r4", &[0, 0x180], 0).1, 0xFFFF_FF80);
+    assert_eq!(run("extsh r3, r4", &[0, 0x1_8000], 0).1, 0xFFFF_8000);
+}
+
+#[test]
+fn test_emu_rotate() {
+    assert_eq!(
+        run("rlwinm r3, r4, 8, 24, 31", &[0, 0x1234_5678], 0).1,
+        0x12
+    );
+    // The mask wraps around if MB > ME.
+    assert_eq!(
+        run("rlwinm r3, r4, 0, 28, 3", &[0, 0x1234_5678], 0).1,
+        0x1000_0008
+    );
+    assert_eq!(
+        run("rlwnm r3, r4, r5, 0, 31", &[0, 0x1234_5678, 36], 0).1,
+        0x2345_6781
+    );
+    assert_eq!(
+        run("rlwimi r3, r4, 16, 0, 15", &[0xAAAA_AAAA, 0x1234_5678], 0).1,
+        0x5678_AAAA
+    );
+}
+
+#[test]
+fn test_emu_shift() {
+    assert_eq!(run("slw r3, r4, r5", &[0, 1, 31], 0).1, 0x8000_0000);
+    assert_eq!(run("slw r3, r4, r5", &[0, 1, 32], 0).1, 0);
+    assert_eq!(run("slw r3, r4, r5", &[0, 1, 64], 0).1, 1);
+    assert_eq!(run("srw r3, r4, r5", &[0, 0x8000_0000, 31], 0).1, 1);
+    assert_eq!(run("srw r3, r4, r5", &[0, 0x8000_0000, 63], 0).1, 0);
+    // The carry is set if a negative value loses one bits.
+    let (cpu, r3) = run("srawi r3, r4, 1", &[0, -3i32 as u32], 0);
+    assert_eq!((r3, cpu.xer), (-2i32 as u32, CA));
+    let (cpu, r3) = run("srawi r3, r4, 1", &[0, -4i32 as u32], CA);
+    assert_eq!((r3, cpu.xer), (-2i32 as u32, 0));
+    let (cpu, r3) = run("srawi r3, r4, 1", &[0, 3], 0);
+    assert_eq!((r3, cpu.xer), (1, 0));
+    let (cpu, r3) = run("sraw r3, r4, r5", &[0, -4i32 as u32, 40], 0);
+    assert_eq!((r3, cpu.xer), (u32::MAX, CA));
+    let (cpu, r3) = run("sraw r3, r4, r5", &[0, 4, 40], 0);
+    assert_eq!((r3, cpu.xer), (0, 0));
+}
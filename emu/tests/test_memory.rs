@@ -0,0 +1,201 @@
+mod common;
+
+use ppc750cl_emu::{Cpu, Error, Memory, Ram};
+
+use common::{exec, BASE};
+
+/// Fills RAM with the low byte of each offset.
+fn setup() -> (Cpu, Ram) {
+    let (cpu, mut ram) = common::setup(0x100);
+    for (i, byte) in ram.data.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    (cpu, ram)
+}
+
+#[test]
+fn test_emu_load() {
+    let (mut cpu, mut ram) = setup();
+    exec(&mut cpu, &mut ram, "lwz r3, 0x4(r4)").unwrap();
+    assert_eq!(cpu.gpr[3], 0x1415_1617);
+    exec(&mut cpu, &mut ram, "lbz r3, -0x1(r4)").unwrap();
+    assert_eq!(cpu.gpr[3], 0x0F);
+    exec(&mut cpu, &mut ram, "lhz r3, 0x70(r4)").unwrap();
+    assert_eq!(cpu.gpr[3], 0x8081);
+    exec(&mut cpu, &mut ram, "lha r3, 0x70(r4)").unwrap();
+    assert_eq!(cpu.gpr[3], 0xFFFF_8081);
+    exec(&mut cpu, &mut ram, "lhbrx r3, r4, r0").unwrap();
+    assert_eq!(cpu.gpr[3], 0x1110);
+    exec(&mut cpu, &mut ram, "lwbrx r3, r4, r0").unwrap();
+    assert_eq!(cpu.gpr[3], 0x1312_1110);
+    // rA|0 reads as zero.
+    cpu.gpr[5] = BASE + 0x20;
+    exec(&mut cpu, &mut ram, "lwzx r3, r0, r5").unwrap();
+    assert_eq!(cpu.gpr[3], 0x2021_2223);
+}
+
+#[test]
+fn test_emu_load_indexed() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[5] = 0x70;
+    exec(&mut cpu, &mut ram, "lbzx r3, r4, r5").unwrap();
+    assert_eq!(cpu.gpr[3], 0x80);
+    exec(&mut cpu, &mut ram, "lhzx r3, r4, r5").unwrap();
+    assert_eq!(cpu.gpr[3], 0x8081);
+    exec(&mut cpu, &mut ram, "lhax r3, r4, r5").unwrap();
+    assert_eq!(cpu.gpr[3], 0xFFFF_8081);
+    assert_eq!(cpu.gpr[4], BASE + 0x10);
+}
+
+#[test]
+fn test_emu_update() {
+    let (mut cpu, mut ram) = setup();
+    exec(&mut cpu, &mut ram, "lwzu r3, 0x8(r4)").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x1819_1A1B, BASE + 0x18));
+    cpu.gpr[5] = -8i32 as u32;
+    exec(&mut cpu, &mut ram, "stbux r3, r4, r5").unwrap();
+    assert_eq!((ram.data[0x10], cpu.gpr[4]), (0x1B, BASE + 0x10));
+}
+
+#[test]
+fn test_emu_load_update() {
+    let (mut cpu, mut ram) = setup();
+    exec(&mut cpu, &mut ram, "lbzu r3, 0x1(r4)").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x11, BASE + 0x11));
+    exec(&mut cpu, &mut ram, "lhzu r3, 0x1(r4)").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x1213, BASE + 0x12));
+    exec(&mut cpu, &mut ram, "lhau r3, 0x6e(r4)").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0xFFFF_8081, BASE + 0x80));
+
+    cpu.gpr[5] = -0x70i32 as u32;
+    exec(&mut cpu, &mut ram, "lbzux r3, r4, r5").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x10, BASE + 0x10));
+    cpu.gpr[5] = 0x2;
+    exec(&mut cpu, &mut ram, "lhzux r3, r4, r5").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x1213, BASE + 0x12));
+    cpu.gpr[5] = 0x6E;
+    exec(&mut cpu, &mut ram, "lhaux r3, r4, r5").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0xFFFF_8081, BASE + 0x80));
+    cpu.gpr[5] = -0x10i32 as u32;
+    exec(&mut cpu, &mut ram, "lwzux r3, r4, r5").unwrap();
+    assert_eq!((cpu.gpr[3], cpu.gpr[4]), (0x7071_7273, BASE + 0x70));
+}
+
+#[test]
+fn test_emu_store_update() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[3] = 0xAABB_CCDD;
+    exec(&mut cpu, &mut ram, "stbu r3, 0x1(r4)").unwrap();
+    assert_eq!((ram.data[0x11], cpu.gpr[4]), (0xDD, BASE + 0x11));
+    exec(&mut cpu, &mut ram, "sthu r3, 0x3(r4)").unwrap();
+    assert_eq!(ram.data[0x14..0x16], [0xCC, 0xDD]);
+    assert_eq!(cpu.gpr[4], BASE + 0x14);
+    cpu.gpr[5] = 0x4;
+    exec(&mut cpu, &mut ram, "sthux r3, r4, r5").unwrap();
+    assert_eq!(ram.data[0x18..0x1A], [0xCC, 0xDD]);
+    assert_eq!(cpu.gpr[4], BASE + 0x18);
+    exec(&mut cpu, &mut ram, "stwux r3, r4, r5").unwrap();
+    assert_eq!(ram.data[0x1C..0x20], [0xAA, 0xBB, 0xCC, 0xDD]);
+    assert_eq!(cpu.gpr[4], BASE + 0x1C);
+    // Neighbouring bytes are kept.
+    assert_eq!(
+        (ram.data[0x10], ram.data[0x16], ram.data[0x20]),
+        (0x10, 0x16, 0x20)
+    );
+}
+
+#[test]
+fn test_emu_store() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[3] = 0xAABB_CCDD;
+    exec(&mut cpu, &mut ram, "stw r3, 0x0(r4)").unwrap();
+    assert_eq!(ram.data[0x10..0x14], [0xAA, 0xBB, 0xCC, 0xDD]);
+    exec(&mut cpu, &mut ram, "sth r3, 0x4(r4)").unwrap();
+    assert_eq!(ram.data[0x14..0x16], [0xCC, 0xDD]);
+    exec(&mut cpu, &mut ram, "stb r3, 0x6(r4)").unwrap();
+    assert_eq!(ram.data[0x16], 0xDD);
+    exec(&mut cpu, &mut ram, "sthbrx r3, r4, r0").unwrap();
+    assert_eq!(ram.data[0x10..0x12], [0xDD, 0xCC]);
+    exec(&mut cpu, &mut ram, "stwbrx r3, r4, r0").unwrap();
+    assert_eq!(ram.data[0x10..0x14], [0xDD, 0xCC, 0xBB, 0xAA]);
+    cpu.gpr[5] = 0x20;
+    exec(&mut cpu, &mut ram, "stbx r3, r4, r5").unwrap();
+    assert_eq!(ram.data[0x30..0x32], [0xDD, 0x31]);
+    exec(&mut cpu, &mut ram, "sthx r3, r4, r5").unwrap();
+    assert_eq!(ram.data[0x30..0x33], [0xCC, 0xDD, 0x32]);
+    exec(&mut cpu, &mut ram, "stwx r3, r4, r5").unwrap();
+    assert_eq!(ram.data[0x30..0x35], [0xAA, 0xBB, 0xCC, 0xDD, 0x34]);
+    assert_eq!(cpu.gpr[4], BASE + 0x10);
+}
+
+#[test]
+fn test_emu_multiple() {
+    let (mut cpu, mut ram) = setup();
+    exec(&mut cpu, &mut ram, "lmw r29, 0x0(r4)").unwrap();
+    assert_eq!(cpu.gpr[29..], [0x1011_1213, 0x1415_1617, 0x1819_1A1B]);
+    exec(&mut cpu, &mut ram, "stmw r29, 0x40(r4)").unwrap();
+    assert_eq!(ram.data[0x50..0x5C], ram.data[0x10..0x1C].to_vec());
+}
+
+#[test]
+fn test_emu_string() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[6] = u32::MAX;
+    exec(&mut cpu, &mut ram, "lswi r5, r4, 6").unwrap();
+    assert_eq!(cpu.gpr[5..7], [0x1011_1213, 0x1415_0000]);
+    // The length of indexed forms is held by XER.
+    cpu.xer = 3;
+    cpu.gpr[7] = 0x40;
+    exec(&mut cpu, &mut ram, "stswx r5, r4, r7").unwrap();
+    assert_eq!(ram.data[0x50..0x54], [0x10, 0x11, 0x12, 0x53]);
+
+    // Loads pad the last register with zeros and wrap around after r31.
+    cpu.xer = 6;
+    cpu.gpr[7] = 0x8;
+    exec(&mut cpu, &mut ram, "lswx r31, r4, r7").unwrap();
+    assert_eq!((cpu.gpr[31], cpu.gpr[0]), (0x1819_1A1B, 0x1C1D_0000));
+    // No registers are written if XER[BC] is zero.
+    cpu.xer = 0;
+    exec(&mut cpu, &mut ram, "lswx r5, r4, r7").unwrap();
+    assert_eq!(cpu.gpr[5], 0x1011_1213);
+
+    cpu.gpr[5] = 0x1122_3344;
+    cpu.gpr[6] = 0x5566_7788;
+    exec(&mut cpu, &mut ram, "stswi r5, r4, 5").unwrap();
+    assert_eq!(ram.data[0x10..0x16], [0x11, 0x22, 0x33, 0x44, 0x55, 0x15]);
+}
+
+#[test]
+fn test_emu_reservation() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[3] = 0xAABB_CCDD;
+    exec(&mut cpu, &mut ram, "stwcx. r3, r0, r4").unwrap();
+    assert_eq!((cpu.cr_field(0), ram.data[0x10]), (0b0000, 0x10));
+    exec(&mut cpu, &mut ram, "lwarx r5, r0, r4").unwrap();
+    assert_eq!(cpu.reservation, Some(BASE + 0x10));
+    exec(&mut cpu, &mut ram, "stwcx. r3, r0, r4").unwrap();
+    assert_eq!((cpu.cr_field(0), ram.data[0x10]), (0b0010, 0xAA));
+    assert_eq!(cpu.reservation, None);
+}
+
+#[test]
+fn test_emu_dcbz() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[5] = 0x14;
+    exec(&mut cpu, &mut ram, "dcbz r4, r5").unwrap();
+    assert!(ram.data[0x20..0x40].iter().all(|&byte| byte == 0));
+    assert_eq!(ram.data[0x1F], 0x1F);
+    assert_eq!(ram.data[0x40], 0x40);
+}
+
+#[test]
+fn test_emu_unmapped() {
+    let (mut cpu, mut ram) = setup();
+    cpu.gpr[4] = BASE + 0xFE;
+    assert_eq!(
+        exec(&mut cpu, &mut ram, "lwz r3, 0x0(r4)"),
+        Err(Error::Unmapped(BASE + 0xFE))
+    );
+    assert_eq!(cpu.pc, BASE);
+    assert_eq!(ram.read_u16(BASE + 0xFE), Ok(0xFEFF));
+}
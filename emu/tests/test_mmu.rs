@@ -0,0 +1,198 @@
+mod common;
+
+use ppc750cl_emu::{wimg, AccessType, Bat, Cpu, Error, Fault, Memory, Protection, Ram};
+
+use common::write_code;
+
+/// BATs mapping the cached and uncached mirrors of main memory, as set up by the OS.
+const CACHED: Bat = Bat {
+    upper: 0x8000_1FFF,
+    lower: 0x0000_0002,
+};
+const UNCACHED: Bat = Bat {
+    upper: 0xC000_1FFF,
+    lower: 0x0000_002A,
+};
+
+fn setup() -> (Cpu, Ram) {
+    let mut cpu = Cpu::new(0x8000_0000);
+    for (num, bat) in [(528, CACHED), (536, CACHED), (538, UNCACHED)] {
+        cpu.spr[num] = bat.upper;
+        cpu.spr[num + 1] = bat.lower;
+    }
+    (cpu, Ram::new(0, 0x40_0000))
+}
+
+fn fault(addr: u32, access: AccessType, fault: Fault) -> Error {
+    Error::Translation {
+        addr,
+        access,
+        fault,
+    }
+}
+
+#[test]
+fn test_mmu_bat() {
+    assert_eq!(CACHED.bepi(), 0x8000_0000);
+    assert_eq!(CACHED.size(), 0x1000_0000);
+    assert!(CACHED.vs() && CACHED.vp());
+    assert_eq!(UNCACHED.wimg(), wimg::I | wimg::G);
+    assert_eq!(UNCACHED.protection(), Protection::ReadWrite);
+    assert_eq!(
+        UNCACHED.to_string(),
+        "C0000000-CFFFFFFF -> 00000000 WIMG=0101 RW Vs Vp"
+    );
+    // Only the bits of the block length are passed through.
+    let small = Bat {
+        upper: 0x8000_0002,
+        lower: 0x0100_0001,
+    };
+    assert_eq!(small.size(), 0x2_0000);
+    assert_eq!(small.translate(0x8001_2345, true), Some(0x0101_2345));
+    assert_eq!(small.translate(0x8002_0000, true), None);
+    assert_eq!(small.translate(0x8001_2345, false), None);
+    assert_eq!(small.protection(), Protection::ReadOnly);
+}
+
+#[test]
+fn test_mmu_translate_bat() {
+    let (cpu, mut ram) = setup();
+    let mmu = cpu.mmu();
+    let translation = mmu
+        .translate(&mut ram, 0x8012_3456, AccessType::Read, true)
+        .unwrap();
+    assert_eq!((translation.addr, translation.wimg), (0x0012_3456, 0));
+    assert!(translation.block);
+    let translation = mmu
+        .translate(&mut ram, 0xC012_3456, AccessType::Write, true)
+        .unwrap();
+    assert_eq!(translation.addr, 0x0012_3456);
+    assert_eq!(translation.wimg, wimg::I | wimg::G);
+    // The uncached mirror has no IBAT.
+    assert_eq!(
+        mmu.translate(&mut ram, 0xC000_0000, AccessType::Execute, true),
+        Err(fault(0xC000_0000, AccessType::Execute, Fault::PageNotFound))
+    );
+
+    // Read-only and inaccessible blocks.
+    let (mut cpu, mut ram) = setup();
+    cpu.spr[537] = 0x0000_0001;
+    cpu.spr[539] = 0x0000_0000;
+    let mmu = cpu.mmu();
+    assert!(mmu
+        .translate(&mut ram, 0x8000_0000, AccessType::Read, true)
+        .is_ok());
+    assert_eq!(
+        mmu.translate(&mut ram, 0x8000_0000, AccessType::Write, true),
+        Err(fault(0x8000_0000, AccessType::Write, Fault::Protection))
+    );
+    assert_eq!(
+        mmu.translate(&mut ram, 0xC000_0000, AccessType::Read, true),
+        Err(fault(0xC000_0000, AccessType::Read, Fault::Protection))
+    );
+
+    // The BATs 4 to 7 of Broadway are enabled by HID4.
+    let (mut cpu, mut ram) = setup();
+    cpu.spr[568] = 0x9000_1FFF;
+    cpu.spr[569] = 0x1000_0002;
+    assert!(cpu
+        .mmu()
+        .translate(&mut ram, 0x9000_0000, AccessType::Read, true)
+        .is_err());
+    cpu.spr[1011] = 0x0200_0000;
+    let translation = cpu
+        .mmu()
+        .translate(&mut ram, 0x9000_0000, AccessType::Read, true)
+        .unwrap();
+    assert_eq!(translation.addr, 0x1000_0000);
+}
+
+#[test]
+fn test_mmu_page_table() {
+    let (mut cpu, mut ram) = setup();
+    // A page table of 64 KiB at 0x10000, segment 0 with VSID 0x123 and Kp set.
+    cpu.spr[25] = 0x0001_0000;
+    cpu.sr[0] = 0x2000_0123;
+    // Primary hash 0x123 ^ 5 for the page at 0x5000, in the second slot of its group.
+    let pteg = 0x1_0000 + (0x126 << 6);
+    ram.write_u32(pteg + 8, 0x8000_0000 | 0x123 << 7).unwrap();
+    ram.write_u32(pteg + 12, 0x0030_0000 | (wimg::M as u32) << 3 | 0b10)
+        .unwrap();
+    // Secondary hash for the page at 0x6000, with the API of 0x0040_0000.
+    let pteg = 0x1_0000 + ((!(0x123 ^ 0x406) & 0x3FF) << 6);
+    ram.write_u32(pteg, 0x8000_0000 | 0x123 << 7 | 0x40 | 1)
+        .unwrap();
+    ram.write_u32(pteg + 4, 0x0031_0000 | 0b01).unwrap();
+
+    let mmu = cpu.mmu();
+    let translation = mmu
+        .translate(&mut ram, 0x0000_5678, AccessType::Read, true)
+        .unwrap();
+    assert_eq!(translation.addr, 0x0030_0678);
+    assert_eq!((translation.wimg, translation.block), (wimg::M, false));
+    // The referenced bit is set on access, the changed bit on writes.
+    assert_eq!(
+        ram.read_u32(0x1_0000 + (0x126 << 6) + 12).unwrap(),
+        0x0030_0112
+    );
+    mmu.translate(&mut ram, 0x0000_5678, AccessType::Write, true)
+        .unwrap();
+    assert_eq!(
+        ram.read_u32(0x1_0000 + (0x126 << 6) + 12).unwrap(),
+        0x0030_0192
+    );
+
+    let translation = mmu
+        .translate(&mut ram, 0x0040_6ABC, AccessType::Read, false)
+        .unwrap();
+    assert_eq!(translation.addr, 0x0031_0ABC);
+    // With Kp set, PP 01 is read-only in user mode but writable in supervisor mode.
+    assert_eq!(
+        mmu.translate(&mut ram, 0x0040_6ABC, AccessType::Write, false),
+        Err(fault(0x0040_6ABC, AccessType::Write, Fault::Protection))
+    );
+    assert!(mmu
+        .translate(&mut ram, 0x0040_6ABC, AccessType::Write, true)
+        .is_ok());
+    assert_eq!(
+        mmu.translate(&mut ram, 0x0000_7000, AccessType::Read, true),
+        Err(fault(0x0000_7000, AccessType::Read, Fault::PageNotFound))
+    );
+
+    cpu.sr[1] = 0x1000_0000;
+    cpu.sr[2] = 0x8000_0000;
+    let mmu = cpu.mmu();
+    assert_eq!(
+        mmu.translate(&mut ram, 0x1000_0000, AccessType::Execute, true),
+        Err(fault(0x1000_0000, AccessType::Execute, Fault::NoExecute))
+    );
+    assert_eq!(
+        mmu.translate(&mut ram, 0x2000_0000, AccessType::Read, true),
+        Err(fault(0x2000_0000, AccessType::Read, Fault::DirectStore))
+    );
+}
+
+#[test]
+fn test_mmu_execute() {
+    let (mut cpu, mut ram) = setup();
+    // Instruction and data translation enabled.
+    cpu.msr = 0x30;
+    cpu.gpr[3] = 0x1234_5678;
+    cpu.gpr[4] = 0xC000_0000;
+    write_code(&mut ram, 0, &["stw r3, 0x10(r4)"]);
+    cpu.step(&mut ram).unwrap();
+    assert_eq!(cpu.pc, 0x8000_0004);
+    assert_eq!(ram.read_u32(0x10).unwrap(), 0x1234_5678);
+    // Real mode accesses physical addresses.
+    cpu.msr = 0;
+    cpu.pc = 0;
+    cpu.gpr[4] = 0;
+    cpu.step(&mut ram).unwrap();
+    assert_eq!(cpu.pc, 4);
+    cpu.msr = 0x30;
+    cpu.pc = 0x9000_0000;
+    assert_eq!(
+        cpu.step(&mut ram),
+        Err(fault(0x9000_0000, AccessType::Execute, Fault::PageNotFound))
+    );
+}
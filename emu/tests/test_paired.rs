@@ -0,0 +1,159 @@
+mod common;
+
+use ppc750cl_emu::fpscr::*;
+use ppc750cl_emu::{Cpu, FloatRegister, Ram};
+
+use common::{exec, BASE, POSITIVE};
+
+fn ps(ps0: f64, ps1: f64) -> FloatRegister {
+    FloatRegister { ps0, ps1 }
+}
+
+/// Executes an instruction with the given register values from f2 on, returning f1.
+fn run(text: &str, args: &[FloatRegister]) -> (Cpu, FloatRegister) {
+    let mut cpu = Cpu::new(BASE);
+    cpu.fpr[2..2 + args.len()].copy_from_slice(args);
+    exec(&mut cpu, &mut Ram::default(), text).unwrap();
+    let f1 = cpu.fpr[1];
+    (cpu, f1)
+}
+
+#[test]
+fn test_emu_ps_arithmetic() {
+    let (a, b, c) = (ps(1.0, 2.0), ps(3.0, 4.0), ps(5.0, 6.0));
+    assert_eq!(run("ps_add f1, f2, f3", &[a, b]).1, ps(4.0, 6.0));
+    assert_eq!(run("ps_sub f1, f2, f3", &[a, b]).1, ps(-2.0, -2.0));
+    assert_eq!(run("ps_mul f1, f2, f3", &[a, b]).1, ps(3.0, 8.0));
+    assert_eq!(
+        run("ps_div f1, f2, f3", &[a, b]).1,
+        ps((1.0f32 / 3.0) as f64, 0.5)
+    );
+    assert_eq!(run("ps_muls0 f1, f2, f3", &[a, b]).1, ps(3.0, 6.0));
+    assert_eq!(run("ps_muls1 f1, f2, f3", &[a, b]).1, ps(4.0, 8.0));
+    // Operands are frD, frA, frC, frB.
+    assert_eq!(run("ps_madd f1, f2, f3, f4", &[a, b, c]).1, ps(8.0, 14.0));
+    assert_eq!(run("ps_msub f1, f2, f3, f4", &[a, b, c]).1, ps(-2.0, 2.0));
+    assert_eq!(
+        run("ps_nmadd f1, f2, f3, f4", &[a, b, c]).1,
+        ps(-8.0, -14.0)
+    );
+    assert_eq!(run("ps_nmsub f1, f2, f3, f4", &[a, b, c]).1, ps(2.0, -2.0));
+    assert_eq!(run("ps_madds0 f1, f2, f3, f4", &[a, b, c]).1, ps(8.0, 12.0));
+    assert_eq!(run("ps_madds1 f1, f2, f3, f4", &[a, b, c]).1, ps(9.0, 14.0));
+    // Both halves are rounded to single precision, FPRF is set from ps0.
+    let (cpu, f1) = run("ps_add f1, f2, f3", &[ps(-1.0, 1.0), ps(0.0, 1e-10)]);
+    assert_eq!(f1, ps(-1.0, 1.0));
+    assert_eq!(cpu.fpscr, FX | XX | FI | 0x08 << 12);
+}
+
+#[test]
+fn test_emu_ps_sum() {
+    let (a, b, c) = (ps(1.0, 2.0), ps(3.0, 4.0), ps(5.0, 6.0));
+    assert_eq!(
+        run("ps_sum0 f1, f2, f3, f4", &[a, b, c]).1,
+        ps(1.0 + 6.0, 4.0)
+    );
+    assert_eq!(
+        run("ps_sum1 f1, f2, f3, f4", &[a, b, c]).1,
+        ps(3.0, 1.0 + 6.0)
+    );
+    // The half copied from frC is rounded too.
+    let c = ps(1.0 + 2f64.powi(-30), 1.0 + 2f64.powi(-30));
+    assert_eq!(run("ps_sum0 f1, f2, f3, f4", &[a, c, c]).1.ps1, 1.0);
+    // FPRF is set from the sum, in ps1 for ps_sum1.
+    let args = [ps(-1.0, 0.0), ps(5.0, 6.0), ps(0.0, -2.0)];
+    let (cpu, f1) = run("ps_sum0 f1, f2, f3, f4", &args);
+    assert_eq!((f1, cpu.fpscr), (ps(-3.0, 6.0), 0x08 << 12));
+    let (cpu, f1) = run("ps_sum1 f1, f2, f3, f4", &args);
+    assert_eq!((f1, cpu.fpscr), (ps(5.0, -3.0), 0x08 << 12));
+}
+
+#[test]
+fn test_emu_ps_move() {
+    let (a, b, c) = (ps(1.0, -2.0), ps(3.0, 4.0), ps(5.0, 6.0));
+    assert_eq!(run("ps_merge00 f1, f2, f3", &[a, b]).1, ps(1.0, 3.0));
+    assert_eq!(run("ps_merge01 f1, f2, f3", &[a, b]).1, ps(1.0, 4.0));
+    assert_eq!(run("ps_merge10 f1, f2, f3", &[a, b]).1, ps(-2.0, 3.0));
+    assert_eq!(run("ps_merge11 f1, f2, f3", &[a, b]).1, ps(-2.0, 4.0));
+    assert_eq!(run("ps_mr f1, f2", &[a]).1, a);
+    assert_eq!(run("ps_neg f1, f2", &[a]).1, ps(-1.0, 2.0));
+    assert_eq!(run("ps_abs f1, f2", &[a]).1, ps(1.0, 2.0));
+    assert_eq!(run("ps_nabs f1, f2", &[a]).1, ps(-1.0, -2.0));
+    assert_eq!(run("ps_sel f1, f2, f3, f4", &[a, b, c]).1, ps(3.0, 6.0));
+    // Merges do not round.
+    let value = 1.0 + 2f64.powi(-30);
+    assert_eq!(
+        run("ps_merge00 f1, f2, f2", &[ps(value, 0.0)]).1,
+        ps(value, value)
+    );
+}
+
+#[test]
+fn test_emu_ps_compare() {
+    let (a, b) = (ps(1.0, f64::NAN), ps(2.0, 2.0));
+    let (cpu, _) = run("ps_cmpu0 cr3, f2, f3", &[a, b]);
+    assert_eq!((cpu.cr_field(3), cpu.fpscr), (0b1000, 0b1000 << 12));
+    let (cpu, _) = run("ps_cmpu1 cr3, f2, f3", &[a, b]);
+    assert_eq!((cpu.cr_field(3), cpu.fpscr), (0b0001, 0b0001 << 12));
+    let (cpu, _) = run("ps_cmpo1 cr3, f2, f3", &[a, b]);
+    assert_eq!(cpu.fpscr, FX | VX | VXVC | 0b0001 << 12);
+}
+
+#[test]
+fn test_emu_ps_estimate() {
+    let (cpu, f1) = run("ps_res f1, f2", &[ps(2.0, 0.0)]);
+    assert_eq!(f1, ps(0.5 - 2f64.powi(-14), f64::INFINITY));
+    assert_eq!(cpu.fpscr, FX | ZX | POSITIVE);
+    let (cpu, f1) = run("ps_rsqrte f1, f2", &[ps(1.0, 4.0)]);
+    assert_eq!(
+        f1,
+        ps(1.0 - 3.0 * 2f64.powi(-14), 0.5 - 1.5 * 2f64.powi(-14))
+    );
+    assert_eq!(cpu.fpscr, POSITIVE);
+}
+
+/// Sets up memory and GQR 1 for quantized loads and stores.
+/// Sets GQR1 for the quantized loads and stores.
+fn setup(gqr: u32) -> (Cpu, Ram) {
+    let (mut cpu, ram) = common::setup(0x40);
+    cpu.spr[912 + 1] = gqr;
+    (cpu, ram)
+}
+
+#[test]
+fn test_emu_psq_float() {
+    let (mut cpu, mut ram) = setup(0);
+    cpu.fpr[1] = ps(1.5, -2.0);
+    exec(&mut cpu, &mut ram, "psq_st f1, 0x0(r4), 0, qr1").unwrap();
+    assert_eq!(ram.data[0x10..0x18], [0x3F, 0xC0, 0, 0, 0xC0, 0, 0, 0]);
+    exec(&mut cpu, &mut ram, "psq_l f2, 0x0(r4), 0, qr1").unwrap();
+    assert_eq!(cpu.fpr[2], ps(1.5, -2.0));
+    // Single loads set ps1 to one, single stores only write ps0.
+    exec(&mut cpu, &mut ram, "psq_l f3, 0x4(r4), 1, qr1").unwrap();
+    assert_eq!(cpu.fpr[3], ps(-2.0, 1.0));
+    exec(&mut cpu, &mut ram, "psq_stu f3, 0x8(r4), 1, qr1").unwrap();
+    assert_eq!(ram.data[0x18..0x20], [0xC0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(cpu.gpr[4], BASE + 0x18);
+}
+
+#[test]
+fn test_emu_psq_integer() {
+    // Loads u8 with a scale of 2, stores s16 with a scale of -1 truncating towards zero.
+    let (mut cpu, mut ram) = setup(0x0204_3F07);
+    ram.data[0x10..0x12].copy_from_slice(&[0x06, 0xFF]);
+    exec(&mut cpu, &mut ram, "psq_l f1, 0x0(r4), 0, qr1").unwrap();
+    assert_eq!(cpu.fpr[1], ps(1.5, 63.75));
+    cpu.fpr[1] = ps(-3.0, 40000.0);
+    exec(&mut cpu, &mut ram, "psq_st f1, 0x0(r4), 0, qr1").unwrap();
+    assert_eq!(ram.data[0x10..0x14], [0xFF, 0xFF, 0x4E, 0x20]);
+    // Values are saturated, NaNs are stored as zero.
+    cpu.fpr[1] = ps(-1e10, f64::NAN);
+    exec(&mut cpu, &mut ram, "psq_st f1, 0x0(r4), 0, qr1").unwrap();
+    assert_eq!(ram.data[0x10..0x14], [0x80, 0x00, 0x00, 0x00]);
+    // Indexed forms use the GQR of the instruction.
+    let (mut cpu, mut ram) = setup(0x0006_0000);
+    ram.data[0x10..0x12].copy_from_slice(&[0x80, 0x7F]);
+    cpu.gpr[5] = 0;
+    exec(&mut cpu, &mut ram, "psq_lx f1, r4, r5, 0, qr1").unwrap();
+    assert_eq!(cpu.fpr[1], ps(-128.0, 127.0));
+}
@@ -35,11 +35,11 @@ impl<'a> Hash for BasicBlock<'a> {
 }
 
 impl<'a> BasicBlock<'a> {
-    pub fn from_code_slice(range: Range<CodeIdx>, complete_code: &'a [Ins]) -> BasicBlock {
+    pub fn from_code_slice(range: Range<CodeIdx>, complete_code: &'a [Ins]) -> BasicBlock<'a> {
         let start_idx = complete_code.first().unwrap().addr / 4;
         assert!(start_idx <= range.start);
         let offset = (range.start - start_idx) as usize;
-        let code = &complete_code[offset..(offset + (range.len() as usize))];
+        let code = &complete_code[offset..(offset + range.len())];
         BasicBlock {
             range,
             code,
@@ -92,7 +92,7 @@ impl<'a> Debug for BasicBlock<'a> {
             self.range.end * 4
         )?;
         for ins in self.code {
-            writeln!(f, "{}", FormattedIns(ins.clone()))?;
+            writeln!(f, "{}", FormattedIns(*ins))?;
             if let Some(addr) = self.data_refs.get(&(ins.addr / 4)) {
                 writeln!(f, "  ref: {:0>#8x}", addr)?;
             }
@@ -155,8 +155,10 @@ impl<'a> FlowGraph<'a> {
             // Get last instruction of left block.
             // Unless it's an unconditional branch, we can connect the blocks.
             let last_ins = &src_block.code.last().unwrap();
-            if last_ins.code == 0x4E800020
-                || (last_ins.op == Opcode::B && last_ins.field_BO() == 0b10100)
+            if last_ins.is_blr()
+                || last_ins
+                    .branch_info()
+                    .is_some_and(|branch| branch.is_unconditional() && !branch.link)
             {
                 continue;
             }
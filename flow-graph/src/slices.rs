@@ -33,15 +33,16 @@ impl BasicSlices {
                 continue;
             }
             // We encountered some kind of control flow instruction.
-            if ins.field_BO() == 20 && ins.field_BI() == 0 {
+            let branch = ins.branch_info().unwrap();
+            if let Some(dest) = branch.target {
                 // There's a possibility that branch can be taken.
                 // Branch destinations are always the first instruction of a block.
                 // Thus, we also found the end of another block.
-                let new_index = ins.branch_dest().unwrap() / 4;
+                let new_index = dest / 4;
                 cuts.insert(new_index);
                 branches.insert((cur_index, new_index));
             }
-            if is_conditional_branch(ins) {
+            if !branch.is_unconditional() {
                 // There's a possibility that branch is not taken.
                 // End block anyways.
                 cuts.insert(cur_index + 1);
@@ -51,12 +52,3 @@ impl BasicSlices {
         Self { cuts, branches }
     }
 }
-
-fn is_conditional_branch(ins: &Ins) -> bool {
-    match ins.op {
-        Opcode::Bc | Opcode::Bcctr | Opcode::Bclr => (),
-        _ => return false,
-    };
-    // Check whether bits "branch always".
-    ins.field_BO() & 0b10100 != 0b10100
-}
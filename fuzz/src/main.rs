@@ -5,8 +5,9 @@ use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use ppc750cl::asm::assemble;
 use ppc750cl::formatter::FormattedIns;
-use ppc750cl::Ins;
+use ppc750cl::{Ins, Opcode};
 
 fn main() {
     let matches = clap::Command::new("ppc750cl-fuzz")
@@ -19,25 +20,63 @@ fn main() {
                 .takes_value(true)
                 .help("Number of threads to use (default num CPUs)"),
         )
+        .arg(
+            clap::Arg::new("roundtrip")
+                .long("--roundtrip")
+                .help("Verify that the assembler reproduces every disassembled instruction"),
+        )
+        .arg(
+            clap::Arg::new("detect")
+                .long("--detect")
+                .help("Verify opcode detection against a linear scan of all opcodes"),
+        )
+        .arg(
+            clap::Arg::new("decode-only")
+                .long("--decode-only")
+                .help("Decode instructions without printing them, to measure the decoder"),
+        )
+        .group(clap::ArgGroup::new("mode").args(&["roundtrip", "detect", "decode-only"]))
         .get_matches();
 
     let threads = match matches.value_of("threads") {
         Some(t) => u32::from_str(t).expect("invalid threads flag"),
         None => num_cpus::get() as u32,
     };
+    let mode = if matches.is_present("roundtrip") {
+        Mode::Roundtrip
+    } else if matches.is_present("detect") {
+        Mode::Detect
+    } else if matches.is_present("decode-only") {
+        Mode::DecodeOnly
+    } else {
+        Mode::Print
+    };
     let start = Instant::now();
-    let fuzzer = MultiFuzzer::new(threads);
+    let fuzzer = MultiFuzzer::new(threads, mode);
     fuzzer.run();
     println!("Finished in {:.2}s", start.elapsed().as_secs_f32());
 }
 
+/// What the fuzzer does with every instruction.
+#[derive(Copy, Clone)]
+enum Mode {
+    /// Disassembles and prints to nowhere.
+    Print,
+    /// Re-assembles the disassembly.
+    Roundtrip,
+    /// Compares opcode detection against a linear scan.
+    Detect,
+    /// Decodes without printing.
+    DecodeOnly,
+}
+
 #[derive(Clone)]
 struct MultiFuzzer {
     threads: Vec<Fuzzer>,
 }
 
 impl MultiFuzzer {
-    fn new(num_threads: u32) -> Self {
+    fn new(num_threads: u32, mode: Mode) -> Self {
         assert_ne!(num_threads, 0);
         let mut threads = Vec::<Fuzzer>::with_capacity(num_threads as usize);
         let part_size = 0xFFFF_FFFF / num_threads;
@@ -47,7 +86,7 @@ impl MultiFuzzer {
                 None => break,
                 Some(v) => v,
             };
-            threads.push(Fuzzer::new(offset..next_offset));
+            threads.push(Fuzzer::new(offset..next_offset, mode));
             offset = next_offset;
         }
         threads.last_mut().unwrap().range.end = 0xFFFF_FFFF;
@@ -87,13 +126,15 @@ impl MultiFuzzer {
 struct Fuzzer {
     range: Range<u32>,
     counter: Arc<AtomicU32>,
+    mode: Mode,
 }
 
 impl Fuzzer {
-    fn new(range: Range<u32>) -> Self {
+    fn new(range: Range<u32>, mode: Mode) -> Self {
         Self {
             range,
             counter: Arc::new(AtomicU32::new(0)),
+            mode,
         }
     }
 
@@ -102,10 +143,25 @@ impl Fuzzer {
 
         let counter = Arc::clone(&self.counter);
         let range = self.range.clone();
+        let mode = self.mode;
         std::thread::spawn(move || {
             for x in range.clone() {
-                let ins = Ins::new(x, 0x8000_0000);
-                writeln!(&mut devnull, "{}", FormattedIns(ins)).unwrap();
+                match mode {
+                    Mode::Print => {
+                        let ins = Ins::new(x, 0x8000_0000);
+                        writeln!(&mut devnull, "{}", FormattedIns(ins)).unwrap();
+                    }
+                    Mode::Roundtrip => {
+                        let ins = Ins::new(x, 0x8000_0000);
+                        if ins.op != Opcode::Illegal {
+                            check_roundtrip(ins);
+                        }
+                    }
+                    Mode::Detect => check_detect(x),
+                    Mode::DecodeOnly => {
+                        std::hint::black_box(Ins::new(x, 0x8000_0000));
+                    }
+                }
                 if x % (1 << 19) == 0 {
                     counter.store(x, Ordering::Relaxed);
                 }
@@ -115,6 +171,34 @@ impl Fuzzer {
     }
 }
 
+/// Re-assembles the disassembly of an instruction and checks that it encodes back to the
+/// same word, or, for invalid forms and words with reserved bits set, that it disassembles
+/// identically.
+fn check_roundtrip(ins: Ins) {
+    let disasm = FormattedIns(ins).to_string();
+    let code = match assemble(&disasm) {
+        Ok(code) => code,
+        Err(err) => panic!("{:#010x} {}: {}", ins.code, disasm, err),
+    };
+    if ins.is_valid() {
+        assert_eq!(code, ins.code, "{}", disasm);
+    } else {
+        let disasm2 = FormattedIns(Ins::new(code, ins.addr)).to_string();
+        assert_eq!(disasm, disasm2, "{:#010x} -> {:#010x}", ins.code, code);
+    }
+}
+
+/// Checks the decision tree of [`Opcode::detect`] against the first opcode matching
+/// the word in definition order.
+fn check_detect(code: u32) {
+    let expected = Opcode::ALL
+        .iter()
+        .copied()
+        .find(|op| code & op.bitmask() == op.pattern())
+        .unwrap_or(Opcode::Illegal);
+    assert_eq!(Opcode::detect(code), expected, "{:#010x}", code);
+}
+
 struct DevNull;
 
 impl std::io::Write for DevNull {
@@ -0,0 +1,589 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::Itertools;
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::{LitChar, LitInt, LitStr};
+
+use crate::{to_rust_ident, Error, Field, Isa, Modifier, Result};
+
+/// An expression as used in mnemonic conditions and arguments.
+#[derive(Clone, Debug)]
+pub(crate) enum Expr {
+    Num(i64),
+    Field(String),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum BinOp {
+    Add,
+    Sub,
+    BitAnd,
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+}
+
+impl BinOp {
+    /// Binding power of the operator, following Rust precedence rules.
+    fn precedence(self) -> u8 {
+        match self {
+            BinOp::And => 1,
+            BinOp::Eq | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => 2,
+            BinOp::BitAnd => 3,
+            BinOp::Add | BinOp::Sub => 4,
+        }
+    }
+
+    fn tokens(self) -> TokenStream {
+        match self {
+            BinOp::Add => quote!(+),
+            BinOp::Sub => quote!(-),
+            BinOp::BitAnd => quote!(&),
+            BinOp::Eq => quote!(==),
+            BinOp::Lt => quote!(<),
+            BinOp::Le => quote!(<=),
+            BinOp::Gt => quote!(>),
+            BinOp::Ge => quote!(>=),
+            BinOp::And => quote!(&&),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Num(i64),
+    Ident(String),
+    Op(BinOp),
+    Open,
+    Close,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let chars = src.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word = chars[start..i].iter().collect::<String>();
+            tokens.push(if c.is_ascii_digit() {
+                let value = if let Some(bin) = word.strip_prefix("0b") {
+                    i64::from_str_radix(bin, 2)?
+                } else if let Some(hex) = word.strip_prefix("0x") {
+                    i64::from_str_radix(hex, 16)?
+                } else {
+                    word.parse::<i64>()?
+                };
+                Token::Num(value)
+            } else {
+                Token::Ident(word)
+            });
+            continue;
+        }
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            ('&', Some('&')) => (Token::Op(BinOp::And), 2),
+            ('=', Some('=')) => (Token::Op(BinOp::Eq), 2),
+            ('<', Some('=')) => (Token::Op(BinOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(BinOp::Ge), 2),
+            ('&', _) => (Token::Op(BinOp::BitAnd), 1),
+            ('<', _) => (Token::Op(BinOp::Lt), 1),
+            ('>', _) => (Token::Op(BinOp::Gt), 1),
+            ('+', _) => (Token::Op(BinOp::Add), 1),
+            ('-', _) => (Token::Op(BinOp::Sub), 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            _ => return Err(format!("unexpected character in expression: {}", src).into()),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+impl Expr {
+    /// Parses an expression such as `BO & 0b11110 == 12 && BI == 0`.
+    pub(crate) fn parse(src: &str) -> Result<Self> {
+        let tokens = tokenize(src)?;
+        let mut pos = 0;
+        let expr = Self::parse_binary(&tokens, &mut pos, 0)?;
+        if pos != tokens.len() {
+            return Err(format!("trailing tokens in expression: {}", src).into());
+        }
+        Ok(expr)
+    }
+
+    fn parse_binary(tokens: &[Token], pos: &mut usize, min_prec: u8) -> Result<Self> {
+        let mut lhs = Self::parse_primary(tokens, pos)?;
+        while let Some(Token::Op(op)) = tokens.get(*pos) {
+            let op = *op;
+            if op.precedence() < min_prec {
+                break;
+            }
+            *pos += 1;
+            let rhs = Self::parse_binary(tokens, pos, op.precedence() + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_primary(tokens: &[Token], pos: &mut usize) -> Result<Self> {
+        let token = tokens
+            .get(*pos)
+            .ok_or_else(|| Error::from("unexpected end of expression"))?;
+        *pos += 1;
+        match token {
+            Token::Num(n) => Ok(Expr::Num(*n)),
+            Token::Ident(name) => Ok(Expr::Field(name.clone())),
+            Token::Open => {
+                let expr = Self::parse_binary(tokens, pos, 0)?;
+                if tokens.get(*pos) != Some(&Token::Close) {
+                    return Err("unbalanced parentheses in expression".into());
+                }
+                *pos += 1;
+                Ok(expr)
+            }
+            _ => Err(format!("unexpected token in expression: {:?}", token).into()),
+        }
+    }
+
+    /// Splits a conjunction into its terms.
+    pub(crate) fn conjuncts(self) -> Vec<Expr> {
+        match self {
+            Expr::Binary(BinOp::And, lhs, rhs) => {
+                let mut terms = lhs.conjuncts();
+                terms.extend(rhs.conjuncts());
+                terms
+            }
+            expr => vec![expr],
+        }
+    }
+
+    fn count_field(&self, name: &str) -> usize {
+        match self {
+            Expr::Num(_) => 0,
+            Expr::Field(field) => (field == name) as usize,
+            Expr::Binary(_, lhs, rhs) => lhs.count_field(name) + rhs.count_field(name),
+        }
+    }
+
+    fn collect_fields<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Num(_) => {}
+            Expr::Field(field) => out.push(field),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_fields(out);
+                rhs.collect_fields(out);
+            }
+        }
+    }
+
+    fn unknown_fields(&self, known: &HashSet<String>) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+            .into_iter()
+            .filter(|field| !known.contains(*field))
+            .unique()
+            .collect()
+    }
+
+    /// Compiles the expression into Rust code on `i64` values read from `code`.
+    fn compile(&self, field_by_name: &HashMap<String, &Field>) -> TokenStream {
+        match self {
+            Expr::Num(n) => {
+                let lit = Literal::i64_unsuffixed(*n);
+                quote!(#lit)
+            }
+            Expr::Field(name) => {
+                let bits = field_by_name[name].field_bits();
+                quote!(#bits.extract(code))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.compile(field_by_name);
+                let rhs = rhs.compile(field_by_name);
+                let op = op.tokens();
+                quote!((#lhs #op #rhs))
+            }
+        }
+    }
+
+    /// Solves `self == value` for the only occurrence of `target`.
+    ///
+    /// Only sums and differences are supported, which covers all mnemonics.
+    fn invert(
+        &self,
+        target: &str,
+        value: TokenStream,
+        field_by_name: &HashMap<String, &Field>,
+    ) -> Option<TokenStream> {
+        match self {
+            Expr::Field(name) if name == target => Some(value),
+            Expr::Binary(op @ (BinOp::Add | BinOp::Sub), lhs, rhs) => {
+                if lhs.count_field(target) == 1 {
+                    let rhs = rhs.compile(field_by_name);
+                    let value = match op {
+                        BinOp::Add => quote!((#value - #rhs)),
+                        _ => quote!((#value + #rhs)),
+                    };
+                    lhs.invert(target, value, field_by_name)
+                } else {
+                    let lhs = lhs.compile(field_by_name);
+                    let value = match op {
+                        BinOp::Add => quote!((#value - #lhs)),
+                        _ => quote!((#lhs - #value)),
+                    };
+                    rhs.invert(target, value, field_by_name)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Field {
+    /// Constructs the `FieldBits` layout descriptor of this field.
+    pub(crate) fn field_bits(&self) -> TokenStream {
+        let ident = to_rust_ident("", &self.name);
+        quote!(FieldBits::#ident)
+    }
+
+    /// Defines the `FieldBits` constant of this field.
+    fn field_bits_const(&self) -> TokenStream {
+        let ident = match self.variant_identifier() {
+            Some(ident) if self.arg.is_some() => ident,
+            _ => return TokenStream::new(),
+        };
+        let name = LitStr::new(&self.name, Span::call_site());
+        let start = self.bits.0.start;
+        let end = self.bits.0.end;
+        let signed = self.signed;
+        let split = self.split;
+        let shift_left = self.shift_left;
+        quote! {
+            pub(crate) const #ident: FieldBits = FieldBits {
+                name: #name,
+                start: #start,
+                end: #end,
+                signed: #signed,
+                split: #split,
+                shift_left: #shift_left,
+            };
+        }
+    }
+}
+
+impl Modifier {
+    /// Generates the statements setting this modifier in `code`.
+    ///
+    /// Returns the conditions under which the modifier applies, and the assignments.
+    pub(crate) fn gen_apply(
+        &self,
+        field_by_name: &HashMap<String, &Field>,
+    ) -> Result<(Vec<TokenStream>, TokenStream)> {
+        if self.condition.is_empty() {
+            let bit = LitInt::new(
+                &format!("{:#x}", 1u32 << (31 - self.bit)),
+                Span::call_site(),
+            );
+            return Ok((Vec::new(), quote!(code |= #bit;)));
+        }
+        // Terms that are not assignments guard the modifier.
+        let mut guards = Vec::new();
+        let mut assigns = Vec::new();
+        for term in Expr::parse(&self.condition)?.conjuncts() {
+            let mut solver = Solver::new(field_by_name);
+            if solver.solve_term(&term)? && !solver.stmts.is_empty() {
+                assigns.extend(solver.stmts);
+            } else {
+                guards.push(term.compile(field_by_name));
+            }
+        }
+        Ok((guards, TokenStream::from_iter(assigns)))
+    }
+}
+
+/// Emits statements building up `code` from operand values and conditions.
+struct Solver<'a> {
+    field_by_name: &'a HashMap<String, &'a Field>,
+    known: HashSet<String>,
+    stmts: Vec<TokenStream>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(field_by_name: &'a HashMap<String, &'a Field>) -> Self {
+        Self {
+            field_by_name,
+            known: HashSet::new(),
+            stmts: Vec::new(),
+        }
+    }
+
+    fn field(&self, name: &str) -> Result<&'a Field> {
+        self.field_by_name
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::from(format!("undefined field {}", name)))
+    }
+
+    fn assign(&mut self, name: &str, value: TokenStream) -> Result<()> {
+        let bits = self.field(name)?.field_bits();
+        self.stmts.push(quote! {
+            code = #bits.insert(code, #value)?;
+        });
+        self.known.insert(name.to_owned());
+        Ok(())
+    }
+
+    /// Tries to derive field assignments from an argument binding `expr == value`.
+    fn solve_arg(&mut self, target: &str, expr: &Expr, value: &TokenStream) -> Result<bool> {
+        if expr.unknown_fields(&self.known) != [target] || expr.count_field(target) != 1 {
+            return Ok(false);
+        }
+        match expr.invert(target, value.clone(), self.field_by_name) {
+            Some(value) => {
+                self.assign(target, value)?;
+                Ok(true)
+            }
+            None => Err(format!("cannot invert argument expression for {}", target).into()),
+        }
+    }
+
+    /// Tries to derive field assignments from a condition term.
+    ///
+    /// Returns `true` if the term was consumed.
+    fn solve_term(&mut self, term: &Expr) -> Result<bool> {
+        let (lhs, rhs) = match term {
+            Expr::Binary(BinOp::Eq, lhs, rhs) => (lhs.as_ref(), rhs.as_ref()),
+            _ => return Ok(false),
+        };
+        // Masked assignments, such as `BO & 0b11110 == 12`.
+        if let (Expr::Binary(BinOp::BitAnd, field, mask), Expr::Num(value)) = (lhs, rhs) {
+            if let (Expr::Field(name), Expr::Num(mask)) = (field.as_ref(), mask.as_ref()) {
+                if !self.known.contains(name) {
+                    let bits = self.field(name)?.field_bits();
+                    let mask = LitInt::new(&format!("{:#x}", mask), Span::call_site());
+                    let value = LitInt::new(&format!("{:#x}", value), Span::call_site());
+                    self.stmts.push(quote! {
+                        code = #bits.insert_masked(code, #mask, #value);
+                    });
+                    return Ok(true);
+                }
+            }
+        }
+        let unknown = lhs
+            .unknown_fields(&self.known)
+            .into_iter()
+            .chain(rhs.unknown_fields(&self.known))
+            .unique()
+            .collect::<Vec<_>>();
+        match unknown.as_slice() {
+            // Nothing to assign, the term is a consistency check.
+            [] => Ok(true),
+            [target] => {
+                let target = target.to_string();
+                let (with, without) = if lhs.count_field(&target) > 0 {
+                    (lhs, rhs)
+                } else {
+                    (rhs, lhs)
+                };
+                if with.count_field(&target) != 1 {
+                    return Ok(false);
+                }
+                let value = without.compile(self.field_by_name);
+                match with.invert(&target, value, self.field_by_name) {
+                    Some(value) => {
+                        self.assign(&target, value)?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                }
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Resolves argument bindings and condition terms until no progress can be made.
+    ///
+    /// Returns the condition terms that could not be turned into assignments.
+    fn solve(
+        &mut self,
+        mut args: Vec<(String, Expr, TokenStream)>,
+        mut terms: Vec<Expr>,
+    ) -> Result<Vec<Expr>> {
+        loop {
+            let mut progress = false;
+            let mut pending_args = Vec::new();
+            for (target, expr, value) in args {
+                if self.solve_arg(&target, &expr, &value)? {
+                    progress = true;
+                } else {
+                    pending_args.push((target, expr, value));
+                }
+            }
+            args = pending_args;
+            let mut pending_terms = Vec::new();
+            for term in terms {
+                if self.solve_term(&term)? {
+                    progress = true;
+                } else {
+                    pending_terms.push(term);
+                }
+            }
+            terms = pending_terms;
+            if !progress {
+                break;
+            }
+        }
+        if let Some((target, _, _)) = args.first() {
+            return Err(format!("cannot resolve argument {}", target).into());
+        }
+        Ok(terms)
+    }
+}
+
+impl Isa {
+    /// Generates the `_assemble` function for all opcodes and simplified mnemonics.
+    pub(crate) fn gen_assemble_fn(&self) -> Result<TokenStream> {
+        let mut field_by_name = HashMap::<String, &Field>::new();
+        for field in &self.fields {
+            field_by_name.insert(field.name.clone(), field);
+        }
+        let mut modifier_by_name = HashMap::<String, &Modifier>::new();
+        for modifier in &self.modifiers {
+            modifier_by_name.insert(modifier.name.clone(), modifier);
+        }
+        let mut opcode_by_name = HashMap::new();
+        for opcode in &self.opcodes {
+            opcode_by_name.insert(opcode.name.as_str(), opcode);
+        }
+
+        // Collect assembler forms, basic opcodes first.
+        let mut forms = Vec::new();
+        for opcode in &self.opcodes {
+            let args: Vec<(&str, &str)> = opcode
+                .args
+                .iter()
+                .map(|arg| (arg.as_str(), arg.as_str()))
+                .collect();
+            forms.push((opcode.name.as_str(), opcode, &opcode.modifiers, args, None));
+        }
+        for mnemonic in &self.mnemonics {
+            let opcode = opcode_by_name
+                .get(mnemonic.opcode.as_str())
+                .ok_or_else(|| Error::from(format!("undefined opcode {}", mnemonic.opcode)))?;
+            let modifiers = mnemonic.modifiers.as_ref().unwrap_or(&opcode.modifiers);
+            let args = mnemonic
+                .args
+                .iter()
+                .map(|arg| arg.split_once('=').unwrap_or((arg, arg)))
+                .collect();
+            forms.push((
+                mnemonic.name.as_str(),
+                opcode,
+                modifiers,
+                args,
+                Some(mnemonic.condition.as_str()),
+            ));
+        }
+
+        let mut branches = Vec::new();
+        for (name, opcode, modifiers, args, condition) in forms {
+            let name = LitStr::new(name, Span::call_site());
+            let pattern = LitInt::new(&format!("{:#x}", opcode.pattern), Span::call_site());
+            let mut kinds = Vec::new();
+            let mut bindings = Vec::new();
+            for (i, (field_name, expr)) in args.into_iter().enumerate() {
+                let field: &Field = field_by_name
+                    .get(field_name)
+                    .ok_or_else(|| Error::from(format!("undefined field {}", field_name)))?;
+                let kind = Ident::new(field.arg.as_ref().unwrap(), Span::call_site());
+                kinds.push(quote!(ArgKind::#kind));
+                let idx = Literal::usize_unsuffixed(i);
+                bindings.push((
+                    field_name.to_owned(),
+                    Expr::parse(expr)?,
+                    quote!(args[#idx]),
+                ));
+            }
+            let terms = match condition {
+                Some(condition) if !condition.is_empty() => Expr::parse(condition)?.conjuncts(),
+                _ => Vec::new(),
+            };
+            let mut solver = Solver::new(&field_by_name);
+            solver.solve(bindings, terms)?;
+            let stmts = solver.stmts;
+
+            // Apply modifiers present in the suffix.
+            let mut suffixes = Vec::new();
+            let mut modifier_stmts = Vec::new();
+            for (i, mod_name) in modifiers.iter().enumerate() {
+                let modifier: &Modifier = modifier_by_name
+                    .get(mod_name)
+                    .ok_or_else(|| Error::from(format!("undefined modifier {}", mod_name)))?;
+                suffixes.push(LitChar::new(modifier.suffix, Span::call_site()));
+                let flag = 1u8 << i;
+                let (guards, assigns) = modifier.gen_apply(&field_by_name)?;
+                modifier_stmts.push(quote! {
+                    if suffix & #flag != 0 #(&& #guards)* {
+                        #assigns
+                    }
+                });
+            }
+            let stmts = TokenStream::from_iter(stmts);
+            let modifier_stmts = TokenStream::from_iter(modifier_stmts);
+            let args_ident = if kinds.is_empty() {
+                quote!(_args)
+            } else {
+                quote!(args)
+            };
+            let suffix_ident = if modifiers.is_empty() {
+                quote!(_suffix)
+            } else {
+                quote!(suffix)
+            };
+            branches.push(quote! {
+                if let Some(#suffix_ident) = parse_mnemonic(mnemonic, #name, &[#(#suffixes),*]) {
+                    match parse_operands(operands, [#(#kinds),*]) {
+                        Ok(#args_ident) => {
+                            let mut code = #pattern;
+                            #stmts
+                            #modifier_stmts
+                            return Ok(code);
+                        }
+                        Err(e) => err = prefer_error(err, e),
+                    }
+                }
+            });
+        }
+        let branches = TokenStream::from_iter(branches);
+        let field_consts =
+            TokenStream::from_iter(self.fields.iter().map(|field| field.field_bits_const()));
+        Ok(quote! {
+            #[allow(dead_code, non_upper_case_globals)]
+            impl FieldBits {
+                #field_consts
+            }
+
+            #[allow(clippy::all, unused_mut, unused_parens)]
+            pub(crate) fn _assemble(mnemonic: &str, operands: &[Operand]) -> Result<u32, AsmError> {
+                let mut err: Option<AsmError> = None;
+                #branches
+                Err(err.unwrap_or(AsmError::UnknownMnemonic))
+            }
+        })
+    }
+}
@@ -11,6 +11,8 @@ use quote::quote;
 use serde::{Deserialize, Deserializer};
 use syn::{LitChar, LitInt, LitStr};
 
+mod asm;
+
 macro_rules! token_stream {
     ($stream:ident) => {
         TokenStream::from_iter($stream.into_iter())
@@ -35,13 +37,18 @@ fn _main() -> Result<()> {
         &mut unformatted_code,
         "{}",
         quote! {
+            use crate::asm::*;
+            use crate::encode::*;
             use crate::prelude::*;
         }
     )?;
     writeln!(&mut unformatted_code, "{}", isa.gen_opcode_enum()?)?;
     writeln!(&mut unformatted_code, "{}", isa.gen_field_enum()?)?;
+    writeln!(&mut unformatted_code, "{}", isa.gen_modifier_enum()?)?;
+    writeln!(&mut unformatted_code, "{}", isa.gen_spr_names()?)?;
     writeln!(&mut unformatted_code, "{}", isa.gen_field_impl()?)?;
     writeln!(&mut unformatted_code, "{}", isa.gen_ins_impl()?)?;
+    writeln!(&mut unformatted_code, "{}", isa.gen_assemble_fn()?)?;
 
     let formatted_code = rustfmt(unformatted_code);
     File::create("./disasm/src/generated.rs")?.write_all(&formatted_code)?;
@@ -205,6 +212,32 @@ impl Field {
             }
         }
     }
+
+    /// Constructs a typed accessor that checks whether the opcode has the field.
+    fn construct_typed_accessor(&self, opcodes: &[TokenTree]) -> TokenStream {
+        let (Some(name), Some(arg)) = (self.identifier(""), &self.arg) else {
+            return TokenStream::new();
+        };
+        if opcodes.is_empty() {
+            return TokenStream::new();
+        }
+        let accessor = Ident::new(&name.to_string().to_lowercase(), Span::call_site());
+        let arg = Ident::new(arg, Span::call_site());
+        let value = self.express_value_self();
+        let doc = format!(
+            " Returns the `{}` field, or `None` if the opcode does not have it.",
+            self.name
+        );
+        quote! {
+            #[doc = #doc]
+            pub fn #accessor(&self) -> Option<#arg> {
+                match self.op {
+                    #(Opcode::#opcodes)|* => Some(#arg(#value as _)),
+                    _ => None,
+                }
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Default)]
@@ -214,17 +247,64 @@ pub(crate) struct Opcode {
     desc: String,
     bitmask: u32,
     pattern: u32,
+    // Instruction form, a variant of `Form`
+    form: String,
+    // Functional category, a variant of `Category`
+    category: String,
+    // Processors implementing the opcode, variants of `Variant`, all if empty
+    variants: Vec<String>,
     modifiers: Vec<String>,
-    side_effects: Vec<String>,
     args: Vec<String>,
+    // Register fields that must not be zero
+    nonzero: Vec<String>,
+    // Reserved bits outside of the bitmask, ignored when decoding
+    reserved: u32,
+    // Conditions under which the form is invalid, in addition to `nonzero`
+    invalid: Vec<String>,
     defs: Vec<String>,
     uses: Vec<String>,
+    // Implicitly accessed registers
+    reads: Vec<String>,
+    writes: Vec<String>,
 }
 
 impl Opcode {
     fn variant_identifier(&self) -> Result<TokenTree> {
         to_rust_variant(&self.name)
     }
+
+    /// Expresses how the opcode accesses a register operand, derived from defs and uses.
+    fn express_access(&self, field: &Field) -> TokenStream {
+        const REGISTERS: [&str; 7] = ["GPR", "FPR", "CRField", "CRBit", "SR", "SPR", "GQR"];
+        if !field
+            .arg
+            .as_ref()
+            .is_some_and(|arg| REGISTERS.contains(&arg.as_str()))
+        {
+            return quote!(None);
+        }
+        let contains = |names: &[String]| {
+            names
+                .iter()
+                .any(|name| name.strip_suffix(".nz").unwrap_or(name) == field.name)
+        };
+        let def = contains(&self.defs);
+        // Conditional branches read the bit named by BI, or the field of simplified mnemonics.
+        let tested = self.category == "Branch" && matches!(field.name.as_str(), "BI" | "crfS");
+        let used = contains(&self.uses) || tested;
+        let memory = matches!(self.category.as_str(), "Load" | "Store" | "Cache");
+        if memory && field.name == "rA" {
+            quote!(Some(Access::Base { update: #def }))
+        } else if def && used {
+            quote!(Some(Access::ReadWrite))
+        } else if def {
+            quote!(Some(Access::Write))
+        } else if used {
+            quote!(Some(Access::Read))
+        } else {
+            quote!(None)
+        }
+    }
 }
 
 #[derive(Deserialize, Default)]
@@ -269,15 +349,36 @@ impl Modifier {
     }
 }
 
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub(crate) struct Spr {
+    name: String,
+    number: u16,
+    // Processors implementing the register, variants of `Variant`, all if empty
+    variants: Vec<String>,
+}
+
 #[derive(Deserialize, Default)]
 #[serde(default)]
 pub(crate) struct Isa {
     fields: Vec<Field>,
     modifiers: Vec<Modifier>,
+    sprs: Vec<Spr>,
     opcodes: Vec<Opcode>,
     mnemonics: Vec<Mnemonic>,
 }
 
+/// Expresses a list of variants, where an empty list means all of them.
+fn express_variants(variants: &[String]) -> TokenStream {
+    if variants.is_empty() {
+        return quote!(&Variant::ALL);
+    }
+    let variants = variants
+        .iter()
+        .map(|name| Ident::new(name, Span::call_site()));
+    quote!(&[#(Variant::#variants),*])
+}
+
 fn load_isa() -> Result<Isa> {
     let yaml_file = File::open("isa.yaml")?;
     let isa: Isa = serde_yaml::from_reader(yaml_file)?;
@@ -301,18 +402,21 @@ impl Isa {
 
         // Create functions.
         let mnemonic_fn = self.gen_mnemonic_fn()?;
+        let metadata_fns = self.gen_metadata_fns()?;
         let detect_fn = self.gen_opcode_detect()?;
 
         // Create final enum.
         let opcode_enum = quote! {
-            #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+            #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
             pub enum Opcode {
+                #[default]
                 Illegal = -1,
                 #enum_variants
             }
             #[allow(clippy::all)]
             impl Opcode {
                 #mnemonic_fn
+                #metadata_fns
                 #detect_fn
             }
         };
@@ -345,30 +449,160 @@ impl Isa {
         Ok(mnemonic_fn)
     }
 
+    fn gen_metadata_fns(&self) -> Result<TokenStream> {
+        let mut variants = Vec::new();
+        let mut descs = Vec::new();
+        let mut bitmasks = Vec::new();
+        let mut patterns = Vec::new();
+        let mut reserved = Vec::new();
+        let mut modifiers = Vec::new();
+        let mut forms = Vec::new();
+        let mut categories = Vec::new();
+        let mut reads = Vec::new();
+        let mut writes = Vec::new();
+        let mut availability = Vec::new();
+        for opcode in &self.opcodes {
+            if opcode.form.is_empty() || opcode.category.is_empty() {
+                return Err(format!("missing form or category for {}", opcode.name).into());
+            }
+            variants.push(opcode.variant_identifier()?);
+            descs.push(Literal::string(&opcode.desc));
+            bitmasks.push(LitInt::new(
+                &format!("{:#x}", opcode.bitmask),
+                Span::call_site(),
+            ));
+            patterns.push(LitInt::new(
+                &format!("{:#x}", opcode.pattern),
+                Span::call_site(),
+            ));
+            reserved.push(LitInt::new(
+                &format!("{:#x}", opcode.reserved),
+                Span::call_site(),
+            ));
+            let names = opcode
+                .modifiers
+                .iter()
+                .map(|name| Ident::new(name, Span::call_site()));
+            modifiers.push(quote!(&[#(Modifier::#names),*]));
+            forms.push(Ident::new(&opcode.form, Span::call_site()));
+            categories.push(Ident::new(&opcode.category, Span::call_site()));
+            let registers = |names: &[String]| {
+                let names = names.iter().map(|name| Ident::new(name, Span::call_site()));
+                quote!(&[#(Register::#names),*])
+            };
+            availability.push(express_variants(&opcode.variants));
+            reads.push(registers(&opcode.reads));
+            writes.push(registers(&opcode.writes));
+        }
+        let count = variants.len();
+        Ok(quote! {
+            /// All opcodes in the order they are defined, excluding `Illegal`.
+            pub const ALL: [Opcode; #count] = [#(Opcode::#variants),*];
+
+            pub(crate) fn _description(self) -> &'static str {
+                match self {
+                    Opcode::Illegal => "Illegal Instruction",
+                    #(Opcode::#variants => #descs,)*
+                }
+            }
+
+            pub(crate) fn _bitmask(self) -> u32 {
+                match self {
+                    Opcode::Illegal => 0,
+                    #(Opcode::#variants => #bitmasks,)*
+                }
+            }
+
+            pub(crate) fn _pattern(self) -> u32 {
+                match self {
+                    Opcode::Illegal => 0,
+                    #(Opcode::#variants => #patterns,)*
+                }
+            }
+
+            pub(crate) fn _reserved_bits(self) -> u32 {
+                match self {
+                    Opcode::Illegal => 0,
+                    #(Opcode::#variants => #reserved,)*
+                }
+            }
+
+            pub(crate) fn _modifiers(self) -> &'static [Modifier] {
+                match self {
+                    Opcode::Illegal => &[],
+                    #(Opcode::#variants => #modifiers,)*
+                }
+            }
+
+            pub(crate) fn _form(self) -> Option<Form> {
+                match self {
+                    Opcode::Illegal => None,
+                    #(Opcode::#variants => Some(Form::#forms),)*
+                }
+            }
+
+            pub(crate) fn _category(self) -> Option<Category> {
+                match self {
+                    Opcode::Illegal => None,
+                    #(Opcode::#variants => Some(Category::#categories),)*
+                }
+            }
+
+            pub(crate) fn _variants(self) -> &'static [Variant] {
+                match self {
+                    Opcode::Illegal => &[],
+                    #(Opcode::#variants => #availability,)*
+                }
+            }
+
+            pub(crate) fn _implicit_reads(self) -> &'static [Register] {
+                match self {
+                    Opcode::Illegal => &[],
+                    #(Opcode::#variants => #reads,)*
+                }
+            }
+
+            pub(crate) fn _implicit_writes(self) -> &'static [Register] {
+                match self {
+                    Opcode::Illegal => &[],
+                    #(Opcode::#variants => #writes,)*
+                }
+            }
+        })
+    }
+
+    fn gen_spr_names(&self) -> Result<TokenStream> {
+        let mut arms = Vec::new();
+        for spr in &self.sprs {
+            let number = spr.number;
+            let name = Literal::string(&spr.name);
+            let variants = express_variants(&spr.variants);
+            arms.push(quote! {
+                #number => (#name, #variants),
+            });
+        }
+        let arms = token_stream!(arms);
+        Ok(quote! {
+            impl SPR {
+                pub(crate) fn _name(self, variant: Variant) -> Option<&'static str> {
+                    let (name, variants): (&'static str, &'static [Variant]) = match self.0 {
+                        #arms
+                        _ => return None,
+                    };
+                    variants.contains(&variant).then_some(name)
+                }
+            }
+        })
+    }
+
     fn gen_opcode_detect(&self) -> Result<TokenStream> {
-        // Generate if chain.
-        let if_chain = self
-            .opcodes
-            .iter()
-            .map(|opcode| {
-                let bitmask_str = format!("{:>#8x}", opcode.bitmask);
-                let bitmask = LitInt::new(&bitmask_str, Span::call_site());
-                let pattern_str = format!("{:>#8x}", opcode.pattern);
-                let pattern = LitInt::new(&pattern_str, Span::call_site());
-                let identifier = opcode.variant_identifier()?;
-                Ok(quote! {
-                    if code & #bitmask == #pattern {
-                        return Opcode::#identifier;
-                    }
-                })
-            })
-            .try_collect::<TokenStream, Vec<TokenStream>, Error>()?;
-        let if_chain = token_stream!(if_chain);
+        // Generate decision tree, starting with the primary opcode.
+        let opcodes = self.opcodes.iter().collect::<Vec<_>>();
+        let tree = gen_decode_tree(&opcodes, 0)?;
         // Generate function.
         let func = quote! {
             pub(crate) fn _detect(code: u32) -> Self {
-                #if_chain
-                Opcode::Illegal
+                #tree
             }
         };
         Ok(func)
@@ -384,10 +618,43 @@ impl Isa {
         }
         let enum_variants = token_stream!(enum_variants);
 
+        // Size inline lists for the largest instruction.
+        let max_fields = self
+            .opcodes
+            .iter()
+            .map(|opcode| {
+                opcode
+                    .args
+                    .len()
+                    .max(opcode.defs.len())
+                    .max(opcode.uses.len())
+            })
+            .max()
+            .unwrap_or(0);
+        let max_args = self
+            .opcodes
+            .iter()
+            .map(|opcode| opcode.args.len())
+            .chain(self.mnemonics.iter().map(|mnemonic| mnemonic.args.len()))
+            .max()
+            .unwrap_or(0);
+        let max_fields = Literal::usize_unsuffixed(max_fields);
+        let max_args = Literal::usize_unsuffixed(max_args);
+
         // Create final enum.
         let field_enum = quote! {
+            /// Fields of an instruction, stored inline.
+            pub type Fields = InlineVec<Field, #max_fields>;
+
+            /// Arguments of a simplified instruction, stored inline.
+            pub type Arguments = InlineVec<Argument, #max_args>;
+
+            /// Access modes of the arguments of a simplified instruction, stored inline.
+            pub type Accesses = InlineVec<Option<Access>, #max_args>;
+
             #[allow(non_camel_case_types)]
             #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub enum Field {
                 #enum_variants
             }
@@ -395,6 +662,39 @@ impl Isa {
         Ok(field_enum)
     }
 
+    fn gen_modifier_enum(&self) -> Result<TokenStream> {
+        let enum_variants = self
+            .modifiers
+            .iter()
+            .map(|modifier| Ident::new(&modifier.name, Span::call_site()));
+        let enum_variants = enum_variants.collect::<Vec<_>>();
+        let suffixes = self
+            .modifiers
+            .iter()
+            .map(|modifier| LitChar::new(modifier.suffix, Span::call_site()));
+        let count = Literal::usize_unsuffixed(self.modifiers.len());
+        Ok(quote! {
+            #[allow(non_camel_case_types)]
+            #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+            pub enum Modifier {
+                #(#enum_variants,)*
+            }
+
+            impl Modifier {
+                /// All modifiers, in the order their suffixes are written.
+                pub const ALL: [Modifier; #count] = [#(Modifier::#enum_variants),*];
+
+                /// Returns the mnemonic suffix of the modifier.
+                pub fn suffix(self) -> char {
+                    match self {
+                        #(Modifier::#enum_variants => #suffixes,)*
+                    }
+                }
+            }
+        })
+    }
+
     fn gen_field_argument(&self) -> Result<TokenStream> {
         let mut match_arms = Vec::new();
         for field in &self.fields {
@@ -435,13 +735,34 @@ impl Isa {
         })
     }
 
+    fn gen_field_bits(&self) -> Result<TokenStream> {
+        let mut match_arms = Vec::new();
+        for field in &self.fields {
+            if let (Some(variant), Some(_)) = (field.variant_identifier(), field.arg.as_ref()) {
+                let bits = field.field_bits();
+                match_arms.push(quote! { Field::#variant(_) => Some(#bits), });
+            }
+        }
+        let match_arms = token_stream!(match_arms);
+        Ok(quote! {
+            pub(crate) fn field_bits(&self) -> Option<FieldBits> {
+                match self {
+                    #match_arms
+                    _ => None,
+                }
+            }
+        })
+    }
+
     fn gen_field_impl(&self) -> Result<TokenStream> {
         let field_argument = self.gen_field_argument()?;
         let field_name = self.gen_field_name()?;
+        let field_bits = self.gen_field_bits()?;
         Ok(quote! {
             impl Field {
                 #field_argument
                 #field_name
+                #field_bits
             }
         })
     }
@@ -461,15 +782,18 @@ impl Isa {
         for simple in &self.mnemonics {
             mnemonics_by_opcode
                 .entry(&simple.opcode)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(simple)
         }
         // Generate match arms for each opcode.
         let mut field_match_arms = Vec::new();
+        let mut access_match_arms = Vec::new();
         let mut def_match_arms = Vec::new();
         let mut use_match_arms = Vec::new();
         let mut suffix_match_arms = Vec::new();
+        let mut encode_match_arms = Vec::new();
         let mut simplified_ins_match_arms = Vec::new();
+        let mut invalid_match_arms = Vec::new();
         for opcode in &self.opcodes {
             // Generate fields of opcode.
             let mut fields = Vec::new();
@@ -484,7 +808,90 @@ impl Isa {
             // Emit match arm.
             let ident = opcode.variant_identifier()?;
             field_match_arms.push(quote! {
-                Opcode::#ident => vec![#fields],
+                Opcode::#ident => Fields::from_slice(&[#fields]),
+            });
+            // Generate access modes of arguments, as listed by `SimplifiedIns::basic_form`.
+            let accesses = opcode
+                .args
+                .iter()
+                .map(|arg| &field_by_name[arg])
+                .filter(|field| field.arg.is_some())
+                .map(|field| opcode.express_access(field));
+            access_match_arms.push(quote! {
+                Opcode::#ident => Accesses::from_slice(&[#(#accesses),*]),
+            });
+
+            // Generate encoder.
+            let pattern = LitInt::new(&format!("{:#x}", opcode.pattern), Span::call_site());
+            let mut arg_bits = Vec::new();
+            for arg in &opcode.args {
+                arg_bits.push(field_by_name[arg].field_bits());
+            }
+            let mut nonzero_checks = Vec::new();
+            for arg in &opcode.nonzero {
+                let field: &Field = field_by_name
+                    .get(arg)
+                    .ok_or_else(|| Error::from(format!("undefined field {}", arg)))?;
+                let bits = field.field_bits();
+                let name = LitStr::new(&field.name, Span::call_site());
+                nonzero_checks.push(quote! {
+                    if #bits.extract(code) == 0 {
+                        return Err(EncodeError::ZeroRegister(#name));
+                    }
+                });
+            }
+            let nonzero_checks = token_stream!(nonzero_checks);
+
+            // Generate form validation.
+            let mut invalid_conditions = Vec::new();
+            for arg in &opcode.nonzero {
+                let value = field_by_name[arg].express_value_self();
+                invalid_conditions.push(quote!(#value == 0));
+            }
+            for condition in &opcode.invalid {
+                invalid_conditions.push(compile_mnemonic_condition(&field_by_name, condition)?);
+            }
+            if !invalid_conditions.is_empty() {
+                invalid_match_arms.push(quote! {
+                    Opcode::#ident => #(#invalid_conditions)||*,
+                });
+            }
+            let mut modifier_arms = Vec::new();
+            for mod_name in &opcode.modifiers {
+                let modifier: &Modifier = modifier_by_name
+                    .get(mod_name)
+                    .ok_or_else(|| Error::from(format!("undefined modifier {}", mod_name)))?;
+                let variant = Ident::new(&modifier.name, Span::call_site());
+                let (guards, assigns) = modifier.gen_apply(&field_by_name)?;
+                let guard = if guards.is_empty() {
+                    TokenStream::new()
+                } else {
+                    quote! {
+                        if !(#(#guards)&&*) {
+                            return Err(EncodeError::InvalidModifier(*modifier));
+                        }
+                    }
+                };
+                modifier_arms.push(quote! {
+                    Modifier::#variant => {
+                        #guard
+                        #assigns
+                    }
+                });
+            }
+            let modifier_arms = token_stream!(modifier_arms);
+            encode_match_arms.push(quote! {
+                Opcode::#ident => {
+                    let mut code = encode_fields(#pattern, fields, [#(#arg_bits),*])?;
+                    #nonzero_checks
+                    for modifier in modifiers {
+                        match modifier {
+                            #modifier_arms
+                            _ => return Err(EncodeError::InvalidModifier(*modifier)),
+                        }
+                    }
+                    Ok(code)
+                },
             });
 
             // Generate modifiers.
@@ -505,7 +912,7 @@ impl Isa {
             let defs = token_stream!(defs);
             let ident = opcode.variant_identifier()?;
             def_match_arms.push(quote! {
-                Opcode::#ident => vec![#defs],
+                Opcode::#ident => Fields::from_slice(&[#defs]),
             });
 
             // Generate uses.
@@ -542,7 +949,7 @@ impl Isa {
             let special_uses = token_stream!(special_uses);
             use_match_arms.push(quote! {
                 Opcode::#ident => {
-                    let mut uses = vec![#uses];
+                    let mut uses = Fields::from_slice(&[#uses]);
                     #special_uses
                     uses
                 },
@@ -553,7 +960,8 @@ impl Isa {
                 let mut simplified_conditions = Vec::new();
                 for mnemonic in mnemonics {
                     if mnemonic.condition.is_empty() {
-                        continue; // TODO else branch
+                        // Assembler-only alias.
+                        continue;
                     }
                     simplified_conditions.push(quote!(if));
                     simplified_conditions.push(compile_mnemonic_condition(
@@ -567,6 +975,7 @@ impl Isa {
                     let suffix = express_suffix(&modifier_by_name, &field_by_name, modifiers)?;
                     // Extract arguments.
                     let mut args = Vec::new();
+                    let mut accesses = Vec::new();
                     for arg in &mnemonic.args {
                         let (field_name, expression) = arg.split_once('=').unwrap_or((arg, arg));
                         let field = field_by_name
@@ -575,6 +984,7 @@ impl Isa {
                         let variant = Ident::new(field.arg.as_ref().unwrap(), Span::call_site());
                         let value = compile_mnemonic_condition(&field_by_name, expression)?;
                         args.push(quote!(Argument::#variant(#variant((#value) as _)),));
+                        accesses.push(opcode.express_access(field));
                     }
                     let args = token_stream!(args);
                     simplified_conditions.push(quote! {
@@ -582,7 +992,8 @@ impl Isa {
                             return SimplifiedIns {
                                 mnemonic: #mnemonic_lit,
                                 suffix: #suffix,
-                                args: vec![#args],
+                                args: Arguments::from_slice(&[#args]),
+                                access: Accesses::from_slice(&[#(#accesses),*]),
                                 ins: self,
                             };
                         }
@@ -597,12 +1008,33 @@ impl Isa {
             }
         }
         let field_match_arms = token_stream!(field_match_arms);
+        let access_match_arms = token_stream!(access_match_arms);
         let def_match_arms = token_stream!(def_match_arms);
         let use_match_arms = token_stream!(use_match_arms);
         let suffix_match_arms = token_stream!(suffix_match_arms);
+        let encode_match_arms = token_stream!(encode_match_arms);
         let simplified_ins_match_arms = token_stream!(simplified_ins_match_arms);
+        let invalid_match_arms = token_stream!(invalid_match_arms);
         let field_accessors =
             TokenStream::from_iter(self.fields.iter().map(|field| field.construct_accessor()));
+        // Typed accessors check for fields named by arguments, defs or uses of the opcode.
+        let mut typed_accessors = Vec::new();
+        for field in &self.fields {
+            let mut opcodes = Vec::new();
+            for opcode in &self.opcodes {
+                let has_field = opcode
+                    .args
+                    .iter()
+                    .chain(&opcode.defs)
+                    .chain(&opcode.uses)
+                    .any(|name| name.strip_suffix(".nz").unwrap_or(name) == field.name);
+                if has_field {
+                    opcodes.push(opcode.variant_identifier()?);
+                }
+            }
+            typed_accessors.push(field.construct_typed_accessor(&opcodes));
+        }
+        let typed_accessors = token_stream!(typed_accessors);
         let modifiers: Vec<TokenStream> = self
             .modifiers
             .iter()
@@ -613,34 +1045,59 @@ impl Isa {
         let ins_impl = quote! {
             #[allow(clippy::all, unused_mut)]
             impl Ins {
-                pub(crate) fn _fields(&self) -> Vec<Field> {
+                pub(crate) fn _fields(&self) -> Fields {
                     match self.op {
-                        Opcode::Illegal => vec![],
+                        Opcode::Illegal => Fields::new(),
                         #field_match_arms
                     }
                 }
 
-                pub(crate) fn _defs(&self) -> Vec<Field> {
+                pub(crate) fn _accesses(&self) -> Accesses {
+                    match self.op {
+                        Opcode::Illegal => Accesses::new(),
+                        #access_match_arms
+                    }
+                }
+
+                pub(crate) fn _encode(
+                    op: Opcode,
+                    fields: &[Field],
+                    modifiers: &[Modifier],
+                ) -> Result<u32, EncodeError> {
+                    match op {
+                        Opcode::Illegal => Err(EncodeError::IllegalOpcode),
+                        #encode_match_arms
+                    }
+                }
+
+                pub(crate) fn _defs(&self) -> Fields {
                     match self.op {
-                        Opcode::Illegal => vec![],
+                        Opcode::Illegal => Fields::new(),
                         #def_match_arms
                     }
                 }
 
-                pub(crate) fn _uses(&self) -> Vec<Field> {
+                pub(crate) fn _uses(&self) -> Fields {
                     match self.op {
-                        Opcode::Illegal => vec![],
+                        Opcode::Illegal => Fields::new(),
                         #use_match_arms
                     }
                 }
 
-                pub(crate) fn _suffix(&self) -> String {
+                pub(crate) fn _suffix(&self) -> Modifiers {
                     match self.op {
-                        Opcode::Illegal => String::new(),
+                        Opcode::Illegal => Modifiers::empty(),
                         #suffix_match_arms
                     }
                 }
 
+                pub(crate) fn _invalid_form(&self) -> bool {
+                    match self.op {
+                        #invalid_match_arms
+                        _ => false,
+                    }
+                }
+
                 pub(crate) fn _simplified(self) -> SimplifiedIns {
                     match self.op {
                         #simplified_ins_match_arms
@@ -653,12 +1110,86 @@ impl Isa {
             impl Ins {
                 #field_accessors
                 #modifier_accessors
+                #typed_accessors
             }
         };
         Ok(ins_impl)
     }
 }
 
+/// Generates a decoder for the given opcodes, which agree on all bits of `known_mask`.
+///
+/// Dispatches on the bits tested by every opcode with a `match`, recursing into each arm.
+/// Opcodes that cannot be told apart that way are tested in order, like the definition list.
+fn gen_decode_tree(opcodes: &[&Opcode], known_mask: u32) -> Result<TokenStream> {
+    let common_mask = opcodes
+        .iter()
+        .fold(!known_mask, |mask, opcode| mask & opcode.bitmask);
+    if opcodes.len() <= 1 || common_mask == 0 {
+        let mut if_chain = Vec::new();
+        for opcode in opcodes {
+            let identifier = opcode.variant_identifier()?;
+            let mask = opcode.bitmask & !known_mask;
+            if mask == 0 {
+                // No bits left to test, later opcodes are unreachable.
+                return Ok(quote!(#(#if_chain)* { Opcode::#identifier }));
+            }
+            let bitmask = LitInt::new(&format!("{:#x}", mask), Span::call_site());
+            let pattern = LitInt::new(&format!("{:#x}", opcode.pattern & mask), Span::call_site());
+            if_chain.push(quote! {
+                if code & #bitmask == #pattern {
+                    Opcode::#identifier
+                } else
+            });
+        }
+        return Ok(quote!(#(#if_chain)* { Opcode::Illegal }));
+    }
+
+    // Extract the common bits as a number if they are contiguous.
+    let shift = common_mask.trailing_zeros();
+    let contiguous =
+        (common_mask >> shift).count_ones() == 32 - (common_mask >> shift).leading_zeros();
+    let (selector, key_shift) = if contiguous {
+        let mask = LitInt::new(&format!("{:#x}", common_mask >> shift), Span::call_site());
+        let shift_lit = Literal::u32_unsuffixed(shift);
+        if common_mask.leading_zeros() == 0 {
+            (quote!(code >> #shift_lit), shift)
+        } else {
+            (quote!((code >> #shift_lit) & #mask), shift)
+        }
+    } else {
+        let mask = LitInt::new(&format!("{:#x}", common_mask), Span::call_site());
+        (quote!(code & #mask), 0)
+    };
+
+    // Group opcodes by their value of the common bits, keeping definition order.
+    let mut groups = Vec::<(u32, Vec<&Opcode>)>::new();
+    for &opcode in opcodes {
+        let key = (opcode.pattern & common_mask) >> key_shift;
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(opcode),
+            None => groups.push((key, vec![opcode])),
+        }
+    }
+    groups.sort_by_key(|(key, _)| *key);
+    let mut match_arms = Vec::new();
+    for (key, group) in groups {
+        let key = if contiguous {
+            Literal::u32_unsuffixed(key)
+        } else {
+            Literal::from_str(&format!("{:#x}", key))?
+        };
+        let subtree = gen_decode_tree(&group, known_mask | common_mask)?;
+        match_arms.push(quote!(#key => #subtree,));
+    }
+    Ok(quote! {
+        match #selector {
+            #(#match_arms)*
+            _ => Opcode::Illegal,
+        }
+    })
+}
+
 /// Converts the given key into an identifier.
 fn to_rust_ident(prefix: &str, key: &str) -> TokenTree {
     TokenTree::Ident(Ident::new(
@@ -744,25 +1275,25 @@ fn express_suffix(
     modifiers: &[String],
 ) -> Result<TokenStream> {
     Ok(if modifiers.is_empty() {
-        quote!(String::new())
+        quote!(Modifiers::empty())
     } else {
         let mut chars = Vec::new();
         for mod_name in modifiers {
             let modifier: &Modifier = modifier_by_name
                 .get(mod_name)
                 .ok_or_else(|| Error::from(format!("undefined modifier {}", mod_name)))?;
-            let lit_char = LitChar::new(modifier.suffix, Span::call_site());
+            let variant = Ident::new(&modifier.name, Span::call_site());
             let modifier_bit = modifier.express_value_self(field_by_name)?;
             chars.push(quote! {
                 if #modifier_bit {
-                    s.push(#lit_char);
+                    s.insert(Modifier::#variant);
                 }
             });
         }
         let chars = token_stream!(chars);
         quote!({
             {
-                let mut s = String::with_capacity(4);
+                let mut s = Modifiers::empty();
                 #chars
                 s
             }